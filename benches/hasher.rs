@@ -3,9 +3,11 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use imdl::bench::{Bench, HasherBench};
 
 fn bench(c: &mut Criterion) {
-  let bench = HasherBench::init();
+  for backend in HasherBench::BACKENDS {
+    let bench = HasherBench::init().with_backend(backend);
 
-  c.bench_function(&bench.name(), |b| b.iter(|| bench.iter()));
+    c.bench_function(&bench.name(), |b| b.iter(|| bench.iter()));
+  }
 }
 
 criterion_group!(benches, bench);
@@ -0,0 +1,455 @@
+use crate::common::*;
+
+const ZIP_CENTRAL_HEADER_SIGNATURE: u64 = 0x0201_4b50;
+const ZIP_END_SIGNATURE: u64 = 0x0605_4b50;
+const ZIP_END_SIZE: u64 = 22;
+const ZIP_LOCAL_HEADER_SIGNATURE: u64 = 0x0403_4b50;
+const ZIP_LOCAL_HEADER_SIZE: usize = 30;
+const ZIP_ZIP64_END_SIGNATURE: u64 = 0x0606_4b50;
+const ZIP_ZIP64_LOCATOR_SIGNATURE: u64 = 0x0706_4b50;
+
+/// A tar or zip archive, whose members can be read without extracting it.
+#[derive(Debug)]
+pub(crate) struct Archive {
+  path: PathBuf,
+  members: HashMap<String, Member>,
+}
+
+/// The location of a regular file in an archive.
+#[derive(Debug, PartialEq)]
+struct Member {
+  /// Offset of the file's contents in tar archives, and of its local header
+  /// in zip archives.
+  offset: u64,
+  size: u64,
+  storage: Storage,
+}
+
+#[derive(Debug, PartialEq)]
+enum Storage {
+  Tar,
+  Zip {
+    compressed_size: u64,
+    encrypted: bool,
+    method: u64,
+  },
+}
+
+impl Archive {
+  /// Index the members of the archive at `path`. Only the headers of a tar
+  /// archive, or the central directory of a zip archive, are read.
+  pub(crate) fn open(path: &Path, format: ArchiveFormat) -> Result<Self, Error> {
+    let file = File::open(path).context(error::Filesystem { path })?;
+
+    let members = match format {
+      ArchiveFormat::Tar => Self::index_tar(file),
+      ArchiveFormat::Zip => Self::index_zip(file),
+    }
+    .context(error::Filesystem { path })?;
+
+    Ok(Self {
+      path: path.to_owned(),
+      members,
+    })
+  }
+
+  /// Size of the member at `path`, or `None` if there is no such member.
+  pub(crate) fn size(&self, path: &str) -> Option<Bytes> {
+    self.members.get(path).map(|member| Bytes(member.size))
+  }
+
+  /// Whether the archive contains members inside the directory at `path`.
+  pub(crate) fn contains_dir(&self, path: &str) -> bool {
+    let prefix = format!("{}/", path);
+    self
+      .members
+      .keys()
+      .any(|member| member.starts_with(&prefix))
+  }
+
+  /// Read the contents of the member at `path`.
+  pub(crate) fn open_member(&self, path: &str) -> io::Result<Box<dyn Read>> {
+    let member = self.members.get(path).ok_or_else(|| {
+      io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Archive has no member `{}`", path),
+      )
+    })?;
+
+    let mut file = File::open(&self.path)?;
+
+    file.seek(SeekFrom::Start(member.offset))?;
+
+    match member.storage {
+      Storage::Tar => Ok(Box::new(BufReader::new(file.take(member.size)))),
+      Storage::Zip {
+        compressed_size,
+        encrypted,
+        method,
+      } => {
+        if encrypted {
+          return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Encrypted zip archive members are not supported",
+          ));
+        }
+
+        let mut header = [0; ZIP_LOCAL_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+
+        if Self::le(&header, 0, 4)? != ZIP_LOCAL_HEADER_SIGNATURE {
+          return Err(Self::invalid("bad local header signature"));
+        }
+
+        let skip = Self::le(&header, 26, 2)? + Self::le(&header, 28, 2)?;
+        file.seek(SeekFrom::Current(
+          i64::try_from(skip).invariant_unwrap("two u16 fields fit in i64"),
+        ))?;
+
+        let data = BufReader::new(file.take(compressed_size));
+
+        match method {
+          0 => Ok(Box::new(data)),
+          8 => Ok(Box::new(Inflater::new(data).take(member.size))),
+          _ => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Unsupported zip compression method {}", method),
+          )),
+        }
+      }
+    }
+  }
+
+  fn index_tar(file: File) -> io::Result<HashMap<String, Member>> {
+    let mut input = BufReader::new(file);
+    let mut reader = TarReader::file(&mut input);
+    let mut members = HashMap::new();
+
+    while let Some(entry) = reader.next_entry()? {
+      if entry.file {
+        members.insert(
+          Self::normalize(&String::from_utf8_lossy(&entry.path)),
+          Member {
+            offset: reader.position(),
+            size: entry.size,
+            storage: Storage::Tar,
+          },
+        );
+      }
+    }
+
+    Ok(members)
+  }
+
+  fn index_zip(mut file: File) -> io::Result<HashMap<String, Member>> {
+    let length = file.seek(SeekFrom::End(0))?;
+
+    // The end of central directory record is followed by a comment of up to
+    // 65535 bytes.
+    let tail_start = length.saturating_sub(ZIP_END_SIZE + 0xffff);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(tail_start))?;
+    file.read_to_end(&mut tail)?;
+
+    let end = (0..tail.len().saturating_sub(3))
+      .rev()
+      .find(|i| Self::le(&tail, *i, 4).ok() == Some(ZIP_END_SIGNATURE))
+      .ok_or_else(|| Self::invalid("end of central directory record not found"))?;
+
+    let mut directory_size = Self::le(&tail, end + 12, 4)?;
+    let mut directory_offset = Self::le(&tail, end + 16, 4)?;
+
+    if directory_size == 0xffff_ffff || directory_offset == 0xffff_ffff {
+      let locator = end
+        .checked_sub(20)
+        .ok_or_else(|| Self::invalid("zip64 end of central directory locator not found"))?;
+
+      if Self::le(&tail, locator, 4)? != ZIP_ZIP64_LOCATOR_SIGNATURE {
+        return Err(Self::invalid(
+          "zip64 end of central directory locator not found",
+        ));
+      }
+
+      let mut record = [0; 56];
+      file.seek(SeekFrom::Start(Self::le(&tail, locator + 8, 8)?))?;
+      file.read_exact(&mut record)?;
+
+      if Self::le(&record, 0, 4)? != ZIP_ZIP64_END_SIGNATURE {
+        return Err(Self::invalid(
+          "bad zip64 end of central directory signature",
+        ));
+      }
+
+      directory_size = Self::le(&record, 40, 8)?;
+      directory_offset = Self::le(&record, 48, 8)?;
+    }
+
+    let mut directory = Vec::new();
+    file.seek(SeekFrom::Start(directory_offset))?;
+    (&mut file)
+      .take(directory_size)
+      .read_to_end(&mut directory)?;
+
+    let mut members = HashMap::new();
+    let mut i = 0;
+
+    while i < directory.len() {
+      if Self::le(&directory, i, 4)? != ZIP_CENTRAL_HEADER_SIGNATURE {
+        return Err(Self::invalid("bad central directory header signature"));
+      }
+
+      let flags = Self::le(&directory, i + 8, 2)?;
+      let method = Self::le(&directory, i + 10, 2)?;
+      let mut compressed_size = Self::le(&directory, i + 20, 4)?;
+      let mut size = Self::le(&directory, i + 24, 4)?;
+      let name_length = Self::length(&directory, i + 28)?;
+      let extra_length = Self::length(&directory, i + 30)?;
+      let comment_length = Self::length(&directory, i + 32)?;
+      let mut offset = Self::le(&directory, i + 42, 4)?;
+
+      let name_start = i + 46;
+      let extra_start = name_start + name_length;
+      let extra_end = extra_start + extra_length;
+
+      let name = directory
+        .get(name_start..extra_start)
+        .ok_or_else(|| Self::invalid("truncated central directory"))?;
+
+      let extra = directory
+        .get(extra_start..extra_end)
+        .ok_or_else(|| Self::invalid("truncated central directory"))?;
+
+      // Sizes and offsets that don't fit in 32 bits are stored in the zip64
+      // extended information extra field, in this order, if they are present.
+      let mut j = 0;
+      while j + 4 <= extra.len() {
+        let id = Self::le(extra, j, 2)?;
+        let length = Self::length(extra, j + 2)?;
+
+        if id == 1 {
+          let mut k = j + 4;
+          for field in &mut [&mut size, &mut compressed_size, &mut offset] {
+            if **field == 0xffff_ffff {
+              **field = Self::le(extra, k, 8)?;
+              k += 8;
+            }
+          }
+        }
+
+        j += 4 + length;
+      }
+
+      let name = String::from_utf8_lossy(name);
+
+      if !name.ends_with('/') {
+        members.insert(
+          Self::normalize(&name),
+          Member {
+            offset,
+            size,
+            storage: Storage::Zip {
+              compressed_size,
+              encrypted: flags & 1 != 0,
+              method,
+            },
+          },
+        );
+      }
+
+      i = extra_end + comment_length;
+    }
+
+    Ok(members)
+  }
+
+  /// Remove empty and `.` components from `path`, and a leading `/`.
+  fn normalize(path: &str) -> String {
+    path
+      .split('/')
+      .filter(|component| !component.is_empty() && *component != ".")
+      .collect::<Vec<&str>>()
+      .join("/")
+  }
+
+  /// Read a little-endian integer of `length` bytes from `bytes` at `offset`.
+  fn le(bytes: &[u8], offset: usize, length: usize) -> io::Result<u64> {
+    let field = bytes
+      .get(offset..offset + length)
+      .ok_or_else(|| Self::invalid("unexpected end of data"))?;
+
+    Ok(
+      field
+        .iter()
+        .rev()
+        .fold(0, |value, byte| value << 8 | u64::from(*byte)),
+    )
+  }
+
+  /// Read a little-endian 16-bit length from `bytes` at `offset`.
+  fn length(bytes: &[u8], offset: usize) -> io::Result<usize> {
+    let length = Self::le(bytes, offset, 2)?;
+    Ok(usize::try_from(length).invariant_unwrap("16-bit length fits in usize"))
+  }
+
+  fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!("Invalid zip archive: {}", message),
+    )
+  }
+
+  /// Build a zip archive containing `entries`, each given as a path,
+  /// contents, and whether the contents should be compressed.
+  #[cfg(test)]
+  pub(crate) fn test_zip(entries: &[(&str, &[u8], bool)]) -> Vec<u8> {
+    fn push(bytes: &mut Vec<u8>, value: u64, length: usize) {
+      bytes.extend_from_slice(&value.to_le_bytes()[..length]);
+    }
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+
+    for (path, contents, deflate) in entries {
+      let data = if *deflate {
+        miniz_oxide::deflate::compress_to_vec(contents, 6)
+      } else {
+        contents.to_vec()
+      };
+
+      let method = if *deflate { 8 } else { 0 };
+      let crc32 = crc32fast::hash(contents);
+      let offset = archive.len().into_u64();
+
+      for (bytes, signature) in &mut [
+        (&mut archive, ZIP_LOCAL_HEADER_SIGNATURE),
+        (&mut directory, ZIP_CENTRAL_HEADER_SIGNATURE),
+      ] {
+        let central = *signature == ZIP_CENTRAL_HEADER_SIGNATURE;
+        push(bytes, *signature, 4);
+        if central {
+          push(bytes, 20, 2);
+        }
+        push(bytes, 20, 2);
+        push(bytes, 0, 2);
+        push(bytes, method, 2);
+        push(bytes, 0, 4);
+        push(bytes, crc32.into(), 4);
+        push(bytes, data.len().into_u64(), 4);
+        push(bytes, contents.len().into_u64(), 4);
+        push(bytes, path.len().into_u64(), 2);
+        push(bytes, 0, 2);
+        if central {
+          push(bytes, 0, 2);
+          push(bytes, 0, 2);
+          push(bytes, 0, 2);
+          push(bytes, 0, 4);
+          push(bytes, offset, 4);
+        }
+        bytes.extend_from_slice(path.as_bytes());
+      }
+
+      archive.extend_from_slice(&data);
+    }
+
+    let directory_offset = archive.len().into_u64();
+    archive.extend_from_slice(&directory);
+
+    push(&mut archive, ZIP_END_SIGNATURE, 4);
+    push(&mut archive, 0, 4);
+    push(&mut archive, entries.len().into_u64(), 2);
+    push(&mut archive, entries.len().into_u64(), 2);
+    push(&mut archive, directory.len().into_u64(), 4);
+    push(&mut archive, directory_offset, 4);
+    push(&mut archive, 0, 2);
+
+    archive
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn archive(format: ArchiveFormat, bytes: &[u8]) -> (TempDir, Archive) {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join(format!("archive.{}", format));
+    fs::write(&path, bytes).unwrap();
+    let archive = Archive::open(&path, format).unwrap();
+    (tempdir, archive)
+  }
+
+  fn read(archive: &Archive, path: &str) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    archive.open_member(path)?.read_to_end(&mut contents)?;
+    Ok(contents)
+  }
+
+  #[test]
+  fn tar() {
+    let (_tempdir, archive) = archive(
+      ArchiveFormat::Tar,
+      &TarReader::test_archive(&[
+        ("./foo/", b'5', b""),
+        ("./foo/a", b'0', &[1; 1000]),
+        ("./foo/b", b'0', b"b"),
+        ("./foo/link", b'2', b""),
+      ]),
+    );
+
+    assert_eq!(read(&archive, "foo/a").unwrap(), vec![1; 1000]);
+    assert_eq!(read(&archive, "foo/b").unwrap(), b"b");
+    assert_eq!(archive.size("foo/a"), Some(Bytes(1000)));
+    assert_eq!(archive.size("foo/link"), None);
+    assert_eq!(archive.size("foo"), None);
+    assert!(archive.contains_dir("foo"));
+    assert!(!archive.contains_dir("fo"));
+    assert_eq!(
+      read(&archive, "foo/c").unwrap_err().kind(),
+      io::ErrorKind::NotFound
+    );
+  }
+
+  #[test]
+  fn zip() {
+    let (_tempdir, archive) = archive(
+      ArchiveFormat::Zip,
+      &Archive::test_zip(&[
+        ("foo/", b"", false),
+        ("foo/a", &[1; 1000], true),
+        ("foo/b", b"b", false),
+      ]),
+    );
+
+    assert_eq!(read(&archive, "foo/a").unwrap(), vec![1; 1000]);
+    assert_eq!(read(&archive, "foo/b").unwrap(), b"b");
+    assert_eq!(archive.size("foo/a"), Some(Bytes(1000)));
+    assert_eq!(archive.size("foo"), None);
+    assert!(archive.contains_dir("foo"));
+  }
+
+  #[test]
+  fn zip_invalid() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("archive.zip");
+    fs::write(&path, "foo").unwrap();
+
+    assert_matches!(
+      Archive::open(&path, ArchiveFormat::Zip),
+      Err(Error::Filesystem { source, .. }) if source.kind() == io::ErrorKind::InvalidData
+    );
+  }
+
+  #[test]
+  fn normalize() {
+    assert_eq!(Archive::normalize("./a//b/"), "a/b");
+    assert_eq!(Archive::normalize("/a"), "a");
+  }
+
+  #[test]
+  fn le() {
+    assert_eq!(Archive::le(&[1, 2, 3], 1, 2).unwrap(), 0x0302);
+    assert!(Archive::le(&[1, 2, 3], 2, 2).is_err());
+  }
+}
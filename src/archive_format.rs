@@ -0,0 +1,51 @@
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, EnumVariantNames, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum ArchiveFormat {
+  Tar,
+  Zip,
+}
+
+impl ArchiveFormat {
+  /// Format of the archive at `path`, as determined by its extension.
+  pub(crate) fn from_path(path: &Path) -> Option<Self> {
+    path.extension()?.to_str()?.to_lowercase().parse().ok()
+  }
+
+  pub(crate) fn name(self) -> &'static str {
+    self.into()
+  }
+}
+
+impl Display for ArchiveFormat {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn variants() {
+    assert_eq!(ArchiveFormat::VARIANTS, &["tar", "zip"]);
+  }
+
+  #[test]
+  fn from_path() {
+    assert_eq!(
+      ArchiveFormat::from_path(Path::new("foo.tar")),
+      Some(ArchiveFormat::Tar)
+    );
+    assert_eq!(
+      ArchiveFormat::from_path(Path::new("foo/bar.ZIP")),
+      Some(ArchiveFormat::Zip)
+    );
+    assert_eq!(ArchiveFormat::from_path(Path::new("foo.tar.gz")), None);
+    assert_eq!(ArchiveFormat::from_path(Path::new("tar")), None);
+  }
+}
@@ -16,12 +16,30 @@ pub trait Bench {
 }
 
 pub struct HasherBench {
+  backend: IoBackend,
   tempfile: NamedTempFile,
 }
 
+impl HasherBench {
+  /// Names of the I/O backends that files can be hashed with.
+  pub const BACKENDS: &'static [&'static str] = IoBackend::VARIANTS;
+
+  /// Hash the tempfile with the I/O backend named `backend`.
+  pub fn with_backend(self, backend: &str) -> Self {
+    Self {
+      backend: backend.parse().unwrap(),
+      ..self
+    }
+  }
+}
+
 impl Bench for HasherBench {
   fn name(&self) -> String {
-    format!("bench::HasherBench: {} tempfile", Bytes(TEMPFILE_BYTES))
+    format!(
+      "bench::HasherBench: {} tempfile, {} I/O",
+      Bytes(TEMPFILE_BYTES),
+      self.backend
+    )
   }
 
   fn init() -> Self {
@@ -46,7 +64,10 @@ impl Bench for HasherBench {
       writer.flush().unwrap();
     }
 
-    Self { tempfile }
+    Self {
+      backend: IoBackend::default(),
+      tempfile,
+    }
   }
 
   fn iter(&self) {
@@ -55,8 +76,9 @@ impl Bench for HasherBench {
       Bytes::from(TEMPFILE_BYTES),
     );
 
-    let hasher = Hasher::new(false, 16 << 10, None);
+    let hasher =
+      Hasher::new(false, 16 << 10, None).reader(FileReader::new(self.backend, None).unwrap());
 
-    let _result = hasher.hash_files(&files).unwrap();
+    let _result = hasher.hash_files(files, "bench").unwrap();
   }
 }
@@ -0,0 +1,532 @@
+use crate::common::*;
+
+/// A bencoded value. Dictionaries are stored sorted by key, so re-encoding a
+/// value always produces canonical bencode.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Bencode {
+  Integer(i64),
+  String(Vec<u8>),
+  List(Vec<Bencode>),
+  Dict(BTreeMap<Vec<u8>, Bencode>),
+}
+
+/// Key used to represent byte strings that are not valid UTF-8 as JSON
+/// objects, with the bytes encoded as hexadecimal.
+const JSON_BYTES_KEY: &str = "$bytes";
+
+const JSON_BYTES_KEY_PREFIX: &str = "$bytes:";
+
+impl Bencode {
+  pub(crate) fn from_input(input: &Input) -> Result<Bencode, Error> {
+    BencodeParser::new(input.data())
+      .parse()
+      .context(error::BencodeDecode {
+        input: input.source().clone(),
+      })
+  }
+
+  /// Get the values at `path`. Paths without `*` select at most one value.
+  pub(crate) fn get(&self, path: &KeyPath) -> Vec<&Bencode> {
+    let mut values = vec![self];
+
+    for component in path.components() {
+      values = values
+        .into_iter()
+        .flat_map(|value| value.children(component))
+        .collect();
+    }
+
+    values
+  }
+
+  fn children(&self, component: &KeyPathComponent) -> Vec<&Bencode> {
+    match (self, component) {
+      (Self::Dict(dict), KeyPathComponent::Key(key)) => {
+        dict.get(key.as_bytes()).into_iter().collect()
+      }
+      (Self::List(list), KeyPathComponent::Key(key)) => key
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| list.get(index))
+        .into_iter()
+        .collect(),
+      (Self::List(list), KeyPathComponent::Each) => list.iter().collect(),
+      (Self::Dict(_), KeyPathComponent::Each) | (Self::Integer(_), _) | (Self::String(_), _) => {
+        Vec::new()
+      }
+    }
+  }
+
+  /// Set the values at `path` to `value`. Missing dictionaries along `path`
+  /// are created. If `path` selects the item one past the end of a list,
+  /// `value` is appended.
+  pub(crate) fn set(&mut self, path: &KeyPath, value: Bencode) -> Result<(), Error> {
+    if self.set_inner(path.components(), &value) {
+      Ok(())
+    } else {
+      Err(Error::KeyPathNotFound { path: path.clone() })
+    }
+  }
+
+  fn set_inner(&mut self, components: &[KeyPathComponent], value: &Bencode) -> bool {
+    let (first, rest) = match components.split_first() {
+      Some(split) => split,
+      None => {
+        *self = value.clone();
+        return true;
+      }
+    };
+
+    match (self, first) {
+      (Self::Dict(dict), KeyPathComponent::Key(key)) => {
+        if rest.is_empty() {
+          dict.insert(key.as_bytes().to_vec(), value.clone());
+          true
+        } else {
+          dict
+            .entry(key.as_bytes().to_vec())
+            .or_insert_with(|| Self::Dict(BTreeMap::new()))
+            .set_inner(rest, value)
+        }
+      }
+      (Self::List(list), KeyPathComponent::Key(key)) => match key.parse::<usize>() {
+        Ok(index) if index < list.len() => list[index].set_inner(rest, value),
+        Ok(index) if index == list.len() && rest.is_empty() => {
+          list.push(value.clone());
+          true
+        }
+        _ => false,
+      },
+      (Self::List(list), KeyPathComponent::Each) => {
+        list.iter_mut().all(|item| item.set_inner(rest, value))
+      }
+      (Self::Dict(_), KeyPathComponent::Each) | (Self::Integer(_), _) | (Self::String(_), _) => {
+        false
+      }
+    }
+  }
+
+  /// Remove and return the values at `path`.
+  pub(crate) fn delete(&mut self, path: &KeyPath) -> Result<Vec<Bencode>, Error> {
+    self
+      .delete_inner(path.components())
+      .ok_or_else(|| Error::KeyPathNotFound { path: path.clone() })
+  }
+
+  fn delete_inner(&mut self, components: &[KeyPathComponent]) -> Option<Vec<Bencode>> {
+    let (first, rest) = components.split_first()?;
+
+    match (self, first) {
+      (Self::Dict(dict), KeyPathComponent::Key(key)) => {
+        if rest.is_empty() {
+          dict.remove(key.as_bytes()).map(|value| vec![value])
+        } else {
+          dict.get_mut(key.as_bytes())?.delete_inner(rest)
+        }
+      }
+      (Self::List(list), KeyPathComponent::Key(key)) => {
+        let index = key
+          .parse::<usize>()
+          .ok()
+          .filter(|index| *index < list.len())?;
+
+        if rest.is_empty() {
+          Some(vec![list.remove(index)])
+        } else {
+          list[index].delete_inner(rest)
+        }
+      }
+      (Self::List(list), KeyPathComponent::Each) => {
+        if rest.is_empty() {
+          Some(list.drain(..).collect())
+        } else {
+          let mut deleted = Vec::new();
+          for item in list {
+            deleted.extend(item.delete_inner(rest)?);
+          }
+          Some(deleted)
+        }
+      }
+      (Self::Dict(_), KeyPathComponent::Each) | (Self::Integer(_), _) | (Self::String(_), _) => {
+        None
+      }
+    }
+  }
+
+  /// Convert to JSON. Byte strings that are not valid UTF-8 are represented
+  /// as objects with a single `$bytes` key, whose value is the hex-encoded
+  /// bytes. Dictionary keys that are not valid UTF-8, or that start with
+  /// `$bytes`, are represented as `$bytes:` followed by the hex-encoded key,
+  /// so that they cannot be mistaken for byte strings or encoded keys.
+  pub(crate) fn to_json(&self) -> serde_json::Value {
+    match self {
+      Self::Integer(integer) => (*integer).into(),
+      Self::String(bytes) => match str::from_utf8(bytes) {
+        Ok(text) => text.into(),
+        Err(_) => {
+          let mut object = serde_json::Map::new();
+          object.insert(JSON_BYTES_KEY.into(), Self::hex(bytes).into());
+          object.into()
+        }
+      },
+      Self::List(list) => list.iter().map(Self::to_json).collect(),
+      Self::Dict(dict) => dict
+        .iter()
+        .map(|(key, value)| (Self::json_key(key), value.to_json()))
+        .collect::<serde_json::Map<String, serde_json::Value>>()
+        .into(),
+    }
+  }
+
+  /// Convert from JSON, the inverse of `to_json`. Only integers, strings,
+  /// arrays, and objects can be represented as bencode.
+  pub(crate) fn from_json(json: &serde_json::Value) -> Result<Bencode, Error> {
+    let unrepresentable = || Error::JsonUnrepresentable {
+      value: json.to_string(),
+    };
+
+    match json {
+      serde_json::Value::Number(number) => number
+        .as_i64()
+        .map(Self::Integer)
+        .ok_or_else(unrepresentable),
+      serde_json::Value::String(text) => Ok(Self::String(text.as_bytes().to_vec())),
+      serde_json::Value::Array(array) => Ok(Self::List(
+        array
+          .iter()
+          .map(Self::from_json)
+          .collect::<Result<_, _>>()?,
+      )),
+      serde_json::Value::Object(object) => {
+        if let (1, Some(serde_json::Value::String(hex))) =
+          (object.len(), object.get(JSON_BYTES_KEY))
+        {
+          return Self::unhex(hex)
+            .map(Self::String)
+            .ok_or_else(unrepresentable);
+        }
+
+        Ok(Self::Dict(
+          object
+            .iter()
+            .map(|(key, value)| {
+              let key = match key.strip_prefix(JSON_BYTES_KEY_PREFIX) {
+                Some(hex) => Self::unhex(hex).ok_or_else(unrepresentable)?,
+                None => key.as_bytes().to_vec(),
+              };
+              Ok((key, Self::from_json(value)?))
+            })
+            .collect::<Result<_, Error>>()?,
+        ))
+      }
+      serde_json::Value::Bool(_) | serde_json::Value::Null => Err(unrepresentable()),
+    }
+  }
+
+  /// Render as an indented tree, for display.
+  pub(crate) fn tree(&self) -> String {
+    let mut buffer = String::new();
+    self.tree_inner(0, &mut buffer);
+    buffer
+  }
+
+  fn tree_inner(&self, indent: usize, buffer: &mut String) {
+    match self {
+      Self::Dict(dict) if !dict.is_empty() => {
+        for (key, value) in dict {
+          buffer.push_str(&" ".repeat(indent));
+          buffer.push_str(&String::from_utf8_lossy(key));
+          buffer.push(':');
+          value.tree_item(indent, buffer);
+        }
+      }
+      Self::List(list) if !list.is_empty() => {
+        for value in list {
+          buffer.push_str(&" ".repeat(indent));
+          buffer.push('-');
+          value.tree_item(indent, buffer);
+        }
+      }
+      _ => {
+        buffer.push_str(&" ".repeat(indent));
+        buffer.push_str(&self.scalar());
+        buffer.push('\n');
+      }
+    }
+  }
+
+  fn tree_item(&self, indent: usize, buffer: &mut String) {
+    if self.is_empty_or_scalar() {
+      buffer.push(' ');
+      buffer.push_str(&self.scalar());
+      buffer.push('\n');
+    } else {
+      buffer.push('\n');
+      self.tree_inner(indent + 2, buffer);
+    }
+  }
+
+  fn is_empty_or_scalar(&self) -> bool {
+    match self {
+      Self::Dict(dict) => dict.is_empty(),
+      Self::List(list) => list.is_empty(),
+      Self::Integer(_) | Self::String(_) => true,
+    }
+  }
+
+  fn scalar(&self) -> String {
+    match self {
+      Self::Integer(integer) => integer.to_string(),
+      Self::String(bytes) => match str::from_utf8(bytes) {
+        Ok(text) => format!("{:?}", text),
+        Err(_) => format!("<{} bytes: {}>", bytes.len(), Self::hex(bytes)),
+      },
+      Self::List(_) => "[]".into(),
+      Self::Dict(_) => "{}".into(),
+    }
+  }
+
+  fn json_key(key: &[u8]) -> String {
+    match str::from_utf8(key) {
+      Ok(text) if !text.starts_with(JSON_BYTES_KEY) => text.to_owned(),
+      _ => format!("{}{}", JSON_BYTES_KEY_PREFIX, Self::hex(key)),
+    }
+  }
+
+  fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+  }
+
+  fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+      return None;
+    }
+
+    (0..hex.len())
+      .step_by(2)
+      .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+      .collect()
+  }
+
+  pub(crate) fn encode(&self) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    self.encode_into(&mut buffer);
+    buffer
+  }
+
+  /// If this value is a dictionary, insert those entries of `entries` whose
+  /// keys are not already present.
+  pub(crate) fn insert_missing(&mut self, entries: &BTreeMap<Vec<u8>, Bencode>) {
+    if let Self::Dict(dict) = self {
+      for (key, value) in entries {
+        dict.entry(key.clone()).or_insert_with(|| value.clone());
+      }
+    }
+  }
+
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    match self {
+      Self::Integer(integer) => buffer.extend(format!("i{}e", integer).as_bytes()),
+      Self::String(string) => Self::encode_string(string, buffer),
+      Self::List(list) => {
+        buffer.push(b'l');
+        for item in list {
+          item.encode_into(buffer);
+        }
+        buffer.push(b'e');
+      }
+      Self::Dict(dict) => {
+        buffer.push(b'd');
+        for (key, value) in dict {
+          Self::encode_string(key, buffer);
+          value.encode_into(buffer);
+        }
+        buffer.push(b'e');
+      }
+    }
+  }
+
+  fn encode_string(string: &[u8], buffer: &mut Vec<u8>) {
+    buffer.extend(string.len().to_string().as_bytes());
+    buffer.push(b':');
+    buffer.extend(string);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn value(bytes: &[u8]) -> Bencode {
+    BencodeParser::new(bytes).parse().unwrap()
+  }
+
+  fn path(text: &str) -> KeyPath {
+    text.parse().unwrap()
+  }
+
+  #[test]
+  fn get() {
+    let value = value(b"d1:ad1:bli1ei2eee1:c3:fooe");
+    assert_eq!(value.get(&path("c")), &[&Bencode::String(b"foo".to_vec())]);
+    assert_eq!(value.get(&path("a/b/1")), &[&Bencode::Integer(2)]);
+    assert!(value.get(&path("a/b/2")).is_empty());
+    assert!(value.get(&path("a/b/x")).is_empty());
+    assert!(value.get(&path("c/d")).is_empty());
+    assert!(value.get(&path("a*")).is_empty());
+  }
+
+  #[test]
+  fn get_each() {
+    let value = value(b"d1:ald1:bi1eed1:bi2eed1:ci3eeee");
+    assert_eq!(
+      value.get(&path("a*b")),
+      &[&Bencode::Integer(1), &Bencode::Integer(2)]
+    );
+    assert_eq!(value.get(&path("a*")).len(), 3);
+  }
+
+  #[test]
+  fn set() {
+    let mut value = value(b"d1:ali1eee");
+    value.set(&path("a/0"), Bencode::Integer(2)).unwrap();
+    value.set(&path("a/1"), Bencode::Integer(3)).unwrap();
+    value.set(&path("b/c"), Bencode::Integer(4)).unwrap();
+    assert_eq!(value.encode(), b"d1:ali2ei3ee1:bd1:ci4eee");
+
+    assert_matches!(
+      value.set(&path("a/3"), Bencode::Integer(0)),
+      Err(Error::KeyPathNotFound { .. })
+    );
+    assert_matches!(
+      value.set(&path("b/c/d"), Bencode::Integer(0)),
+      Err(Error::KeyPathNotFound { .. })
+    );
+  }
+
+  #[test]
+  fn set_each() {
+    let mut value = value(b"d1:ald1:bi1eedee1:ci0ee");
+    value.set(&path("a*b"), Bencode::Integer(2)).unwrap();
+    assert_eq!(value.encode(), b"d1:ald1:bi2eed1:bi2eee1:ci0ee");
+
+    value.set(&path("a*"), Bencode::Integer(3)).unwrap();
+    assert_eq!(value.encode(), b"d1:ali3ei3ee1:ci0ee");
+
+    assert_matches!(
+      value.set(&path("c*"), Bencode::Integer(0)),
+      Err(Error::KeyPathNotFound { .. })
+    );
+  }
+
+  #[test]
+  fn delete() {
+    let mut value = value(b"d1:ali1ei2ee1:bi3ee");
+    assert_eq!(value.delete(&path("a/0")).unwrap(), &[Bencode::Integer(1)]);
+    assert_eq!(value.delete(&path("b")).unwrap(), &[Bencode::Integer(3)]);
+    assert_eq!(value.encode(), b"d1:ali2eee");
+
+    assert_matches!(value.delete(&path("b")), Err(Error::KeyPathNotFound { .. }));
+    assert_matches!(
+      value.delete(&path("a/1")),
+      Err(Error::KeyPathNotFound { .. })
+    );
+  }
+
+  #[test]
+  fn delete_each() {
+    let mut value = value(b"d1:ald1:bi1e1:ci2eed1:bi3eeee");
+    assert_eq!(
+      value.delete(&path("a*b")).unwrap(),
+      &[Bencode::Integer(1), Bencode::Integer(3)]
+    );
+    assert_eq!(value.encode(), b"d1:ald1:ci2eedeee");
+
+    assert_eq!(value.delete(&path("a*")).unwrap().len(), 2);
+    assert_eq!(value.encode(), b"d1:alee");
+
+    assert_matches!(
+      value.delete(&path("a*b")),
+      Ok(deleted) if deleted.is_empty()
+    );
+  }
+
+  #[test]
+  fn json_round_trip() {
+    let value = value(b"d1:ali1e3:fooe1:b2:\xff\x00e");
+    let json = value.to_json();
+    assert_eq!(json.to_string(), r#"{"a":[1,"foo"],"b":{"$bytes":"ff00"}}"#);
+    assert_eq!(Bencode::from_json(&json).unwrap(), value);
+  }
+
+  #[test]
+  fn json_round_trip_keys() {
+    let value = value(b"d6:$bytesi4e8:$bytes:ai2e1:ai3e2:\xff\x00i1ee");
+    let json = value.to_json();
+    assert_eq!(
+      json.to_string(),
+      r#"{"$bytes:246279746573":4,"$bytes:2462797465733a61":2,"$bytes:ff00":1,"a":3}"#
+    );
+    assert_eq!(Bencode::from_json(&json).unwrap(), value);
+  }
+
+  #[test]
+  fn json_unrepresentable() {
+    for text in &[
+      "1.5",
+      "true",
+      "null",
+      r#"{"$bytes":"xyz"}"#,
+      r#"{"$bytes:xyz":1}"#,
+      "[null]",
+    ] {
+      let json = serde_json::from_str(text).unwrap();
+      assert_matches!(
+        Bencode::from_json(&json),
+        Err(Error::JsonUnrepresentable { .. })
+      );
+    }
+  }
+
+  #[test]
+  fn tree() {
+    let value = value(b"d1:ad1:bli1eldeee1:cdee1:d3:foo1:e2:\xff\x00e");
+    assert_eq!(
+      value.tree(),
+      concat!(
+        "a:\n",
+        "  b:\n",
+        "    - 1\n",
+        "    -\n",
+        "      - {}\n",
+        "  c: {}\n",
+        "d: \"foo\"\n",
+        "e: <2 bytes: ff00>\n",
+      )
+    );
+    assert_eq!(Bencode::Integer(1).tree(), "1\n");
+  }
+
+  #[test]
+  fn encode() {
+    fn case(value: Bencode, want: &str) {
+      assert_eq!(String::from_utf8(value.encode()).unwrap(), want);
+    }
+
+    case(Bencode::Integer(0), "i0e");
+    case(Bencode::Integer(-12), "i-12e");
+    case(Bencode::String(b"foo".to_vec()), "3:foo");
+    case(Bencode::String(Vec::new()), "0:");
+    case(
+      Bencode::List(vec![Bencode::Integer(1), Bencode::String(b"a".to_vec())]),
+      "li1e1:ae",
+    );
+
+    let mut dict = BTreeMap::new();
+    dict.insert(b"b".to_vec(), Bencode::Integer(2));
+    dict.insert(b"a".to_vec(), Bencode::List(Vec::new()));
+    case(Bencode::Dict(dict), "d1:ale1:bi2ee");
+  }
+}
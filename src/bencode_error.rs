@@ -0,0 +1,66 @@
+use crate::common::*;
+
+/// Error encountered while parsing bencode, with the byte offset at which it
+/// occurred.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum BencodeError {
+  Eof { offset: usize },
+  IntegerInvalid { offset: usize },
+  IntegerOverflow { offset: usize },
+  NestingTooDeep { offset: usize },
+  UnexpectedByte { offset: usize, byte: u8 },
+}
+
+impl BencodeError {
+  pub(crate) fn offset(self) -> usize {
+    match self {
+      Self::Eof { offset }
+      | Self::IntegerInvalid { offset }
+      | Self::IntegerOverflow { offset }
+      | Self::NestingTooDeep { offset }
+      | Self::UnexpectedByte { offset, .. } => offset,
+    }
+  }
+}
+
+impl Display for BencodeError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Eof { .. } => write!(f, "Unexpected end of input")?,
+      Self::IntegerInvalid { .. } => write!(f, "Invalid integer")?,
+      Self::IntegerOverflow { .. } => write!(f, "Integer out of range")?,
+      Self::NestingTooDeep { .. } => write!(f, "Values nested too deeply")?,
+      Self::UnexpectedByte { byte, .. } => write!(
+        f,
+        "Unexpected byte `{}`",
+        char::from(*byte).escape_default()
+      )?,
+    }
+
+    write!(f, " at byte offset {}", self.offset())
+  }
+}
+
+impl std::error::Error for BencodeError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn display() {
+    assert_eq!(
+      BencodeError::Eof { offset: 3 }.to_string(),
+      "Unexpected end of input at byte offset 3"
+    );
+
+    assert_eq!(
+      BencodeError::UnexpectedByte {
+        offset: 0,
+        byte: b'\n'
+      }
+      .to_string(),
+      "Unexpected byte `\\n` at byte offset 0"
+    );
+  }
+}
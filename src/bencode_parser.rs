@@ -0,0 +1,321 @@
+use crate::common::*;
+
+/// Maximum depth of nested lists and dictionaries.
+const MAX_DEPTH: usize = 512;
+
+/// Lenient bencode parser. Non-canonical encodings, such as unsorted
+/// dictionary keys and integers with leading zeros, are accepted, and their
+/// byte offsets recorded.
+pub(crate) struct BencodeParser<'a> {
+  data: &'a [u8],
+  depth: usize,
+  non_canonical: Vec<NonCanonical>,
+  offset: usize,
+  spans: BTreeMap<Vec<u8>, Range<usize>>,
+}
+
+impl<'a> BencodeParser<'a> {
+  pub(crate) fn new(data: &'a [u8]) -> Self {
+    Self {
+      data,
+      depth: 0,
+      non_canonical: Vec::new(),
+      offset: 0,
+      spans: BTreeMap::new(),
+    }
+  }
+
+  /// Parse a single value from the input. Data following the value is
+  /// ignored, and recorded as non-canonical.
+  pub(crate) fn parse(&mut self) -> Result<Bencode, BencodeError> {
+    let value = self.value()?;
+
+    if self.offset < self.data.len() {
+      self.non_canonical.push(NonCanonical::TrailingData {
+        offset: self.offset,
+      });
+    }
+
+    Ok(value)
+  }
+
+  pub(crate) fn non_canonical(&self) -> &[NonCanonical] {
+    &self.non_canonical
+  }
+
+  /// Byte range of the value for `key` in the top-level dictionary, as it
+  /// appears in the input.
+  pub(crate) fn span(&self, key: &[u8]) -> Option<Range<usize>> {
+    self.spans.get(key).cloned()
+  }
+
+  fn peek(&self) -> Result<u8, BencodeError> {
+    self
+      .data
+      .get(self.offset)
+      .cloned()
+      .ok_or(BencodeError::Eof {
+        offset: self.offset,
+      })
+  }
+
+  fn value(&mut self) -> Result<Bencode, BencodeError> {
+    match self.peek()? {
+      b'i' => {
+        self.offset += 1;
+        Ok(Bencode::Integer(self.integer(b'e')?))
+      }
+      b'l' => {
+        self.enter()?;
+        let mut list = Vec::new();
+        while self.peek()? != b'e' {
+          list.push(self.value()?);
+        }
+        self.leave();
+        Ok(Bencode::List(list))
+      }
+      b'd' => {
+        self.enter()?;
+        let mut dict = BTreeMap::new();
+        let mut previous: Option<Vec<u8>> = None;
+        while self.peek()? != b'e' {
+          let key_offset = self.offset;
+          let key = self.string()?;
+
+          let duplicate = dict.contains_key(&key);
+
+          if duplicate {
+            self
+              .non_canonical
+              .push(NonCanonical::DuplicateKey { offset: key_offset });
+          } else if previous.as_ref().map_or(false, |previous| key < *previous) {
+            self
+              .non_canonical
+              .push(NonCanonical::UnsortedKey { offset: key_offset });
+          }
+
+          let value_offset = self.offset;
+          let value = self.value()?;
+
+          if self.depth == 1 && !self.spans.contains_key(&key) {
+            self.spans.insert(key.clone(), value_offset..self.offset);
+          }
+
+          if !duplicate {
+            dict.insert(key.clone(), value);
+          }
+
+          previous = Some(key);
+        }
+        self.leave();
+        Ok(Bencode::Dict(dict))
+      }
+      b'0'..=b'9' => Ok(Bencode::String(self.string()?)),
+      byte => Err(BencodeError::UnexpectedByte {
+        offset: self.offset,
+        byte,
+      }),
+    }
+  }
+
+  fn enter(&mut self) -> Result<(), BencodeError> {
+    if self.depth == MAX_DEPTH {
+      return Err(BencodeError::NestingTooDeep {
+        offset: self.offset,
+      });
+    }
+    self.depth += 1;
+    self.offset += 1;
+    Ok(())
+  }
+
+  fn leave(&mut self) {
+    self.depth -= 1;
+    self.offset += 1;
+  }
+
+  fn string(&mut self) -> Result<Vec<u8>, BencodeError> {
+    let byte = self.peek()?;
+
+    if !byte.is_ascii_digit() {
+      return Err(BencodeError::UnexpectedByte {
+        offset: self.offset,
+        byte,
+      });
+    }
+
+    let length_offset = self.offset;
+
+    let length =
+      usize::try_from(self.integer(b':')?).map_err(|_| BencodeError::IntegerOverflow {
+        offset: length_offset,
+      })?;
+
+    let start = self.offset;
+
+    let end = start
+      .checked_add(length)
+      .filter(|end| *end <= self.data.len())
+      .ok_or(BencodeError::Eof {
+        offset: self.data.len(),
+      })?;
+
+    self.offset = end;
+
+    Ok(self.data[start..end].to_vec())
+  }
+
+  fn integer(&mut self, terminator: u8) -> Result<i64, BencodeError> {
+    let start = self.offset;
+
+    let negative = self.peek()? == b'-';
+    if negative {
+      self.offset += 1;
+    }
+
+    let digits_start = self.offset;
+
+    while self.peek()?.is_ascii_digit() {
+      self.offset += 1;
+    }
+
+    let digits = &self.data[digits_start..self.offset];
+
+    if digits.is_empty() {
+      return Err(BencodeError::IntegerInvalid { offset: start });
+    }
+
+    let byte = self.peek()?;
+    if byte != terminator {
+      return Err(BencodeError::UnexpectedByte {
+        offset: self.offset,
+        byte,
+      });
+    }
+
+    let text = String::from_utf8_lossy(&self.data[start..self.offset]);
+
+    self.offset += 1;
+
+    if digits.len() > 1 && digits[0] == b'0' {
+      self
+        .non_canonical
+        .push(NonCanonical::LeadingZero { offset: start });
+    }
+
+    if negative && digits.iter().all(|digit| *digit == b'0') {
+      self
+        .non_canonical
+        .push(NonCanonical::NegativeZero { offset: start });
+    }
+
+    text
+      .parse()
+      .map_err(|_| BencodeError::IntegerOverflow { offset: start })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn parse(text: &str) -> (Bencode, Vec<NonCanonical>) {
+    let mut parser = BencodeParser::new(text.as_bytes());
+    let value = parser.parse().unwrap();
+    (value, parser.non_canonical().to_vec())
+  }
+
+  fn error(text: &str) -> BencodeError {
+    BencodeParser::new(text.as_bytes()).parse().unwrap_err()
+  }
+
+  #[test]
+  fn canonical() {
+    for text in &[
+      "i0e",
+      "i-1e",
+      "i123e",
+      "0:",
+      "3:foo",
+      "le",
+      "li1ei2ee",
+      "de",
+      "d1:ai1e1:bli2eee",
+    ] {
+      let (value, non_canonical) = parse(text);
+      assert_eq!(non_canonical, &[], "{}", text);
+      assert_eq!(String::from_utf8(value.encode()).unwrap(), *text);
+    }
+  }
+
+  #[test]
+  fn non_canonical() {
+    fn case(text: &str, want: &str, non_canonical: &[NonCanonical]) {
+      let (value, have) = parse(text);
+      assert_eq!(have, non_canonical, "{}", text);
+      assert_eq!(String::from_utf8(value.encode()).unwrap(), want);
+    }
+
+    case("i01e", "i1e", &[NonCanonical::LeadingZero { offset: 1 }]);
+    case("i-0e", "i0e", &[NonCanonical::NegativeZero { offset: 1 }]);
+    case(
+      "03:foo",
+      "3:foo",
+      &[NonCanonical::LeadingZero { offset: 0 }],
+    );
+    case("i1ex", "i1e", &[NonCanonical::TrailingData { offset: 3 }]);
+    case(
+      "d1:bi1e1:ai2ee",
+      "d1:ai2e1:bi1ee",
+      &[NonCanonical::UnsortedKey { offset: 7 }],
+    );
+    case(
+      "d1:ai1e1:ai2ee",
+      "d1:ai1ee",
+      &[NonCanonical::DuplicateKey { offset: 7 }],
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(error(""), BencodeError::Eof { offset: 0 });
+    assert_eq!(error("i1"), BencodeError::Eof { offset: 2 });
+    assert_eq!(error("ie"), BencodeError::IntegerInvalid { offset: 1 });
+    assert_eq!(error("i-e"), BencodeError::IntegerInvalid { offset: 1 });
+    assert_eq!(
+      error("i99999999999999999999e"),
+      BencodeError::IntegerOverflow { offset: 1 }
+    );
+    assert_eq!(error("5:foo"), BencodeError::Eof { offset: 5 });
+    assert_eq!(
+      error("x"),
+      BencodeError::UnexpectedByte {
+        offset: 0,
+        byte: b'x'
+      }
+    );
+    assert_eq!(
+      error("di1ei2ee"),
+      BencodeError::UnexpectedByte {
+        offset: 1,
+        byte: b'i'
+      }
+    );
+    assert_eq!(
+      error(&"l".repeat(MAX_DEPTH + 1)),
+      BencodeError::NestingTooDeep { offset: MAX_DEPTH }
+    );
+  }
+
+  #[test]
+  fn span() {
+    let text = "d4:infod1:ai1ee1:zi2ee";
+    let mut parser = BencodeParser::new(text.as_bytes());
+    parser.parse().unwrap();
+    assert_eq!(parser.span(b"info"), Some(7..15));
+    assert_eq!(parser.span(b"z"), Some(18..21));
+    assert_eq!(parser.span(b"a"), None);
+  }
+}
@@ -8,24 +8,24 @@ pub(crate) use std::{
   env,
   ffi::{OsStr, OsString},
   fmt::{self, Display, Formatter},
-  fs::{self, File},
+  fs::{self, File, Metadata},
   hash::Hash,
-  io::{self, BufRead, BufReader, Cursor, Read, Write},
+  io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write},
   iter::{self, Sum},
   num::{ParseFloatError, ParseIntError, TryFromIntError},
-  ops::{AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign},
+  ops::{AddAssign, Div, DivAssign, Mul, MulAssign, Range, Sub, SubAssign},
   path::{self, Path, PathBuf},
   process::ExitStatus,
   str::{self, FromStr},
   string::FromUtf8Error,
   sync::Once,
-  time::{SystemTime, SystemTimeError},
+  time::{Duration, Instant, SystemTime, SystemTimeError},
   usize,
 };
 
 // dependencies
-pub(crate) use bendy::{decoding::FromBencode, encoding::ToBencode, value::Value};
-pub(crate) use chrono::{TimeZone, Utc};
+pub(crate) use bendy::value::Value;
+pub(crate) use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 pub(crate) use globset::{Glob, GlobMatcher};
 pub(crate) use ignore::WalkBuilder;
 pub(crate) use indicatif::{ProgressBar, ProgressStyle};
@@ -36,6 +36,7 @@ pub(crate) use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Ser
 pub(crate) use serde_hex::SerHex;
 pub(crate) use serde_with::rust::unwrap_or_skip;
 pub(crate) use sha1::Sha1;
+pub(crate) use sha2::{Digest as _, Sha256};
 pub(crate) use snafu::{ResultExt, Snafu};
 pub(crate) use static_assertions::const_assert;
 pub(crate) use structopt::{
@@ -44,6 +45,7 @@ pub(crate) use structopt::{
 };
 pub(crate) use strum::{IntoEnumIterator, VariantNames};
 pub(crate) use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
+pub(crate) use unicode_normalization::UnicodeNormalization;
 pub(crate) use unicode_width::UnicodeWidthStr;
 pub(crate) use url::{Host, Url};
 
@@ -55,7 +57,7 @@ pub(crate) use log::trace;
 pub(crate) use crate::{consts, error, host_port_parse_error};
 
 // functions
-pub(crate) use crate::xor_args::xor_args;
+pub(crate) use crate::{xml_escape::xml_escape, xor_args::xor_args};
 
 // traits
 pub(crate) use crate::{
@@ -65,18 +67,83 @@ pub(crate) use crate::{
 
 // structs and enums
 pub(crate) use crate::{
-  arguments::Arguments, bytes::Bytes, env::Env, error::Error, file_error::FileError,
-  file_info::FileInfo, file_path::FilePath, file_status::FileStatus, files::Files, hasher::Hasher,
-  host_port::HostPort, host_port_parse_error::HostPortParseError, info::Info, infohash::Infohash,
-  input::Input, input_target::InputTarget, lint::Lint, linter::Linter, magnet_link::MagnetLink,
-  md5_digest::Md5Digest, metainfo::Metainfo, metainfo_error::MetainfoError, mode::Mode,
-  options::Options, output_stream::OutputStream, output_target::OutputTarget,
-  piece_length_picker::PieceLengthPicker, piece_list::PieceList, platform::Platform,
-  sha1_digest::Sha1Digest, shell::Shell, sort_key::SortKey, sort_order::SortOrder,
-  sort_spec::SortSpec, status::Status, style::Style, subcommand::Subcommand, table::Table,
-  torrent_summary::TorrentSummary, use_color::UseColor, verifier::Verifier, walker::Walker,
+  archive::Archive,
+  archive_format::ArchiveFormat,
+  arguments::Arguments,
+  bencode::Bencode,
+  bencode_error::BencodeError,
+  bencode_parser::BencodeParser,
+  bytes::Bytes,
+  config::Config,
+  distribution::Distribution,
+  env::Env,
+  error::Error,
+  file_error::FileError,
+  file_info::FileInfo,
+  file_list::FileList,
+  file_path::FilePath,
+  file_reader::FileReader,
+  file_status::FileStatus,
+  files::{Contents, Files},
+  git_tree::GitTree,
+  hasher::Hasher,
+  host_port::HostPort,
+  host_port_parse_error::HostPortParseError,
+  inflater::Inflater,
+  info::Info,
+  infohash::Infohash,
+  input::Input,
+  input_target::InputTarget,
+  io_backend::IoBackend,
+  key_path::{KeyPath, KeyPathComponent},
+  lint::Lint,
+  linter::Linter,
+  magnet_link::MagnetLink,
+  manifest::{Manifest, ManifestEntry},
+  manifest_format::ManifestFormat,
+  md5_digest::Md5Digest,
+  metainfo::Metainfo,
+  metainfo_error::MetainfoError,
+  mode::Mode,
+  non_canonical::NonCanonical,
+  options::Options,
+  output_stream::OutputStream,
+  output_target::OutputTarget,
+  path_checker::PathChecker,
+  path_violation::PathViolation,
+  piece_length_picker::PieceLengthPicker,
+  piece_list::PieceList,
+  platform::Platform,
+  profile::Profile,
+  resume::Resume,
+  resume_format::ResumeFormat,
+  sampler::Sampler,
+  severity::Severity,
+  sha1_digest::Sha1Digest,
+  sha256_digest::Sha256Digest,
+  shell::Shell,
+  skip_reason::SkipReason,
+  sort_key::SortKey,
+  sort_order::SortOrder,
+  sort_spec::SortSpec,
+  sparse_file::SparseFile,
+  status::Status,
+  style::Style,
+  subcommand::Subcommand,
+  table::Table,
+  tar_reader::TarReader,
+  time_spec::TimeSpec,
+  torrent_summary::TorrentSummary,
+  use_color::UseColor,
+  verifier::Verifier,
+  violation::Violation,
+  walker::{FileStream, WalkEntry, Walker},
 };
 
+// linux-only structs and enums
+#[cfg(target_os = "linux")]
+pub(crate) use crate::inotify::{Change, Inotify};
+
 // type aliases
 pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -91,6 +158,7 @@ mod test {
   };
 
   // test dependencies
+  pub(crate) use bendy::decoding::FromBencode;
   pub(crate) use tempfile::TempDir;
   pub(crate) use temptree::temptree;
 
@@ -0,0 +1,265 @@
+use crate::common::*;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Config {
+  path: PathBuf,
+  profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+  #[serde(default)]
+  profile: BTreeMap<String, Profile>,
+}
+
+impl Config {
+  pub(crate) const DEFAULT_PROFILE: &'static str = "default";
+  pub(crate) const FILENAME: &'static str = "imdl.toml";
+
+  /// Load the config file given with `--config`, or if not given, the first
+  /// file named `imdl.toml` found in the current directory or any of its
+  /// ancestors, stopping at `Env::config_root` if set.
+  pub(crate) fn load(env: &Env, options: &Options) -> Result<Option<Config>> {
+    let path = if let Some(path) = &options.config {
+      env.resolve(path)?
+    } else {
+      let mut found = None;
+
+      for dir in env.dir().ancestors() {
+        let path = dir.join(Self::FILENAME);
+
+        if path.is_file() {
+          found = Some(path);
+          break;
+        }
+
+        if Some(dir) == env.config_root() {
+          break;
+        }
+      }
+
+      match found {
+        Some(path) => path,
+        None => return Ok(None),
+      }
+    };
+
+    let text = fs::read_to_string(&path).context(error::Filesystem { path: &path })?;
+
+    let file =
+      toml::from_str::<ConfigFile>(&text).context(error::ConfigDeserialize { path: &path })?;
+
+    Ok(Some(Config {
+      profiles: file.profile,
+      path,
+    }))
+  }
+
+  /// Load the profile selected with `--profile`, or the profile named
+  /// `default`, if it exists.
+  pub(crate) fn load_profile(env: &Env, options: &Options) -> Result<Option<Profile>> {
+    match Self::load(env, options)? {
+      Some(config) => Ok(
+        config
+          .selected_profile(options)?
+          .map(|(_name, profile)| profile.clone()),
+      ),
+      None => match &options.profile {
+        Some(profile) => Err(Error::ConfigMissing {
+          profile: profile.clone(),
+        }),
+        None => Ok(None),
+      },
+    }
+  }
+
+  pub(crate) fn selected_profile(&self, options: &Options) -> Result<Option<(&str, &Profile)>> {
+    match &options.profile {
+      Some(name) => match self.profiles.get_key_value(name) {
+        Some((name, profile)) => Ok(Some((name, profile))),
+        None => Err(Error::ConfigProfileUnknown {
+          path: self.path.clone(),
+          profile: name.clone(),
+        }),
+      },
+      None => Ok(
+        self
+          .profiles
+          .get_key_value(Self::DEFAULT_PROFILE)
+          .map(|(name, profile)| (name.as_str(), profile)),
+      ),
+    }
+  }
+
+  pub(crate) fn path(&self) -> &Path {
+    &self.path
+  }
+
+  #[cfg(test)]
+  pub(crate) fn profile(&self, name: &str) -> Option<&Profile> {
+    self.profiles.get(name)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn load(env: &TestEnv) -> Result<Option<Config>> {
+    Config::load(env, &Options::from_iter(&["imdl"]))
+  }
+
+  #[test]
+  fn missing() {
+    let env = test_env! {
+      args: [],
+      tree: {},
+    };
+
+    assert_eq!(load(&env).unwrap(), None);
+  }
+
+  #[test]
+  fn current_directory() {
+    let env = test_env! {
+      args: [],
+      tree: {
+        "imdl.toml": "[profile.default]\nsource = \"FOO\"\n",
+      },
+    };
+
+    let config = load(&env).unwrap().unwrap();
+
+    assert_eq!(config.path(), env.resolve("imdl.toml").unwrap());
+
+    assert_eq!(
+      config.profile("default"),
+      Some(&Profile {
+        source: Some("FOO".into()),
+        ..Profile::default()
+      })
+    );
+  }
+
+  #[test]
+  fn ancestor_directory() {
+    let env = test_env! {
+      args: [],
+      cwd: "a/b",
+      tree: {
+        "imdl.toml": "[profile.default]\nsource = \"FOO\"\n",
+        a: {
+          b: {},
+        },
+      },
+    };
+
+    let config = load(&env).unwrap().unwrap();
+
+    assert_eq!(config.path(), env.resolve("../../imdl.toml").unwrap());
+  }
+
+  #[test]
+  fn nearest_wins() {
+    let env = test_env! {
+      args: [],
+      cwd: "a",
+      tree: {
+        "imdl.toml": "[profile.default]\nsource = \"FOO\"\n",
+        a: {
+          "imdl.toml": "[profile.default]\nsource = \"BAR\"\n",
+        },
+      },
+    };
+
+    let config = load(&env).unwrap().unwrap();
+
+    assert_eq!(
+      config.profile("default").unwrap().source,
+      Some("BAR".into())
+    );
+  }
+
+  #[test]
+  fn search_stops_at_root() {
+    let tempdir = temptree! {
+      "imdl.toml": "[profile.default]\nsource = \"FOO\"\n",
+      a: {
+        b: {},
+      },
+    };
+
+    let env = TestEnvBuilder::new()
+      .tempdir(tempdir)
+      .current_dir("a/b".into())
+      .config_root("a".into())
+      .build();
+
+    assert_eq!(load(&env).unwrap(), None);
+  }
+
+  #[test]
+  fn invalid() {
+    let env = test_env! {
+      args: [],
+      tree: {
+        "imdl.toml": "[profile.default]\nfoo = 1\n",
+      },
+    };
+
+    assert_matches!(load(&env), Err(Error::ConfigDeserialize { .. }));
+  }
+
+  #[test]
+  fn selected_profile() {
+    let env = test_env! {
+      args: [],
+      tree: {
+        "imdl.toml": "[profile.default]\nsource = \"FOO\"\n[profile.bar]\nsource = \"BAR\"\n",
+      },
+    };
+
+    let config = load(&env).unwrap().unwrap();
+
+    let (name, profile) = config
+      .selected_profile(&Options::from_iter(&["imdl"]))
+      .unwrap()
+      .unwrap();
+    assert_eq!(name, "default");
+    assert_eq!(profile.source, Some("FOO".into()));
+
+    let (name, profile) = config
+      .selected_profile(&Options::from_iter(&["imdl", "--profile", "bar"]))
+      .unwrap()
+      .unwrap();
+    assert_eq!(name, "bar");
+    assert_eq!(profile.source, Some("BAR".into()));
+
+    assert_matches!(
+      config.selected_profile(&Options::from_iter(&["imdl", "--profile", "baz"])),
+      Err(Error::ConfigProfileUnknown { profile, .. }) if profile == "baz"
+    );
+  }
+
+  #[test]
+  fn no_default_profile() {
+    let env = test_env! {
+      args: [],
+      tree: {
+        "imdl.toml": "[profile.bar]\nsource = \"BAR\"\n",
+      },
+    };
+
+    let config = load(&env).unwrap().unwrap();
+
+    assert_eq!(
+      config
+        .selected_profile(&Options::from_iter(&["imdl"]))
+        .unwrap(),
+      None
+    );
+  }
+}
@@ -15,6 +15,10 @@ pub(crate) const CREATED_BY_DEFAULT: &str = concat!(
   env!("GIT_HEAD_PARTIAL_HASH"),
 );
 
+/// Value for `created by` torrent metainfo field when creating reproducible
+/// torrents, which omits version information.
+pub(crate) const CREATED_BY_REPRODUCIBLE: &str = "imdl";
+
 /// Value for `encoding` torrent metainfo field.
 pub(crate) const ENCODING_UTF8: &str = "UTF-8";
 
@@ -65,6 +69,10 @@ pub(crate) const PROGRESS_STYLE: &str = "{spinner:.green} ⟪{elapsed_precise}
                                          {binary_bytes}/{binary_total_bytes} \
                                          ⟨{binary_bytes_per_sec}, {eta}⟩";
 
+/// Progress style for content whose total size is not known in advance.
+pub(crate) const PROGRESS_SPINNER_STYLE: &str =
+  "{spinner:.green} ⟪{elapsed_precise}⟫ {binary_bytes} ⟨{binary_bytes_per_sec}⟩";
+
 pub(crate) const VERSION: &str = concat!("v", env!("CARGO_PKG_VERSION"));
 
 pub(crate) const VERSION_MESSAGE: &str = "Print version number.";
@@ -0,0 +1,154 @@
+use crate::common::*;
+
+/// A collection of samples, summarized by count, extrema, mean, median, and a
+/// histogram with power-of-two buckets.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Distribution {
+  samples: Vec<u64>,
+}
+
+#[derive(Serialize)]
+struct Summary {
+  count: u64,
+  min: Option<u64>,
+  max: Option<u64>,
+  mean: Option<f64>,
+  median: Option<u64>,
+  histogram: BTreeMap<u64, u64>,
+}
+
+impl Distribution {
+  pub(crate) fn new() -> Self {
+    Self::default()
+  }
+
+  pub(crate) fn record(&mut self, sample: u64) {
+    self.samples.push(sample);
+  }
+
+  pub(crate) fn count(&self) -> u64 {
+    self.samples.len().into_u64()
+  }
+
+  pub(crate) fn min(&self) -> Option<u64> {
+    self.samples.iter().min().copied()
+  }
+
+  pub(crate) fn max(&self) -> Option<u64> {
+    self.samples.iter().max().copied()
+  }
+
+  #[allow(clippy::as_conversions, clippy::cast_precision_loss)]
+  pub(crate) fn mean(&self) -> Option<f64> {
+    if self.samples.is_empty() {
+      return None;
+    }
+
+    let sum = self
+      .samples
+      .iter()
+      .map(|sample| u128::from(*sample))
+      .sum::<u128>();
+
+    Some(sum as f64 / self.samples.len() as f64)
+  }
+
+  /// The lower median of the samples.
+  pub(crate) fn median(&self) -> Option<u64> {
+    if self.samples.is_empty() {
+      return None;
+    }
+
+    let mut sorted = self.samples.clone();
+    sorted.sort_unstable();
+    Some(sorted[(sorted.len() - 1) / 2])
+  }
+
+  /// Sample counts, keyed by the largest power of two less than or equal to
+  /// the samples in each bucket. Zero samples are counted in bucket `0`.
+  pub(crate) fn histogram(&self) -> BTreeMap<u64, u64> {
+    let mut histogram = BTreeMap::new();
+
+    for sample in &self.samples {
+      let bucket = if *sample == 0 {
+        0
+      } else {
+        1 << (63 - sample.leading_zeros())
+      };
+
+      histogram.increment(bucket);
+    }
+
+    histogram
+  }
+}
+
+impl Serialize for Distribution {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    Summary {
+      count: self.count(),
+      min: self.min(),
+      max: self.max(),
+      mean: self.mean(),
+      median: self.median(),
+      histogram: self.histogram(),
+    }
+    .serialize(serializer)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn empty() {
+    let distribution = Distribution::new();
+    assert_eq!(distribution.count(), 0);
+    assert_eq!(distribution.min(), None);
+    assert_eq!(distribution.max(), None);
+    assert_eq!(distribution.mean(), None);
+    assert_eq!(distribution.median(), None);
+    assert!(distribution.histogram().is_empty());
+  }
+
+  #[test]
+  fn summary() {
+    let mut distribution = Distribution::new();
+
+    for sample in &[7, 0, 1, 4, 3, 16] {
+      distribution.record(*sample);
+    }
+
+    assert_eq!(distribution.count(), 6);
+    assert_eq!(distribution.min(), Some(0));
+    assert_eq!(distribution.max(), Some(16));
+    assert_eq!(distribution.mean(), Some(31.0 / 6.0));
+    assert_eq!(distribution.median(), Some(3));
+
+    let mut histogram = BTreeMap::new();
+    histogram.insert(0, 1);
+    histogram.insert(1, 1);
+    histogram.insert(2, 1);
+    histogram.insert(4, 2);
+    histogram.insert(16, 1);
+    assert_eq!(distribution.histogram(), histogram);
+  }
+
+  #[test]
+  fn serialize() {
+    let mut distribution = Distribution::new();
+    distribution.record(2);
+    distribution.record(3);
+
+    assert_eq!(
+      serde_json::to_string(&distribution).unwrap(),
+      r#"{"count":2,"min":2,"max":3,"mean":2.5,"median":2,"histogram":{"2":2}}"#
+    );
+  }
+}
@@ -2,10 +2,12 @@ use crate::common::*;
 
 pub(crate) struct Env {
   args: Vec<OsString>,
+  config_root: Option<PathBuf>,
   dir: PathBuf,
   input: Box<dyn InputStream>,
   err: OutputStream,
   out: OutputStream,
+  vars: BTreeMap<OsString, OsString>,
 }
 
 impl Env {
@@ -23,7 +25,9 @@ impl Env {
 
     Self::new(
       dir,
+      None,
       env::args(),
+      env::vars_os(),
       Box::new(io::stdin()),
       out_stream,
       err_stream,
@@ -95,9 +99,11 @@ impl Env {
     });
   }
 
-  pub(crate) fn new<S, I>(
+  pub(crate) fn new<S, I, V>(
     dir: PathBuf,
+    config_root: Option<PathBuf>,
     args: I,
+    vars: V,
     input: Box<dyn InputStream>,
     out: OutputStream,
     err: OutputStream,
@@ -105,10 +111,13 @@ impl Env {
   where
     S: Into<OsString>,
     I: IntoIterator<Item = S>,
+    V: IntoIterator<Item = (OsString, OsString)>,
   {
     Self {
       args: args.into_iter().map(Into::into).collect(),
+      vars: vars.into_iter().collect(),
       input,
+      config_root,
       dir,
       out,
       err,
@@ -158,10 +167,22 @@ impl Env {
     }
   }
 
+  /// Directory at which the search for a config file stops, or `None` if the
+  /// search should continue up to the filesystem root.
+  pub(crate) fn config_root(&self) -> Option<&Path> {
+    self.config_root.as_deref()
+  }
+
   pub(crate) fn dir(&self) -> &Path {
     &self.dir
   }
 
+  /// Get the value of environment variable `key`. Environment variables are
+  /// captured when the `Env` is created, so tests can control them.
+  pub(crate) fn var(&self, key: &str) -> Option<&OsStr> {
+    self.vars.get(OsStr::new(key)).map(OsString::as_os_str)
+  }
+
   pub(crate) fn err(&self) -> &OutputStream {
     &self.err
   }
@@ -5,6 +5,27 @@ use crate::common::*;
 pub(crate) enum Error {
   #[snafu(display("Failed to parse announce URL: {}", source))]
   AnnounceUrlParse { source: url::ParseError },
+  #[snafu(display(
+    "`--io` and `--read-size` cannot be used when verifying content in archive `{}`",
+    path.display()
+  ))]
+  ArchiveIo { path: PathBuf },
+  #[snafu(display(
+    "Failed to create {} torrent{} with `--batch`",
+    count,
+    if *count == 1 { "" } else { "s" }
+  ))]
+  BatchFailed { count: usize },
+  #[snafu(display(
+    "Batch template `{}` must be a filename, without path separators, and not `.` or `..`",
+    template
+  ))]
+  BatchTemplate { template: String },
+  #[snafu(display("Failed to decode bencode from {}: {}", input, source))]
+  BencodeDecode {
+    input: InputTarget,
+    source: BencodeError,
+  },
   #[snafu(display("Failed to parse byte count `{}`: {}", text, source))]
   ByteParse {
     text: String,
@@ -18,20 +39,53 @@ pub(crate) enum Error {
   CommandInvoke { command: String, source: io::Error },
   #[snafu(display("Command `{}` returned bad exit status: {}", command, status))]
   CommandStatus { command: String, status: ExitStatus },
+  #[snafu(display("Failed to deserialize config file `{}`: {}", path.display(), source))]
+  ConfigDeserialize {
+    path: PathBuf,
+    source: toml::de::Error,
+  },
+  #[snafu(display(
+    "Profile `{}` was requested, but no `{}` config file was found",
+    profile,
+    Config::FILENAME
+  ))]
+  ConfigMissing { profile: String },
+  #[snafu(display("Profile `{}` not found in config file `{}`", profile, path.display()))]
+  ConfigProfileUnknown { profile: String, path: PathBuf },
   #[snafu(display("Filename was not valid unicode: `{}`", filename.display()))]
   FilenameDecode { filename: PathBuf },
   #[snafu(display("Path had no file name: `{}`", path.display()))]
   FilenameExtract { path: PathBuf },
-  #[snafu(display("Unknown file ordering: `{}`", text))]
-  FileOrderUnknown { text: String },
+  #[snafu(display("File list {} is not valid UTF-8", list))]
+  FileListDecode { list: InputTarget },
+  #[snafu(display("File list {} does not contain any files", list))]
+  FileListEmpty { list: InputTarget },
+  #[snafu(display(
+    "Line {} of file list {} must contain a torrent path and a source path, separated by a tab",
+    line,
+    list
+  ))]
+  FileListLine { list: InputTarget, line: usize },
+  #[snafu(display("File list source `{}` is not a file", path.display()))]
+  FileListSource { path: PathBuf },
+  #[snafu(display("Multiple files have torrent path `{}`", path))]
+  FilePathDuplicate { path: FilePath },
+  #[snafu(display("Torrent path `{}` is a file, but also contains `{}`", file, path))]
+  FilePathPrefix { file: FilePath, path: FilePath },
   #[snafu(display("I/O error at `{}`: {}", path.display(), source))]
   Filesystem { source: io::Error, path: PathBuf },
   #[snafu(display("Error searching for files: {}", source))]
   FileSearch { source: ignore::Error },
+  #[snafu(display("`--git-rev` may not be used with `--input -`"))]
+  GitInput,
+  #[snafu(display("Failed to find commit `{}` in git repository `{}`", rev, repo.display()))]
+  GitRevision { rev: String, repo: PathBuf },
   #[snafu(display("Invalid glob: {}", source))]
   GlobParse { source: globset::Error },
   #[snafu(display("Failed to serialize torrent info dictionary: {}", source))]
   InfoSerialize { source: bendy::serde::Error },
+  #[snafu(display("Failed to watch for filesystem changes: {}", source))]
+  Inotify { source: io::Error },
   #[snafu(display("Input target empty"))]
   InputTargetEmpty,
   #[snafu(display(
@@ -40,8 +94,45 @@ pub(crate) enum Error {
     message,
   ))]
   Internal { message: String },
-  #[snafu(display("Unknown lint: {}", text))]
-  LintUnknown { text: String },
+  #[snafu(display("Failed to parse JSON: {}", source))]
+  JsonParse { source: serde_json::Error },
+  #[snafu(display("Failed to serialize JSON: {}", source))]
+  JsonSerialize { source: serde_json::Error },
+  #[snafu(display("JSON value `{}` cannot be represented as bencode", value))]
+  JsonUnrepresentable { value: String },
+  #[snafu(display("No value at key path `{}`", path))]
+  KeyPathNotFound { path: KeyPath },
+  #[snafu(display("Invalid key path `{}`: key path components may not be empty", text))]
+  KeyPathParse { text: String },
+  #[snafu(display(
+    "Found {} lint violation{}",
+    count,
+    if *count == 1 { "" } else { "s" }
+  ))]
+  LintDenied { count: usize },
+  #[snafu(display(
+    "Failed to lint {} torrent{}",
+    count,
+    if *count == 1 { "" } else { "s" }
+  ))]
+  LintInput { count: usize },
+  #[snafu(display(
+    "Lint `{}` is only checked by `imdl torrent lint`, and cannot be allowed when creating \
+     torrents",
+    lint
+  ))]
+  LintNotCreate { lint: Lint },
+  #[snafu(display(
+    "Manifest format `{}` requires {} checksums, but file `{}` has none",
+    format,
+    checksum,
+    path
+  ))]
+  ManifestChecksumMissing {
+    format: ManifestFormat,
+    checksum: &'static str,
+    path: String,
+  },
   #[snafu(display("Failed to deserialize torrent metainfo from {}: {}", input, source))]
   MetainfoDeserialize {
     source: bendy::serde::Error,
@@ -49,10 +140,10 @@ pub(crate) enum Error {
   },
   #[snafu(display("Failed to serialize torrent metainfo: {}", source))]
   MetainfoSerialize { source: bendy::serde::Error },
-  #[snafu(display("Failed to decode torrent metainfo from {}: {}", input, error))]
+  #[snafu(display("Failed to decode torrent metainfo from {}: {}", input, source))]
   MetainfoDecode {
     input: InputTarget,
-    error: bendy::decoding::Error,
+    source: BencodeError,
   },
   #[snafu(display("Metainfo from {} failed to validate: {}", input, source))]
   MetainfoValidate {
@@ -85,6 +176,8 @@ pub(crate) enum Error {
     prefix.display(),
   ))]
   PathStripEmpty { path: PathBuf, prefix: PathBuf },
+  #[snafu(display("{}", violation))]
+  PathLint { violation: PathViolation },
   #[snafu(display(
     "Failed to strip prefix `{}` from path `{}`: {}",
     prefix.display(),
@@ -113,6 +206,47 @@ pub(crate) enum Error {
   PieceLengthZero,
   #[snafu(display("Private torrents must have tracker"))]
   PrivateTrackerless,
+  #[snafu(display(
+    "Read size `{}` is not a multiple of {}, as required by `--io direct`",
+    bytes,
+    Bytes::from(FileReader::DIRECT_ALIGNMENT.into_u64())
+  ))]
+  ReadSizeUnaligned { bytes: Bytes },
+  #[snafu(display("Read size cannot be zero"))]
+  ReadSizeZero,
+  #[snafu(display(
+    "Reproducible torrents cannot be created from content with files hidden by file \
+     attributes, which are only hidden on some platforms: `{}`",
+    path.display()
+  ))]
+  ReproducibleHidden { path: PathBuf },
+  #[snafu(display(
+    "Reproducible torrents cannot be created from content containing symlinks, which are \
+     handled differently on different platforms: `{}`",
+    path.display()
+  ))]
+  ReproducibleSymlink { path: PathBuf },
+  #[snafu(display(
+    "Unknown resume format `{}`, expected one of: {}",
+    text,
+    ResumeFormat::VARIANTS.join(", ")
+  ))]
+  ResumeFormatParse { text: String },
+  #[snafu(display("Resume data save path was not valid unicode: `{}`", path.display()))]
+  ResumeSavePathDecode { path: PathBuf },
+  #[snafu(display(
+    "{} of {} sampled pieces do not match content at `{}`",
+    bad,
+    sampled,
+    path.display()
+  ))]
+  SampleMismatch {
+    bad: usize,
+    sampled: usize,
+    path: PathBuf,
+  },
+  #[snafu(display("Failed to parse `SOURCE_DATE_EPOCH` value `{}`: {}", text, source))]
+  SourceDateEpochParse { text: String, source: ParseIntError },
   #[snafu(display("Completion script for shell `{}` not UTF-8: {}", shell.name(), source))]
   ShellDecode { shell: Shell, source: FromUtf8Error },
   #[snafu(display("Failed to write to standard error: {}", source))]
@@ -129,11 +263,16 @@ pub(crate) enum Error {
   SymlinkRoot { root: PathBuf },
   #[snafu(display("Failed to retrieve system time: {}", source))]
   SystemTime { source: SystemTimeError },
+  #[snafu(display("`--tar` may only be used with `--input -`"))]
+  TarInput,
+  #[snafu(display("Tar archive entry has invalid path `{}`", path))]
+  TarPath { path: String },
   #[snafu(display(
-    "Feature `{}` cannot be used without passing the `--unstable` flag",
-    feature
+    "Invalid time `{}`: Expected an RFC 3339 timestamp, a `YYYY-MM-DD` date, or a number followed \
+     by one of `s`, `m`, `h`, `d`, or `w`",
+    text
   ))]
-  Unstable { feature: &'static str },
+  TimeSpecParse { text: String },
   #[snafu(display("Torrent verification failed."))]
   Verify,
 }
@@ -144,6 +283,7 @@ impl Error {
       Self::PieceLengthUneven { .. } => Some(Lint::UnevenPieceLength),
       Self::PieceLengthSmall { .. } => Some(Lint::SmallPieceLength),
       Self::PrivateTrackerless => Some(Lint::PrivateTrackerless),
+      Self::PathLint { violation } => Some(violation.lint()),
       _ => None,
     }
   }
@@ -18,6 +18,7 @@ impl FileError {
     path: &Path,
     expected_length: Bytes,
     expected_md5: Option<Md5Digest>,
+    reader: &FileReader,
   ) -> Result<(), FileError> {
     let metadata = match path.metadata() {
       Ok(metadata) => metadata,
@@ -34,27 +35,54 @@ impl FileError {
       return Err(FileError::Directory);
     }
 
-    let actual = Bytes(metadata.len());
+    Self::check_length(Bytes(metadata.len()), expected_length)?;
 
-    let difference = actual.absolute_difference(expected_length);
+    if let Some(expected) = expected_md5 {
+      Self::check_md5(&mut reader.open(path)?, expected)?;
+    }
+
+    Ok(())
+  }
+
+  /// Like `verify`, but for the member of `archive` at `path`.
+  pub(crate) fn verify_member(
+    archive: &Archive,
+    path: &str,
+    expected_length: Bytes,
+    expected_md5: Option<Md5Digest>,
+  ) -> Result<(), FileError> {
+    let actual = archive.size(path).ok_or(FileError::Missing)?;
+
+    Self::check_length(actual, expected_length)?;
+
+    if let Some(expected) = expected_md5 {
+      Self::check_md5(&mut archive.open_member(path)?, expected)?;
+    }
+
+    Ok(())
+  }
+
+  fn check_length(actual: Bytes, expected: Bytes) -> Result<(), FileError> {
+    let difference = actual.absolute_difference(expected);
 
-    if actual > expected_length {
+    if actual > expected {
       return Err(FileError::Surfeit(difference));
     }
 
-    if actual < expected_length {
+    if actual < expected {
       return Err(FileError::Dearth(difference));
     }
 
-    if let Some(expected) = expected_md5 {
-      let mut reader = File::open(path)?;
-      let mut context = md5::Context::new();
-      io::copy(&mut reader, &mut context)?;
-      let actual = context.compute().into();
+    Ok(())
+  }
 
-      if actual != expected {
-        return Err(FileError::Md5 { actual, expected });
-      }
+  fn check_md5(reader: &mut dyn Read, expected: Md5Digest) -> Result<(), FileError> {
+    let mut context = md5::Context::new();
+    io::copy(reader, &mut context)?;
+    let actual = context.compute().into();
+
+    if actual != expected {
+      return Err(FileError::Md5 { actual, expected });
     }
 
     Ok(())
@@ -10,4 +10,8 @@ pub(crate) struct FileInfo {
     with = "unwrap_or_skip"
   )]
   pub(crate) md5sum: Option<Md5Digest>,
+  /// File dictionary keys not otherwise recognized, such as the BEP 47 `attr`
+  /// and `sha1` keys, which are preserved when serializing.
+  #[serde(skip)]
+  pub(crate) extra: BTreeMap<Vec<u8>, Bencode>,
 }
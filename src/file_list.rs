@@ -0,0 +1,164 @@
+use crate::common::*;
+
+/// An explicit list of torrent paths, and the paths of the files on disk that
+/// hold their contents.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FileList {
+  entries: Vec<(FilePath, PathBuf)>,
+}
+
+impl FileList {
+  /// Parse a file list from `input`. Each line contains a torrent path, with
+  /// components separated by `/`, a tab, and a source path. Empty lines and
+  /// lines starting with `#` are ignored.
+  pub(crate) fn parse(input: &Input) -> Result<Self, Error> {
+    let text = str::from_utf8(input.data()).map_err(|_| Error::FileListDecode {
+      list: input.source().clone(),
+    })?;
+
+    let mut entries = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    for (i, line) in text.lines().enumerate() {
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let mut fields = line.splitn(2, '\t');
+
+      let (torrent_path, source) = match (fields.next(), fields.next()) {
+        (Some(torrent_path), Some(source)) if !torrent_path.is_empty() && !source.is_empty() => {
+          (torrent_path, source)
+        }
+        _ => {
+          return Err(Error::FileListLine {
+            list: input.source().clone(),
+            line: i + 1,
+          })
+        }
+      };
+
+      let file_path = FilePath::from_relative_path(Path::new(torrent_path))?;
+
+      if !seen.insert(file_path.clone()) {
+        return Err(Error::FilePathDuplicate { path: file_path });
+      }
+
+      entries.push((file_path, PathBuf::from(source)));
+    }
+
+    if entries.is_empty() {
+      return Err(Error::FileListEmpty {
+        list: input.source().clone(),
+      });
+    }
+
+    // Paths inside a directory sort immediately after the directory's path,
+    // so a path that is also used as a directory is followed by a path
+    // inside it.
+    for (file, path) in seen.iter().zip(seen.iter().skip(1)) {
+      if path.components().starts_with(file.components()) {
+        return Err(Error::FilePathPrefix {
+          file: file.clone(),
+          path: path.clone(),
+        });
+      }
+    }
+
+    Ok(Self { entries })
+  }
+
+  /// Files in this list, in list order. Relative source paths are resolved
+  /// against the current directory of `env`.
+  pub(crate) fn files(self, env: &Env) -> Result<Files, Error> {
+    let root = env.resolve(".")?;
+
+    let mut contents = Vec::new();
+    let mut sources = BTreeMap::new();
+    let mut total_size = 0;
+
+    for (file_path, source) in self.entries {
+      let source = env.resolve(source)?;
+
+      let metadata = fs::metadata(&source).context(error::Filesystem { path: &source })?;
+
+      if !metadata.is_file() {
+        return Err(Error::FileListSource { path: source });
+      }
+
+      total_size += metadata.len();
+
+      contents.push(file_path.clone());
+      sources.insert(file_path, source);
+    }
+
+    Ok(Files::dir(root, Bytes(total_size), contents, sources))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn parse(text: &str) -> Result<FileList, Error> {
+    FileList::parse(&Input::new(
+      InputTarget::Path("list".into()),
+      text.as_bytes().to_vec(),
+    ))
+  }
+
+  #[test]
+  fn entries() {
+    assert_eq!(
+      parse("# comment\n\nb/c\t/mnt/x\na\trelative y\n").unwrap(),
+      FileList {
+        entries: vec![
+          (
+            FilePath::from_components(&["b", "c"]),
+            PathBuf::from("/mnt/x")
+          ),
+          (
+            FilePath::from_components(&["a"]),
+            PathBuf::from("relative y")
+          ),
+        ]
+      }
+    );
+  }
+
+  #[test]
+  fn missing_source() {
+    assert_matches!(parse("a\tb\nc\n"), Err(Error::FileListLine { line: 2, .. }));
+    assert_matches!(parse("a\t\n"), Err(Error::FileListLine { line: 1, .. }));
+  }
+
+  #[test]
+  fn invalid_path() {
+    assert_matches!(parse("../a\tb\n"), Err(Error::PathComponent { .. }));
+  }
+
+  #[test]
+  fn duplicate() {
+    assert_matches!(
+      parse("a/b\tx\na/b\ty\n"),
+      Err(Error::FilePathDuplicate { path }) if path == FilePath::from_components(&["a", "b"])
+    );
+  }
+
+  #[test]
+  fn prefix() {
+    assert_matches!(
+      parse("a/b\tx\na b\tz\na\ty\n"),
+      Err(Error::FilePathPrefix { file, path })
+      if file == FilePath::from_components(&["a"]) && path == FilePath::from_components(&["a", "b"])
+    );
+    assert_matches!(parse("a\tx\na b\ty\nab\tz\n"), Ok(_));
+  }
+
+  #[test]
+  fn empty() {
+    assert_matches!(parse("# nothing\n"), Err(Error::FileListEmpty { .. }));
+  }
+}
@@ -38,6 +38,12 @@ impl FilePath {
     Ok(FilePath { components })
   }
 
+  pub(crate) fn from_component(component: &str) -> FilePath {
+    FilePath {
+      components: vec![component.to_owned()],
+    }
+  }
+
   pub(crate) fn name(&self) -> &str {
     &self.components[self.components.len() - 1]
   }
@@ -46,6 +52,28 @@ impl FilePath {
     &self.components
   }
 
+  /// Returns a copy of this path with each component in Unicode Normalization
+  /// Form C.
+  pub(crate) fn nfc(&self) -> FilePath {
+    FilePath {
+      components: self
+        .components
+        .iter()
+        .map(|component| component.nfc().collect())
+        .collect(),
+    }
+  }
+
+  pub(crate) fn to_lowercase(&self) -> FilePath {
+    FilePath {
+      components: self
+        .components
+        .iter()
+        .map(|component| component.to_lowercase())
+        .collect(),
+    }
+  }
+
   pub(crate) fn absolute(&self, root: &Path) -> PathBuf {
     let mut absolute = root.to_owned();
     for component in &self.components {
@@ -0,0 +1,314 @@
+use crate::common::*;
+
+/// Opens files to be hashed, reading them with an `IoBackend`.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct FileReader {
+  backend: IoBackend,
+  /// Bytes to drop from the page cache at a time with `IoBackend::Fadvise`.
+  /// Zero if every read should be dropped.
+  piece_length: u64,
+  read_size: Option<usize>,
+}
+
+impl FileReader {
+  /// Alignment of buffers, file offsets, and read sizes for `O_DIRECT`.
+  pub(crate) const DIRECT_ALIGNMENT: usize = 4096;
+  /// Read size for `IoBackend::Direct` if not given.
+  const DIRECT_READ_SIZE: usize = 1 << 20;
+
+  pub(crate) fn new(backend: IoBackend, read_size: Option<Bytes>) -> Result<Self> {
+    let read_size = match read_size {
+      Some(bytes) => {
+        let read_size = usize::try_from(bytes.count()).unwrap_or(usize::max_value());
+
+        if read_size == 0 {
+          return Err(Error::ReadSizeZero);
+        }
+
+        if backend == IoBackend::Direct && read_size % Self::DIRECT_ALIGNMENT != 0 {
+          return Err(Error::ReadSizeUnaligned { bytes });
+        }
+
+        Some(read_size)
+      }
+      None => None,
+    };
+
+    Ok(Self {
+      piece_length: 0,
+      backend,
+      read_size,
+    })
+  }
+
+  /// Use `piece_length` as the amount of data after which pages are dropped
+  /// from the page cache, with `IoBackend::Fadvise`.
+  pub(crate) fn piece_length(self, piece_length: usize) -> Self {
+    Self {
+      piece_length: piece_length.into_u64(),
+      ..self
+    }
+  }
+
+  /// The most bytes that should be read at once. Unlimited, unless a read
+  /// size was given, or `O_DIRECT` is used.
+  pub(crate) fn read_size(&self) -> usize {
+    match (self.read_size, self.backend) {
+      (Some(read_size), _) => read_size,
+      (None, IoBackend::Direct) => Self::DIRECT_READ_SIZE,
+      (None, IoBackend::Buffered) | (None, IoBackend::Fadvise) => usize::max_value(),
+    }
+  }
+
+  pub(crate) fn open(&self, path: &Path) -> io::Result<Box<dyn BufRead>> {
+    Ok(match self.backend {
+      IoBackend::Buffered => Box::new(BufReader::new(SparseFile::open(path)?)),
+      IoBackend::Direct => Box::new(DirectFile::open(path, self.read_size())?),
+      IoBackend::Fadvise => Box::new(BufReader::new(DropBehind {
+        file: SparseFile::open(path)?,
+        interval: self.piece_length,
+        dropped: 0,
+        position: 0,
+      })),
+    })
+  }
+}
+
+/// Reads a file opened with `O_DIRECT`, bypassing the page cache, into an
+/// aligned buffer. Falls back to regular reads on platforms and filesystems
+/// that do not support `O_DIRECT`.
+struct DirectFile {
+  buffer: Vec<u8>,
+  consumed: usize,
+  eof: bool,
+  file: File,
+  filled: usize,
+  read_size: usize,
+  /// Offset of the first aligned byte of `buffer`.
+  start: usize,
+}
+
+impl DirectFile {
+  fn open(path: &Path, read_size: usize) -> io::Result<Self> {
+    let file = Self::open_direct(path)?;
+
+    let buffer = vec![0; read_size + FileReader::DIRECT_ALIGNMENT];
+
+    let start = buffer.as_ptr().align_offset(FileReader::DIRECT_ALIGNMENT);
+
+    Ok(Self {
+      consumed: 0,
+      eof: false,
+      filled: 0,
+      buffer,
+      file,
+      read_size,
+      start,
+    })
+  }
+
+  #[cfg(target_os = "linux")]
+  fn open_direct(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    match fs::OpenOptions::new()
+      .read(true)
+      .custom_flags(libc::O_DIRECT)
+      .open(path)
+    {
+      Err(error) if error.raw_os_error() == Some(libc::EINVAL) => File::open(path),
+      result => result,
+    }
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  fn open_direct(path: &Path) -> io::Result<File> {
+    File::open(path)
+  }
+}
+
+impl BufRead for DirectFile {
+  fn fill_buf(&mut self) -> io::Result<&[u8]> {
+    if self.consumed == self.filled && !self.eof {
+      self.consumed = 0;
+      self.filled = 0;
+
+      let buffer = &mut self.buffer[self.start..self.start + self.read_size];
+
+      while self.filled < buffer.len() {
+        let bytes_read = match self.file.read(&mut buffer[self.filled..]) {
+          Ok(bytes_read) => bytes_read,
+          Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+          Err(error) => return Err(error),
+        };
+
+        self.filled += bytes_read;
+
+        // Reads are only short at the end of the file, after which the file
+        // offset is no longer aligned.
+        if bytes_read == 0 || self.filled % FileReader::DIRECT_ALIGNMENT != 0 {
+          self.eof = true;
+          break;
+        }
+      }
+    }
+
+    Ok(&self.buffer[self.start + self.consumed..self.start + self.filled])
+  }
+
+  fn consume(&mut self, amount: usize) {
+    self.consumed = (self.consumed + amount).min(self.filled);
+  }
+}
+
+impl Read for DirectFile {
+  fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+    let available = self.fill_buf()?;
+    let bytes_read = available.len().min(buffer.len());
+    buffer[..bytes_read].copy_from_slice(&available[..bytes_read]);
+    self.consume(bytes_read);
+    Ok(bytes_read)
+  }
+}
+
+/// Reads a file, and advises the kernel with `posix_fadvise` that pages that
+/// have been read are no longer needed, after every `interval` bytes.
+struct DropBehind {
+  dropped: u64,
+  file: SparseFile,
+  interval: u64,
+  position: u64,
+}
+
+impl DropBehind {
+  #[cfg(target_os = "linux")]
+  fn drop_cache(&mut self) {
+    use std::os::unix::io::AsRawFd;
+
+    if let (Ok(offset), Ok(len)) = (
+      libc::off_t::try_from(self.dropped),
+      libc::off_t::try_from(self.position - self.dropped),
+    ) {
+      // SAFETY: `posix_fadvise` takes no pointers, and `self.file` owns a
+      // valid file descriptor. The advice is only a hint, so errors are
+      // ignored.
+      unsafe {
+        libc::posix_fadvise(
+          self.file.file().as_raw_fd(),
+          offset,
+          len,
+          libc::POSIX_FADV_DONTNEED,
+        );
+      }
+    }
+
+    self.dropped = self.position;
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  fn drop_cache(&mut self) {
+    self.dropped = self.position;
+  }
+}
+
+impl Read for DropBehind {
+  fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+    let bytes_read = self.file.read(buffer)?;
+
+    self.position += bytes_read.into_u64();
+
+    if bytes_read == 0 || self.position - self.dropped >= self.interval {
+      self.drop_cache();
+    }
+
+    Ok(bytes_read)
+  }
+}
+
+impl Drop for DropBehind {
+  fn drop(&mut self) {
+    if self.position > self.dropped {
+      self.drop_cache();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn contents(len: usize) -> Vec<u8> {
+    (0..len)
+      .map(|i| u8::try_from(i % 251).unwrap())
+      .collect::<Vec<u8>>()
+  }
+
+  #[test]
+  fn backends() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    for len in &[0, 1, 4095, 4096, 4097, 3 * 4096, 100_000] {
+      let path = tempdir.path().join(len.to_string());
+      let expected = contents(*len);
+      fs::write(&path, &expected).unwrap();
+
+      for backend in &[IoBackend::Buffered, IoBackend::Direct, IoBackend::Fadvise] {
+        for read_size in &[None, Some(Bytes(4096)), Some(Bytes(8192))] {
+          let reader = FileReader::new(*backend, *read_size)
+            .unwrap()
+            .piece_length(16 * 1024);
+
+          let mut actual = Vec::new();
+          reader
+            .open(&path)
+            .unwrap()
+            .read_to_end(&mut actual)
+            .unwrap();
+
+          assert!(
+            actual == expected,
+            "{} bytes with {} backend and read size {:?}",
+            len,
+            backend,
+            read_size
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn read_size() {
+    assert_eq!(FileReader::default().read_size(), usize::max_value());
+    assert_eq!(
+      FileReader::new(IoBackend::Direct, None)
+        .unwrap()
+        .read_size(),
+      1 << 20
+    );
+    assert_eq!(
+      FileReader::new(IoBackend::Fadvise, Some(Bytes(100)))
+        .unwrap()
+        .read_size(),
+      100
+    );
+  }
+
+  #[test]
+  fn read_size_invalid() {
+    assert_matches!(
+      FileReader::new(IoBackend::Buffered, Some(Bytes(0))),
+      Err(Error::ReadSizeZero)
+    );
+    assert_matches!(
+      FileReader::new(IoBackend::Direct, Some(Bytes(4097))),
+      Err(Error::ReadSizeUnaligned { bytes: Bytes(4097) })
+    );
+    assert_matches!(
+      FileReader::new(IoBackend::Buffered, Some(Bytes(4097))),
+      Ok(_)
+    );
+  }
+}
@@ -7,14 +7,7 @@ pub(crate) struct FileStatus {
 }
 
 impl FileStatus {
-  pub(crate) fn status(
-    absolute: &Path,
-    path: FilePath,
-    length: Bytes,
-    md5: Option<Md5Digest>,
-  ) -> Self {
-    let error = FileError::verify(absolute, length, md5).err();
-
+  pub(crate) fn new(path: FilePath, error: Option<FileError>) -> Self {
     FileStatus { path, error }
   }
 
@@ -1,26 +1,52 @@
 use crate::common::*;
 
-#[derive(Debug)]
 pub(crate) struct Files {
   root: PathBuf,
-  total_size: Bytes,
-  contents: Option<Vec<FilePath>>,
+  total_size: Option<Bytes>,
+  contents: Contents,
+}
+
+pub(crate) enum Contents {
+  /// `root` is a single file.
+  File,
+  /// Files that were all found before hashing.
+  List {
+    paths: Vec<FilePath>,
+    sources: BTreeMap<FilePath, PathBuf>,
+  },
+  /// Files that are found while they are being hashed, in path order.
+  Stream(FileStream),
 }
 
 impl Files {
   pub(crate) fn file(root: PathBuf, total_size: Bytes) -> Files {
     Files {
-      contents: None,
+      contents: Contents::File,
+      total_size: Some(total_size),
       root,
-      total_size,
     }
   }
 
-  pub(crate) fn dir(root: PathBuf, total_size: Bytes, contents: Vec<FilePath>) -> Files {
+  pub(crate) fn dir(
+    root: PathBuf,
+    total_size: Bytes,
+    paths: Vec<FilePath>,
+    sources: BTreeMap<FilePath, PathBuf>,
+  ) -> Files {
     Files {
-      contents: Some(contents),
+      contents: Contents::List { paths, sources },
+      total_size: Some(total_size),
+      root,
+    }
+  }
+
+  /// Files in the directory at `root`, produced by `stream`. Their total
+  /// size is not known until they have all been found.
+  pub(crate) fn stream(root: PathBuf, stream: FileStream) -> Files {
+    Files {
+      contents: Contents::Stream(stream),
+      total_size: None,
       root,
-      total_size,
     }
   }
 
@@ -28,11 +54,45 @@ impl Files {
     &self.root
   }
 
+  /// Paths of the files, if they are already known.
   pub(crate) fn contents(&self) -> Option<&[FilePath]> {
-    self.contents.as_deref()
+    match &self.contents {
+      Contents::List { paths, .. } => Some(paths),
+      Contents::File | Contents::Stream(_) => None,
+    }
+  }
+
+  pub(crate) fn is_stream(&self) -> bool {
+    matches!(self.contents, Contents::Stream(_))
+  }
+
+  pub(crate) fn into_contents(self) -> Contents {
+    self.contents
+  }
+
+  /// Path of the file on disk that holds the contents of `file_path`. This is
+  /// usually `file_path` relative to the root, but may differ, for example
+  /// if `file_path` has been Unicode-normalized.
+  #[cfg(test)]
+  pub(crate) fn source(&self, file_path: &FilePath) -> PathBuf {
+    match &self.contents {
+      Contents::List { sources, .. } => Self::source_in(&self.root, sources, file_path),
+      Contents::File | Contents::Stream(_) => file_path.absolute(&self.root),
+    }
+  }
+
+  pub(crate) fn source_in(
+    root: &Path,
+    sources: &BTreeMap<FilePath, PathBuf>,
+    file_path: &FilePath,
+  ) -> PathBuf {
+    match sources.get(file_path) {
+      Some(source) => source.clone(),
+      None => file_path.absolute(root),
+    }
   }
 
-  pub(crate) fn total_size(&self) -> Bytes {
+  pub(crate) fn total_size(&self) -> Option<Bytes> {
     self.total_size
   }
 }
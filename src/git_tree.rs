@@ -0,0 +1,372 @@
+use crate::common::*;
+
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// The files in the tree of a commit in a git repository, read with the `git`
+/// command.
+pub(crate) struct GitTree {
+  commit: String,
+  files: Vec<GitBlob>,
+  name: String,
+  repo: PathBuf,
+  skipped: Vec<(PathBuf, SkipReason)>,
+  total_size: Bytes,
+}
+
+/// A regular file in a `GitTree`, and the ID of the blob object holding its
+/// contents.
+#[derive(Debug)]
+pub(crate) struct GitBlob {
+  pub(crate) info: FileInfo,
+  pub(crate) object: String,
+}
+
+/// A `git cat-file --batch` process, from which blob contents are read.
+pub(crate) struct GitObjects {
+  child: Child,
+  stdin: ChildStdin,
+  stdout: BufReader<ChildStdout>,
+}
+
+impl GitTree {
+  /// Load the tree of the commit that `rev` names in the repository at
+  /// `repo`, with files ordered by `sort_by`.
+  pub(crate) fn load(repo: &Path, rev: &str, sort_by: &[SortSpec]) -> Result<Self> {
+    let revision = || Error::GitRevision {
+      repo: repo.to_owned(),
+      rev: rev.to_owned(),
+    };
+
+    if rev.starts_with('-') {
+      return Err(revision());
+    }
+
+    let commit = match Self::git(
+      repo,
+      &[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", rev),
+      ],
+    ) {
+      Ok(commit) => commit,
+      Err(Error::CommandStatus { .. }) => return Err(revision()),
+      Err(error) => return Err(error),
+    };
+
+    let commit = Self::line(commit)?;
+
+    let name = Self::repo_name(repo)?;
+
+    let output = Self::git(
+      repo,
+      &["ls-tree", "-r", "-z", "--long", "--full-tree", &commit],
+    )?;
+
+    let (mut files, skipped) = Self::parse(&output)?;
+
+    files.sort_by(|a, b| SortSpec::compare(sort_by, &a.info, &b.info));
+
+    let total_size = files.iter().map(|file| file.info.length).sum();
+
+    Ok(Self {
+      repo: repo.to_owned(),
+      commit,
+      files,
+      name,
+      skipped,
+      total_size,
+    })
+  }
+
+  /// The full hash of the commit.
+  pub(crate) fn commit(&self) -> &str {
+    &self.commit
+  }
+
+  pub(crate) fn files(&self) -> &[GitBlob] {
+    &self.files
+  }
+
+  /// The name of the repository: the name of the working tree's top-level
+  /// directory, or of a bare repository's directory, without `.git`.
+  pub(crate) fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Entries that are not regular files, like symbolic links and submodules,
+  /// and so are not included in `files`.
+  pub(crate) fn skipped(&self) -> &[(PathBuf, SkipReason)] {
+    &self.skipped
+  }
+
+  pub(crate) fn total_size(&self) -> Bytes {
+    self.total_size
+  }
+
+  /// Start a process from which the contents of `files` can be read.
+  pub(crate) fn objects(&self) -> Result<GitObjects> {
+    let command = "git cat-file --batch";
+
+    let mut child = Command::new("git")
+      .arg("-C")
+      .arg(&self.repo)
+      .args(&["cat-file", "--batch"])
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .context(error::CommandInvoke { command })?;
+
+    let stdin = child.stdin.take().invariant_unwrap("child stdin is piped");
+
+    let stdout = child
+      .stdout
+      .take()
+      .invariant_unwrap("child stdout is piped");
+
+    Ok(GitObjects {
+      stdout: BufReader::new(stdout),
+      child,
+      stdin,
+    })
+  }
+
+  fn repo_name(repo: &Path) -> Result<String> {
+    let bare = Self::line(Self::git(repo, &["rev-parse", "--is-bare-repository"])?)? == "true";
+
+    let dir = if bare {
+      Self::line(Self::git(repo, &["rev-parse", "--absolute-git-dir"])?)?
+    } else {
+      Self::line(Self::git(repo, &["rev-parse", "--show-toplevel"])?)?
+    };
+
+    let dir = PathBuf::from(dir);
+
+    let filename = dir
+      .file_name()
+      .ok_or_else(|| Error::FilenameExtract { path: dir.clone() })?;
+
+    let filename = filename.to_str().ok_or_else(|| Error::FilenameDecode {
+      filename: PathBuf::from(filename),
+    })?;
+
+    Ok(
+      filename
+        .strip_suffix(".git")
+        .filter(|name| bare && !name.is_empty())
+        .unwrap_or(filename)
+        .to_owned(),
+    )
+  }
+
+  /// Parse the output of `git ls-tree -r -z --long`.
+  fn parse(output: &[u8]) -> Result<(Vec<GitBlob>, Vec<(PathBuf, SkipReason)>)> {
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+
+    for record in output.split(|byte| *byte == 0) {
+      if record.is_empty() {
+        continue;
+      }
+
+      let unexpected = || {
+        Error::internal(format!(
+          "Unexpected `git ls-tree` output: `{}`",
+          String::from_utf8_lossy(record)
+        ))
+      };
+
+      let tab = record
+        .iter()
+        .position(|byte| *byte == b'\t')
+        .ok_or_else(unexpected)?;
+
+      let fields = str::from_utf8(&record[..tab])
+        .map_err(|_| unexpected())?
+        .split_whitespace()
+        .collect::<Vec<&str>>();
+
+      let (mode, kind, object, size) = match fields.as_slice() {
+        [mode, kind, object, size] => (*mode, *kind, *object, *size),
+        _ => return Err(unexpected()),
+      };
+
+      let path = str::from_utf8(&record[tab + 1..]).map_err(|_| Error::FilenameDecode {
+        filename: PathBuf::from(String::from_utf8_lossy(&record[tab + 1..]).into_owned()),
+      })?;
+
+      match (mode, kind) {
+        ("120000", _) => skipped.push((PathBuf::from(path), SkipReason::Symlink)),
+        (_, "commit") => skipped.push((PathBuf::from(path), SkipReason::Submodule)),
+        (_, "blob") => files.push(GitBlob {
+          info: FileInfo {
+            path: FilePath::from_relative_path(Path::new(path))?,
+            length: Bytes(size.parse().map_err(|_| unexpected())?),
+            md5sum: None,
+            extra: BTreeMap::new(),
+          },
+          object: object.to_owned(),
+        }),
+        _ => return Err(unexpected()),
+      }
+    }
+
+    Ok((files, skipped))
+  }
+
+  /// Run `git` in `repo` with `args`, and return its standard output.
+  /// Standard error is passed through, so that git's error messages are
+  /// shown.
+  fn git(repo: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let command = format!("git {}", args.join(" "));
+
+    let output = Command::new("git")
+      .arg("-C")
+      .arg(repo)
+      .args(args)
+      .stdin(Stdio::null())
+      .stderr(Stdio::inherit())
+      .output()
+      .context(error::CommandInvoke { command: &command })?;
+
+    if !output.status.success() {
+      return Err(Error::CommandStatus {
+        status: output.status,
+        command,
+      });
+    }
+
+    Ok(output.stdout)
+  }
+
+  fn line(output: Vec<u8>) -> Result<String> {
+    let line = String::from_utf8(output)
+      .map_err(|_| Error::internal("Unexpected non-UTF-8 output from `git rev-parse`"))?;
+
+    Ok(line.trim_end_matches('\n').to_owned())
+  }
+}
+
+impl GitObjects {
+  /// Call `f` with a reader over the contents of `blob`.
+  pub(crate) fn read<T>(
+    &mut self,
+    blob: &GitBlob,
+    f: impl FnOnce(&mut dyn BufRead) -> io::Result<T>,
+  ) -> io::Result<T> {
+    writeln!(self.stdin, "{}", blob.object)?;
+    self.stdin.flush()?;
+
+    let mut header = String::new();
+    self.stdout.read_line(&mut header)?;
+
+    let size = match header.split_whitespace().collect::<Vec<&str>>().as_slice() {
+      [_object, "blob", size] => size.parse::<u64>().ok(),
+      _ => None,
+    };
+
+    let size = size.ok_or_else(|| {
+      io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+          "Unexpected `git cat-file` output for object {}: `{}`",
+          blob.object,
+          header.trim_end()
+        ),
+      )
+    })?;
+
+    let mut contents = (&mut self.stdout).take(size);
+
+    let value = f(&mut contents)?;
+
+    io::copy(&mut contents, &mut io::sink())?;
+
+    let mut newline = [0];
+    self.stdout.read_exact(&mut newline)?;
+
+    Ok(value)
+  }
+
+  /// Wait for `git` to exit.
+  pub(crate) fn finish(self) -> Result<()> {
+    let command = "git cat-file --batch";
+
+    let Self {
+      mut child, stdin, ..
+    } = self;
+
+    drop(stdin);
+
+    let status = child.wait().context(error::CommandInvoke { command })?;
+
+    if !status.success() {
+      return Err(Error::CommandStatus {
+        command: command.to_owned(),
+        status,
+      });
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn parse() {
+    let output = b"100644 blob 0123 5\ta\0\
+                   100755 blob 4567    12\tb/c d\0\
+                   120000 blob 89ab 3\tlink\0\
+                   160000 commit cdef -\tsub\0";
+
+    let (files, skipped) = GitTree::parse(output).unwrap();
+
+    assert_eq!(
+      files
+        .iter()
+        .map(|file| (file.info.clone(), file.object.as_str()))
+        .collect::<Vec<(FileInfo, &str)>>(),
+      &[
+        (
+          FileInfo {
+            path: FilePath::from_components(&["a"]),
+            length: Bytes(5),
+            md5sum: None,
+            extra: BTreeMap::new(),
+          },
+          "0123"
+        ),
+        (
+          FileInfo {
+            path: FilePath::from_components(&["b", "c d"]),
+            length: Bytes(12),
+            md5sum: None,
+            extra: BTreeMap::new(),
+          },
+          "4567"
+        ),
+      ]
+    );
+
+    assert_eq!(
+      skipped,
+      &[
+        (PathBuf::from("link"), SkipReason::Symlink),
+        (PathBuf::from("sub"), SkipReason::Submodule),
+      ]
+    );
+  }
+
+  #[test]
+  fn parse_unexpected() {
+    for output in &[&b"100644 blob 0123\ta\0"[..], b"100644 blob 0123 5 a\0"] {
+      assert_matches!(GitTree::parse(output), Err(Error::Internal { .. }));
+    }
+  }
+}
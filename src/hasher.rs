@@ -2,22 +2,42 @@ use crate::common::*;
 
 pub(crate) struct Hasher {
   buffer: Vec<u8>,
+  crc32: bool,
   length: u64,
+  manifest: Option<Manifest>,
+  manifest_md5: bool,
   md5sum: bool,
+  path_checker: Option<PathChecker>,
   piece_bytes_hashed: usize,
   piece_length: usize,
   pieces: PieceList,
+  reader: FileReader,
   sha1: Sha1,
+  sha256: bool,
   progress_bar: Option<ProgressBar>,
 }
 
+/// Length and checksums of a single file.
+struct FileHashes {
+  length: Bytes,
+  md5: Option<Md5Digest>,
+  sha256: Option<Sha256Digest>,
+  crc32: Option<u32>,
+}
+
 impl Hasher {
   pub(crate) fn new(md5sum: bool, piece_length: usize, progress_bar: Option<ProgressBar>) -> Self {
     Self {
       buffer: vec![0; piece_length],
+      crc32: false,
       length: 0,
+      manifest: None,
+      manifest_md5: false,
+      sha256: false,
       piece_bytes_hashed: 0,
+      path_checker: None,
       pieces: PieceList::new(),
+      reader: FileReader::default().piece_length(piece_length),
       sha1: Sha1::new(),
       piece_length,
       md5sum,
@@ -25,30 +45,167 @@ impl Hasher {
     }
   }
 
-  pub(crate) fn hash_files(mut self, files: &Files) -> Result<(Mode, PieceList), Error> {
-    let mode = if let Some(contents) = files.contents() {
-      let files = self.hash_contents(&files.root(), contents)?;
+  /// Also produce a manifest of the hashed files, with the checksums required
+  /// by `format`, and SHA-256 checksums if `sha256` is true. Checksums are
+  /// calculated in the same pass over the content as piece hashes.
+  pub(crate) fn manifest(mut self, format: ManifestFormat, sha256: bool) -> Self {
+    self.manifest = Some(Manifest::new());
+    self.manifest_md5 = format == ManifestFormat::Md5sum;
+    self.sha256 = sha256 || format == ManifestFormat::Sha256sum;
+    self.crc32 = format == ManifestFormat::Sfv;
+    self
+  }
 
-      Mode::Multiple { files }
-    } else {
-      let (md5sum, length) = self.hash_file(files.root())?;
+  /// Check the paths of files with `checker` as they are found, before they
+  /// are hashed. Only files streamed from a directory or a tar archive are
+  /// checked, since the paths of other files are known in advance.
+  pub(crate) fn path_checker(mut self, checker: PathChecker) -> Self {
+    self.path_checker = Some(checker);
+    self
+  }
+
+  /// Read files with `reader`.
+  pub(crate) fn reader(mut self, reader: FileReader) -> Self {
+    self.reader = reader.piece_length(self.piece_length);
+    self
+  }
+
+  pub(crate) fn hash_files(
+    mut self,
+    files: Files,
+    name: &str,
+  ) -> Result<(Mode, PieceList, Option<Manifest>), Error> {
+    let root = files.root().to_owned();
 
-      Mode::Single { md5sum, length }
+    let mode = match files.into_contents() {
+      Contents::File => {
+        let hashes = self.hash_file(&root)?;
+        let length = hashes.length;
+        let md5sum = self.record(name.to_owned(), hashes);
+
+        Mode::Single { md5sum, length }
+      }
+      Contents::List { paths, sources } => Mode::Multiple {
+        files: self.hash_contents(&root, &paths, &sources)?,
+      },
+      Contents::Stream(stream) => Mode::Multiple {
+        files: self.hash_stream(stream)?,
+      },
     };
 
     self.finish();
 
-    Ok((mode, self.pieces))
+    Ok((mode, self.pieces, self.manifest))
   }
 
-  pub(crate) fn hash_stdin(mut self, stdin: &mut dyn BufRead) -> Result<(Mode, PieceList), Error> {
-    let (md5sum, length) = self.hash_read_io(stdin).context(error::Stdin)?;
+  pub(crate) fn hash_stdin(
+    mut self,
+    stdin: &mut dyn BufRead,
+    name: &str,
+  ) -> Result<(Mode, PieceList, Option<Manifest>), Error> {
+    let hashes = self.hash_read_io(stdin).context(error::Stdin)?;
+    let length = hashes.length;
+    let md5sum = self.record(name.to_owned(), hashes);
 
     let mode = Mode::Single { md5sum, length };
 
     self.finish();
 
-    Ok((mode, self.pieces))
+    Ok((mode, self.pieces, self.manifest))
+  }
+
+  /// Hash the regular files in the tar archive read from `stdin`, in the
+  /// order in which they appear in the archive. Other entries, like
+  /// directories and symlinks, are skipped.
+  pub(crate) fn hash_tar(
+    mut self,
+    stdin: &mut dyn BufRead,
+  ) -> Result<(Mode, PieceList, Option<Manifest>), Error> {
+    let mut tar = TarReader::new(stdin);
+    let mut files = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    while let Some(entry) = tar.next_entry().context(error::Stdin)? {
+      if !entry.file {
+        continue;
+      }
+
+      let path = entry.file_path()?;
+
+      if !seen.insert(path.clone()) {
+        return Err(Error::FilePathDuplicate { path });
+      }
+
+      self.check_path(&path)?;
+
+      let hashes = self.hash_read_io(&mut tar).context(error::Stdin)?;
+      let length = hashes.length;
+      let md5sum = self.record(path.to_string(), hashes);
+
+      files.push(FileInfo {
+        path,
+        md5sum,
+        length,
+        extra: BTreeMap::new(),
+      });
+    }
+
+    self.finish();
+
+    Ok((Mode::Multiple { files }, self.pieces, self.manifest))
+  }
+
+  /// Hash the files in `tree`, reading their contents from the repository.
+  pub(crate) fn hash_git(
+    mut self,
+    tree: &GitTree,
+  ) -> Result<(Mode, PieceList, Option<Manifest>), Error> {
+    let command = "git cat-file --batch";
+
+    let mut objects = tree.objects()?;
+    let mut files = Vec::new();
+
+    for blob in tree.files() {
+      let hashes = objects
+        .read(blob, |contents| self.hash_read_io(contents))
+        .context(error::CommandInvoke { command })?;
+      let length = hashes.length;
+      let md5sum = self.record(blob.info.path.to_string(), hashes);
+
+      files.push(FileInfo {
+        path: blob.info.path.clone(),
+        md5sum,
+        length,
+        extra: BTreeMap::new(),
+      });
+    }
+
+    objects.finish()?;
+
+    self.finish();
+
+    Ok((Mode::Multiple { files }, self.pieces, self.manifest))
+  }
+
+  /// Add a manifest entry for the file at `path`, if a manifest is being
+  /// produced, and return its MD5 checksum, if it should be included in the
+  /// torrent.
+  fn record(&mut self, path: String, hashes: FileHashes) -> Option<Md5Digest> {
+    if let Some(manifest) = &mut self.manifest {
+      manifest.push(ManifestEntry {
+        length: hashes.length,
+        md5: hashes.md5,
+        sha256: hashes.sha256,
+        crc32: hashes.crc32,
+        path,
+      });
+    }
+
+    if self.md5sum {
+      hashes.md5
+    } else {
+      None
+    }
   }
 
   fn finish(&mut self) {
@@ -63,43 +220,114 @@ impl Hasher {
     &mut self,
     root: &Path,
     file_paths: &[FilePath],
+    sources: &BTreeMap<FilePath, PathBuf>,
   ) -> Result<Vec<FileInfo>, Error> {
     let mut files = Vec::new();
 
     for file_path in file_paths {
-      let path = file_path.absolute(root);
+      let path = Files::source_in(root, sources, file_path);
 
-      let (md5sum, length) = self.hash_file(&path)?;
+      let hashes = self.hash_file(&path)?;
+      let length = hashes.length;
+      let md5sum = self.record(file_path.to_string(), hashes);
 
       files.push(FileInfo {
         path: file_path.clone(),
         md5sum,
         length,
+        extra: BTreeMap::new(),
+      });
+    }
+
+    Ok(files)
+  }
+
+  /// Hash files as they are found by `stream`.
+  fn hash_stream(&mut self, mut stream: FileStream) -> Result<Vec<FileInfo>, Error> {
+    let mut files = Vec::new();
+
+    for entry in &mut stream {
+      let (path, source) = match entry? {
+        WalkEntry::File { path, source, .. } => (path, source),
+        WalkEntry::Skipped { .. } => continue,
+      };
+
+      self.check_path(&path)?;
+
+      let hashes = self.hash_file(&source)?;
+      let length = hashes.length;
+      let md5sum = self.record(path.to_string(), hashes);
+
+      files.push(FileInfo {
+        path,
+        md5sum,
+        length,
+        extra: BTreeMap::new(),
       });
     }
 
+    // Unicode normalization may have given files in different directories
+    // the same path.
+    if stream.renamed() {
+      let mut paths = files
+        .iter()
+        .map(|file| &file.path)
+        .collect::<Vec<&FilePath>>();
+
+      paths.sort();
+
+      for pair in paths.windows(2) {
+        if pair[0] == pair[1] {
+          return Err(Error::FilePathDuplicate {
+            path: pair[0].clone(),
+          });
+        }
+      }
+    }
+
     Ok(files)
   }
 
-  fn hash_file(&mut self, path: &Path) -> Result<(Option<Md5Digest>, Bytes), Error> {
-    let file = File::open(path).context(error::Filesystem { path })?;
+  fn check_path(&mut self, path: &FilePath) -> Result<(), Error> {
+    match &mut self.path_checker {
+      Some(checker) => checker.check(path),
+      None => Ok(()),
+    }
+  }
+
+  fn hash_file(&mut self, path: &Path) -> Result<FileHashes, Error> {
+    let mut file = self.reader.open(path).context(error::Filesystem { path })?;
 
     self
-      .hash_read_io(&mut BufReader::new(file))
+      .hash_read_io(&mut file)
       .context(error::Filesystem { path })
   }
 
-  fn hash_read_io(&mut self, file: &mut dyn BufRead) -> io::Result<(Option<Md5Digest>, Bytes)> {
+  fn hash_read_io(&mut self, file: &mut dyn BufRead) -> io::Result<FileHashes> {
     let mut bytes_hashed = 0;
 
-    let mut md5 = if self.md5sum {
+    let mut md5 = if self.md5sum || self.manifest_md5 {
       Some(md5::Context::new())
     } else {
       None
     };
 
+    let mut sha256 = if self.sha256 {
+      Some(Sha256::new())
+    } else {
+      None
+    };
+
+    let mut crc32 = if self.crc32 {
+      Some(crc32fast::Hasher::new())
+    } else {
+      None
+    };
+
     loop {
-      let remaining = &mut self.buffer[..self.piece_length - self.piece_bytes_hashed];
+      let limit = (self.piece_length - self.piece_bytes_hashed).min(self.reader.read_size());
+
+      let remaining = &mut self.buffer[..limit];
 
       let bytes_read = file.read(remaining)?;
 
@@ -124,6 +352,14 @@ impl Hasher {
         md5.consume(read);
       }
 
+      if let Some(sha256) = sha256.as_mut() {
+        sha256.update(read);
+      }
+
+      if let Some(crc32) = crc32.as_mut() {
+        crc32.update(read);
+      }
+
       if let Some(progress_bar) = &self.progress_bar {
         progress_bar.inc(bytes_read.into_u64());
       }
@@ -131,9 +367,11 @@ impl Hasher {
 
     self.length += bytes_hashed.into_u64();
 
-    Ok((
-      md5.map(|context| context.compute().into()),
-      Bytes::from(bytes_hashed.into_u64()),
-    ))
+    Ok(FileHashes {
+      length: Bytes::from(bytes_hashed.into_u64()),
+      md5: md5.map(|context| context.compute().into()),
+      sha256: sha256.map(|sha256| sha256.finalize().into()),
+      crc32: crc32.map(crc32fast::Hasher::finalize),
+    })
   }
 }
@@ -0,0 +1,109 @@
+use crate::common::*;
+
+use miniz_oxide::{
+  inflate::stream::{inflate, InflateState},
+  DataFormat, MZError, MZFlush, MZStatus,
+};
+
+/// Decompresses a raw DEFLATE stream, as used by zip archives.
+pub(crate) struct Inflater<R: BufRead> {
+  done: bool,
+  input: R,
+  state: Box<InflateState>,
+}
+
+impl<R: BufRead> Inflater<R> {
+  pub(crate) fn new(input: R) -> Self {
+    Self {
+      done: false,
+      state: InflateState::new_boxed(DataFormat::Raw),
+      input,
+    }
+  }
+}
+
+impl<R: BufRead> Read for Inflater<R> {
+  fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+    while !self.done && !buffer.is_empty() {
+      let input = self.input.fill_buf()?;
+
+      let eof = input.is_empty();
+
+      let flush = if eof { MZFlush::Finish } else { MZFlush::None };
+
+      let result = inflate(&mut self.state, input, buffer, flush);
+
+      self.input.consume(result.bytes_consumed);
+
+      match result.status {
+        Ok(MZStatus::StreamEnd) => self.done = true,
+        // Decompression fails once the input runs out if the stream is
+        // incomplete.
+        Ok(_) | Err(_) if eof && result.bytes_written == 0 => {
+          return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Truncated compressed data",
+          ));
+        }
+        Ok(_) | Err(MZError::Buf) => {}
+        Err(_) => {
+          return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid compressed data",
+          ))
+        }
+      }
+
+      if result.bytes_written > 0 {
+        return Ok(result.bytes_written);
+      }
+    }
+
+    Ok(0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  use miniz_oxide::deflate::compress_to_vec;
+
+  #[test]
+  fn round_trip() {
+    let data = (0..100_000u32)
+      .map(|i| u8::try_from(i % 251).unwrap())
+      .collect::<Vec<u8>>();
+
+    let compressed = compress_to_vec(&data, 6);
+
+    let mut inflated = Vec::new();
+    Inflater::new(BufReader::with_capacity(7, compressed.as_slice()))
+      .read_to_end(&mut inflated)
+      .unwrap();
+
+    assert_eq!(inflated, data);
+  }
+
+  #[test]
+  fn truncated() {
+    let compressed = compress_to_vec(&[1; 1000], 6);
+
+    let error = Inflater::new(&compressed[..compressed.len() / 2])
+      .read_to_end(&mut Vec::new())
+      .unwrap_err();
+
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+  }
+
+  #[test]
+  fn invalid() {
+    let error = Inflater::new(&[0xff; 100][..])
+      .read_to_end(&mut Vec::new())
+      .unwrap_err();
+
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  }
+}
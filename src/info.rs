@@ -27,6 +27,10 @@ pub(crate) struct Info {
     rename = "update-url"
   )]
   pub(crate) update_url: Option<Url>,
+  /// Info dictionary keys not otherwise recognized, which are preserved when
+  /// serializing.
+  #[serde(skip)]
+  pub(crate) extra: BTreeMap<Vec<u8>, Bencode>,
 }
 
 impl Info {
@@ -35,7 +39,43 @@ impl Info {
   }
 
   pub(crate) fn infohash(&self) -> Result<Infohash> {
-    let encoded = bendy::serde::ser::to_bytes(self).context(error::InfoSerialize)?;
+    let mut encoded = bendy::serde::ser::to_bytes(self).context(error::InfoSerialize)?;
+
+    if self.has_extra() {
+      let mut value = BencodeParser::new(&encoded)
+        .parse()
+        .invariant("Serialized info dictionary is valid bencode")?;
+      self.insert_extra(&mut value);
+      encoded = value.encode();
+    }
+
     Ok(Infohash::from_bencoded_info_dict(&encoded))
   }
+
+  /// Whether or not this info dictionary, or any of its file dictionaries,
+  /// has unrecognized keys.
+  pub(crate) fn has_extra(&self) -> bool {
+    if !self.extra.is_empty() {
+      return true;
+    }
+
+    match &self.mode {
+      Mode::Single { .. } => false,
+      Mode::Multiple { files } => files.iter().any(|file| !file.extra.is_empty()),
+    }
+  }
+
+  /// Insert unrecognized keys into `value`, the serialized form of this info
+  /// dictionary.
+  pub(crate) fn insert_extra(&self, value: &mut Bencode) {
+    value.insert_missing(&self.extra);
+
+    if let (Mode::Multiple { files }, Bencode::Dict(dict)) = (&self.mode, value) {
+      if let Some(Bencode::List(list)) = dict.get_mut(b"files".as_ref()) {
+        for (file, value) in files.iter().zip(list) {
+          value.insert_missing(&file.extra);
+        }
+      }
+    }
+  }
 }
@@ -1,41 +1,37 @@
 use crate::common::*;
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
 pub(crate) struct Infohash {
   inner: Sha1Digest,
 }
 
 impl Infohash {
+  /// Compute the infohash of the metainfo in `input`. The infohash is
+  /// calculated from the bytes of the info dictionary as they appear in
+  /// `input`, so that it matches the infohash calculated by other clients,
+  /// even if the info dictionary is not canonically encoded.
   pub(crate) fn from_input(input: &Input) -> Result<Infohash, Error> {
-    let value = Value::from_bencode(input.data()).map_err(|error| Error::MetainfoDecode {
+    let mut parser = BencodeParser::new(input.data());
+
+    let value = parser.parse().context(error::MetainfoDecode {
       input: input.source().clone(),
-      error,
     })?;
 
     match value {
-      Value::Dict(metainfo) => {
-        let info = metainfo
-          .iter()
-          .find(|pair: &(&Cow<[u8]>, &Value)| pair.0.as_ref() == b"info")
-          .ok_or_else(|| Error::MetainfoValidate {
-            input: input.source().clone(),
-            source: MetainfoError::InfoMissing,
-          })?
-          .1;
-
-        if let Value::Dict(_) = info {
-          let encoded = info.to_bencode().map_err(|error| {
-            Error::internal(format!("Failed to re-encode info dictionary: {}", error))
-          })?;
-
-          Ok(Self::from_bencoded_info_dict(&encoded))
-        } else {
-          Err(Error::MetainfoValidate {
-            input: input.source().clone(),
-            source: MetainfoError::InfoType,
-          })
+      Bencode::Dict(metainfo) => match metainfo.get(b"info".as_ref()) {
+        Some(Bencode::Dict(_)) => {
+          let span = parser.span(b"info").invariant("Info dictionary has span")?;
+          Ok(Self::from_bencoded_info_dict(&input.data()[span]))
         }
-      }
+        Some(_) => Err(Error::MetainfoValidate {
+          input: input.source().clone(),
+          source: MetainfoError::InfoType,
+        }),
+        None => Err(Error::MetainfoValidate {
+          input: input.source().clone(),
+          source: MetainfoError::InfoMissing,
+        }),
+      },
       _ => Err(Error::MetainfoValidate {
         input: input.source().clone(),
         source: MetainfoError::Type,
@@ -87,6 +83,20 @@ mod tests {
     );
   }
 
+  #[test]
+  fn non_canonical_info() {
+    let tempdir = temptree! {
+      foo: "d4:infod1:bi1e1:ai01eee",
+    };
+
+    let path = tempdir.path().join("foo");
+
+    assert_eq!(
+      Infohash::load(&path).unwrap(),
+      Infohash::from_bencoded_info_dict(b"d1:bi1e1:ai01ee")
+    );
+  }
+
   #[test]
   fn load_wrong_type() {
     let tempdir = temptree! {
@@ -0,0 +1,220 @@
+use crate::common::*;
+
+use std::os::unix::io::RawFd;
+
+/// Size of the fixed part of `struct inotify_event`, which is followed by the
+/// NUL-padded name of the file the event concerns.
+const EVENT_HEADER_SIZE: usize = 16;
+
+const MASK: u32 = libc::IN_ATTRIB
+  | libc::IN_CLOSE_WRITE
+  | libc::IN_CREATE
+  | libc::IN_DELETE
+  | libc::IN_MODIFY
+  | libc::IN_MOVED_FROM
+  | libc::IN_MOVED_TO;
+
+/// Watches directories for changes to their contents, using Linux's inotify
+/// API.
+pub(crate) struct Inotify {
+  fd: RawFd,
+  watches: HashMap<i32, PathBuf>,
+}
+
+/// A change reported by `Inotify`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Change {
+  /// The file or directory at this path was changed.
+  Path(PathBuf),
+  /// The kernel's event queue overflowed, so changes may have been missed.
+  Overflow,
+}
+
+impl Inotify {
+  pub(crate) fn new() -> Result<Self> {
+    // SAFETY: `inotify_init1` takes no pointers, and returns either a new file
+    // descriptor, which is owned by the returned value, or -1.
+    let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+
+    if fd < 0 {
+      return Err(Error::Inotify {
+        source: io::Error::last_os_error(),
+      });
+    }
+
+    Ok(Self {
+      fd,
+      watches: HashMap::new(),
+    })
+  }
+
+  /// Watch the directory at `path`. Subdirectories are not watched, and must
+  /// be added separately.
+  pub(crate) fn add(&mut self, path: &Path) -> Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path =
+      std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Filesystem {
+        source: io::Error::new(io::ErrorKind::InvalidInput, "path contains NUL byte"),
+        path: path.to_owned(),
+      })?;
+
+    // SAFETY: `c_path` is a valid NUL-terminated string that outlives the
+    // call.
+    let wd = unsafe { libc::inotify_add_watch(self.fd, c_path.as_ptr(), MASK | libc::IN_ONLYDIR) };
+
+    if wd < 0 {
+      return Err(Error::Filesystem {
+        source: io::Error::last_os_error(),
+        path: path.to_owned(),
+      });
+    }
+
+    self.watches.insert(wd, path.to_owned());
+
+    Ok(())
+  }
+
+  /// Wait until changes are available, or until `timeout` elapses, and return
+  /// any changes. Waits indefinitely if `timeout` is `None`.
+  pub(crate) fn read(&mut self, timeout: Option<Duration>) -> Result<Vec<Change>> {
+    let timeout = match timeout {
+      Some(timeout) => i32::try_from(timeout.as_millis()).unwrap_or(i32::max_value()),
+      None => -1,
+    };
+
+    let mut pollfd = libc::pollfd {
+      fd: self.fd,
+      events: libc::POLLIN,
+      revents: 0,
+    };
+
+    // SAFETY: `pollfd` is a single valid `pollfd` struct.
+    let ready = unsafe { libc::poll(&mut pollfd, 1, timeout) };
+
+    if ready < 0 {
+      let source = io::Error::last_os_error();
+      if source.kind() == io::ErrorKind::Interrupted {
+        return Ok(Vec::new());
+      }
+      return Err(Error::Inotify { source });
+    }
+
+    let mut changes = Vec::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+      // SAFETY: `buffer` is valid for writes of `buffer.len()` bytes.
+      let read = unsafe { libc::read(self.fd, buffer.as_mut_ptr().cast(), buffer.len()) };
+
+      if read < 0 {
+        let source = io::Error::last_os_error();
+        if source.kind() == io::ErrorKind::WouldBlock {
+          break;
+        }
+        return Err(Error::Inotify { source });
+      }
+
+      let read = usize::try_from(read).invariant_unwrap("read is non-negative");
+
+      if read == 0 {
+        break;
+      }
+
+      self.parse(&buffer[..read], &mut changes);
+    }
+
+    Ok(changes)
+  }
+
+  fn parse(&mut self, mut events: &[u8], changes: &mut Vec<Change>) {
+    let field = |bytes: &[u8], offset: usize| {
+      let mut field = [0; 4];
+      field.copy_from_slice(&bytes[offset..offset + 4]);
+      field
+    };
+
+    while events.len() >= EVENT_HEADER_SIZE {
+      let wd = i32::from_ne_bytes(field(events, 0));
+      let mask = u32::from_ne_bytes(field(events, 4));
+      let len = u32::from_ne_bytes(field(events, 12)).into_usize();
+
+      let name = &events[EVENT_HEADER_SIZE..EVENT_HEADER_SIZE + len];
+      let name = &name[..name.iter().position(|b| *b == 0).unwrap_or(name.len())];
+
+      events = &events[EVENT_HEADER_SIZE + len..];
+
+      if mask & libc::IN_Q_OVERFLOW != 0 {
+        changes.push(Change::Overflow);
+        continue;
+      }
+
+      if mask & libc::IN_IGNORED != 0 {
+        self.watches.remove(&wd);
+        continue;
+      }
+
+      if let Some(dir) = self.watches.get(&wd) {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = if name.is_empty() {
+          dir.clone()
+        } else {
+          dir.join(OsStr::from_bytes(name))
+        };
+
+        changes.push(Change::Path(path));
+      }
+    }
+  }
+}
+
+impl Drop for Inotify {
+  fn drop(&mut self) {
+    // SAFETY: `self.fd` is owned by `self`, and is not used after this call.
+    unsafe {
+      libc::close(self.fd);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn changes() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let mut inotify = Inotify::new().unwrap();
+    inotify.add(tempdir.path()).unwrap();
+
+    assert_eq!(inotify.read(Some(Duration::from_millis(0))).unwrap(), &[]);
+
+    fs::create_dir(tempdir.path().join("foo")).unwrap();
+
+    assert_eq!(
+      inotify.read(Some(Duration::from_secs(10))).unwrap(),
+      &[Change::Path(tempdir.path().join("foo"))]
+    );
+
+    inotify.add(&tempdir.path().join("foo")).unwrap();
+
+    fs::write(tempdir.path().join("foo/bar"), "bar").unwrap();
+
+    let changes = inotify.read(Some(Duration::from_secs(10))).unwrap();
+    assert!(!changes.is_empty());
+    assert!(changes
+      .iter()
+      .all(|change| change == &Change::Path(tempdir.path().join("foo/bar"))));
+  }
+
+  #[test]
+  fn add_missing() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let mut inotify = Inotify::new().unwrap();
+    assert_matches!(
+      inotify.add(&tempdir.path().join("missing")),
+      Err(Error::Filesystem { .. })
+    );
+  }
+}
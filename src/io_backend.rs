@@ -0,0 +1,39 @@
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, EnumVariantNames, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum IoBackend {
+  Buffered,
+  Direct,
+  Fadvise,
+}
+
+impl IoBackend {
+  pub(crate) fn name(self) -> &'static str {
+    self.into()
+  }
+}
+
+impl Default for IoBackend {
+  fn default() -> Self {
+    Self::Buffered
+  }
+}
+
+impl Display for IoBackend {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn variants() {
+    assert_eq!(IoBackend::VARIANTS, &["buffered", "direct", "fadvise"]);
+  }
+}
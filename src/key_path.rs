@@ -0,0 +1,124 @@
+use crate::common::*;
+
+/// Path to values inside a bencoded value, using the same syntax as the key
+/// paths displayed by `imdl torrent stats`. Components are dictionary keys,
+/// separated by `/`. A `*` following a component selects every item of the
+/// list it refers to, so `foo*bar/baz` selects `baz` in the `bar` dictionary
+/// of each item of the list `foo`. List items may also be selected by index,
+/// so `foo/0/bar/baz` selects `baz` in the first item of `foo`.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct KeyPath {
+  components: Vec<KeyPathComponent>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum KeyPathComponent {
+  /// Select every item of a list.
+  Each,
+  /// Select a dictionary value by key, or a list item by index.
+  Key(String),
+}
+
+impl KeyPath {
+  pub(crate) fn components(&self) -> &[KeyPathComponent] {
+    &self.components
+  }
+}
+
+impl FromStr for KeyPath {
+  type Err = Error;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let segments = text.split('/').collect::<Vec<&str>>();
+    let last = segments.len() - 1;
+
+    let mut components = Vec::new();
+
+    for (i, segment) in segments.iter().enumerate() {
+      if segment.is_empty()
+        || (i > 0 && segment.starts_with('*'))
+        || (i < last && segment.ends_with('*'))
+      {
+        return Err(Error::KeyPathParse { text: text.into() });
+      }
+
+      for (j, key) in segment.split('*').enumerate() {
+        if j > 0 {
+          components.push(KeyPathComponent::Each);
+        }
+
+        if !key.is_empty() {
+          components.push(KeyPathComponent::Key(key.into()));
+        }
+      }
+    }
+
+    Ok(Self { components })
+  }
+}
+
+impl Display for KeyPath {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let mut previous: Option<&KeyPathComponent> = None;
+
+    for component in &self.components {
+      match component {
+        KeyPathComponent::Each => write!(f, "*")?,
+        KeyPathComponent::Key(key) => {
+          if let Some(KeyPathComponent::Key(_)) = previous {
+            write!(f, "/")?;
+          }
+          write!(f, "{}", key)?;
+        }
+      }
+      previous = Some(component);
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn key(text: &str) -> KeyPathComponent {
+    KeyPathComponent::Key(text.into())
+  }
+
+  #[test]
+  fn parse() {
+    let path = "info/files/0/length".parse::<KeyPath>().unwrap();
+    assert_eq!(
+      path.components(),
+      &[key("info"), key("files"), key("0"), key("length")]
+    );
+    assert_eq!(path.to_string(), "info/files/0/length");
+  }
+
+  #[test]
+  fn parse_each() {
+    let path = "foo*bar/baz".parse::<KeyPath>().unwrap();
+    assert_eq!(
+      path.components(),
+      &[key("foo"), KeyPathComponent::Each, key("bar"), key("baz")]
+    );
+    assert_eq!(path.to_string(), "foo*bar/baz");
+
+    for text in &["*", "*foo", "foo*", "foo**bar", "info/files*length"] {
+      assert_eq!(text.parse::<KeyPath>().unwrap().to_string(), *text);
+    }
+  }
+
+  #[test]
+  fn parse_err() {
+    for text in &["", "/", "foo/", "/foo", "foo//bar", "foo/*bar", "foo*/bar"] {
+      assert_matches!(
+        text.parse::<KeyPath>(),
+        Err(Error::KeyPathParse { text: have }) if have == *text
+      );
+    }
+  }
+}
@@ -48,59 +48,91 @@ mod test_env_builder;
 #[cfg(test)]
 mod capture;
 
+mod archive;
+mod archive_format;
 mod arguments;
+mod bencode;
+mod bencode_error;
+mod bencode_parser;
 mod bytes;
 mod common;
+mod config;
 mod consts;
+mod distribution;
 mod env;
 mod error;
 mod file_error;
 mod file_info;
+mod file_list;
 mod file_path;
+mod file_reader;
 mod file_status;
 mod files;
+mod git_tree;
 mod hasher;
 mod host_port;
 mod host_port_parse_error;
+mod inflater;
 mod info;
 mod infohash;
+#[cfg(target_os = "linux")]
+mod inotify;
 mod input;
 mod input_stream;
 mod input_target;
 mod into_u64;
 mod into_usize;
 mod invariant;
+mod io_backend;
+mod key_path;
 mod lint;
 mod linter;
 mod magnet_link;
+mod manifest;
+mod manifest_format;
 mod md5_digest;
 mod metainfo;
 mod metainfo_error;
 mod mode;
+mod non_canonical;
 mod options;
 mod output_stream;
 mod output_target;
+mod path_checker;
+mod path_violation;
 mod piece_length_picker;
 mod piece_list;
 mod platform;
 mod platform_interface;
 mod print;
+mod profile;
 mod reckoner;
+mod resume;
+mod resume_format;
 mod run;
+mod sampler;
+mod severity;
 mod sha1_digest;
+mod sha256_digest;
 mod shell;
+mod skip_reason;
 mod sort_key;
 mod sort_order;
 mod sort_spec;
+mod sparse_file;
 mod status;
 mod step;
 mod style;
 mod subcommand;
 mod table;
+mod tar_reader;
+mod time_spec;
 mod torrent_summary;
 mod use_color;
 mod verifier;
+mod violation;
 mod walker;
+mod xml_escape;
 mod xor_args;
 
 #[cfg(feature = "bench")]
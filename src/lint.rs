@@ -1,19 +1,79 @@
 use crate::common::*;
 
 #[derive(
-  Eq, PartialEq, Debug, Copy, Clone, Ord, PartialOrd, EnumVariantNames, IntoStaticStr, EnumString,
+  Eq,
+  PartialEq,
+  Debug,
+  Copy,
+  Clone,
+  Ord,
+  PartialOrd,
+  EnumVariantNames,
+  IntoStaticStr,
+  EnumString,
+  Deserialize,
 )]
 #[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
 pub(crate) enum Lint {
+  AbsurdPieceCount,
+  CaseInsensitiveCollision,
+  DuplicatePath,
+  DuplicateTracker,
+  EmptyFile,
+  InvalidTracker,
+  MissingAnnounce,
+  NonCanonicalBencode,
+  NonUtf8Encoding,
+  NormalizationCollision,
+  PieceCountMismatch,
   PrivateTrackerless,
   SmallPieceLength,
   UnevenPieceLength,
+  WindowsIncompatiblePath,
 }
 
 impl Lint {
+  /// Names of the lints checked by `imdl torrent create`. The rest are only
+  /// checked by `imdl torrent lint`.
+  pub(crate) const CREATE: &'static [&'static str] = &[
+    "case-insensitive-collision",
+    "normalization-collision",
+    "private-trackerless",
+    "small-piece-length",
+    "uneven-piece-length",
+    "windows-incompatible-path",
+  ];
+
   pub(crate) fn name(self) -> &'static str {
     self.into()
   }
+
+  /// Whether or not this lint is checked by `imdl torrent create`.
+  pub(crate) fn create(self) -> bool {
+    Self::CREATE.contains(&self.name())
+  }
+
+  /// Severity of violations of this lint, unless allowed or denied.
+  pub(crate) fn default_severity(self) -> Severity {
+    match self {
+      Self::DuplicateTracker
+      | Self::EmptyFile
+      | Self::MissingAnnounce
+      | Self::NonCanonicalBencode
+      | Self::NonUtf8Encoding => Severity::Warning,
+      Self::AbsurdPieceCount
+      | Self::CaseInsensitiveCollision
+      | Self::DuplicatePath
+      | Self::InvalidTracker
+      | Self::NormalizationCollision
+      | Self::PieceCountMismatch
+      | Self::PrivateTrackerless
+      | Self::SmallPieceLength
+      | Self::UnevenPieceLength
+      | Self::WindowsIncompatiblePath => Severity::Error,
+    }
+  }
 }
 
 impl Display for Lint {
@@ -31,13 +91,36 @@ mod tests {
     assert_eq!(
       Lint::VARIANTS,
       &[
+        "absurd-piece-count",
+        "case-insensitive-collision",
+        "duplicate-path",
+        "duplicate-tracker",
+        "empty-file",
+        "invalid-tracker",
+        "missing-announce",
+        "non-canonical-bencode",
+        "non-utf8-encoding",
+        "normalization-collision",
+        "piece-count-mismatch",
         "private-trackerless",
         "small-piece-length",
-        "uneven-piece-length"
+        "uneven-piece-length",
+        "windows-incompatible-path",
       ]
     );
   }
 
+  #[test]
+  fn create() {
+    for name in Lint::CREATE {
+      assert!(name.parse::<Lint>().unwrap().create());
+    }
+
+    assert!(!Lint::AbsurdPieceCount.create());
+    assert!(!Lint::MissingAnnounce.create());
+    assert!(!Lint::PieceCountMismatch.create());
+  }
+
   #[test]
   fn from_str_ok() {
     assert_eq!(
@@ -57,6 +140,18 @@ mod tests {
     case("private-trackerless", Lint::PrivateTrackerless);
     case("small-piece-length", Lint::SmallPieceLength);
     case("uneven-piece-length", Lint::UnevenPieceLength);
+    case("case-insensitive-collision", Lint::CaseInsensitiveCollision);
+    case("normalization-collision", Lint::NormalizationCollision);
+    case("windows-incompatible-path", Lint::WindowsIncompatiblePath);
+    case("absurd-piece-count", Lint::AbsurdPieceCount);
+    case("duplicate-path", Lint::DuplicatePath);
+    case("duplicate-tracker", Lint::DuplicateTracker);
+    case("empty-file", Lint::EmptyFile);
+    case("invalid-tracker", Lint::InvalidTracker);
+    case("missing-announce", Lint::MissingAnnounce);
+    case("non-canonical-bencode", Lint::NonCanonicalBencode);
+    case("non-utf8-encoding", Lint::NonUtf8Encoding);
+    case("piece-count-mismatch", Lint::PieceCountMismatch);
   }
 
   #[test]
@@ -1,13 +1,16 @@
 use crate::common::*;
 
+#[derive(Clone)]
 pub(crate) struct Linter {
   allowed: BTreeSet<Lint>,
+  denied: BTreeSet<Lint>,
 }
 
 impl Linter {
   pub(crate) fn new() -> Linter {
     Linter {
       allowed: BTreeSet::new(),
+      denied: BTreeSet::new(),
     }
   }
 
@@ -15,11 +18,50 @@ impl Linter {
     self.allowed.extend(allowed)
   }
 
+  pub(crate) fn deny(&mut self, denied: impl IntoIterator<Item = Lint>) {
+    self.denied.extend(denied)
+  }
+
   pub(crate) fn is_allowed(&self, lint: Lint) -> bool {
     self.allowed.contains(&lint)
   }
 
   pub(crate) fn is_denied(&self, lint: Lint) -> bool {
-    !self.is_allowed(lint)
+    self.severity(lint) == Some(Severity::Error)
+  }
+
+  /// Severity of violations of `lint`, or `None` if `lint` is allowed.
+  pub(crate) fn severity(&self, lint: Lint) -> Option<Severity> {
+    if self.is_allowed(lint) {
+      None
+    } else if self.denied.contains(&lint) {
+      Some(Severity::Error)
+    } else {
+      Some(lint.default_severity())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn severity() {
+    let mut linter = Linter::new();
+    assert_eq!(
+      linter.severity(Lint::SmallPieceLength),
+      Some(Severity::Error)
+    );
+    assert_eq!(linter.severity(Lint::EmptyFile), Some(Severity::Warning));
+    assert!(!linter.is_denied(Lint::EmptyFile));
+
+    linter.deny(vec![Lint::EmptyFile]);
+    assert_eq!(linter.severity(Lint::EmptyFile), Some(Severity::Error));
+    assert!(linter.is_denied(Lint::EmptyFile));
+
+    linter.allow(vec![Lint::EmptyFile, Lint::SmallPieceLength]);
+    assert_eq!(linter.severity(Lint::EmptyFile), None);
+    assert_eq!(linter.severity(Lint::SmallPieceLength), None);
   }
 }
@@ -10,7 +10,18 @@ pub(crate) struct MagnetLink {
 
 impl MagnetLink {
   pub(crate) fn from_metainfo(metainfo: &Metainfo) -> Result<MagnetLink> {
-    let mut link = Self::with_infohash(metainfo.infohash()?);
+    Self::from_metainfo_with_infohash(metainfo, metainfo.infohash()?)
+  }
+
+  /// Create a link to `metainfo`, using `infohash`, which should be computed
+  /// from the bytes that `metainfo` was loaded from with
+  /// `Infohash::from_input`, since re-encoding an info dictionary that was not
+  /// canonically encoded changes its infohash.
+  pub(crate) fn from_metainfo_with_infohash(
+    metainfo: &Metainfo,
+    infohash: Infohash,
+  ) -> Result<MagnetLink> {
+    let mut link = Self::with_infohash(infohash);
 
     link.set_name(metainfo.info.name.clone());
 
@@ -0,0 +1,249 @@
+use crate::common::*;
+
+/// A list of the files in a torrent, with their lengths and checksums.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Manifest {
+  entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct ManifestEntry {
+  pub(crate) path: String,
+  pub(crate) length: Bytes,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) md5: Option<Md5Digest>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) sha256: Option<Sha256Digest>,
+  #[serde(
+    skip_serializing_if = "Option::is_none",
+    serialize_with = "ManifestEntry::serialize_crc32"
+  )]
+  pub(crate) crc32: Option<u32>,
+}
+
+impl ManifestEntry {
+  fn serialize_crc32<S>(crc32: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    match crc32 {
+      Some(crc32) => serializer.collect_str(&format_args!("{:08X}", crc32)),
+      None => serializer.serialize_none(),
+    }
+  }
+}
+
+impl Manifest {
+  pub(crate) fn new() -> Self {
+    Self::default()
+  }
+
+  pub(crate) fn push(&mut self, entry: ManifestEntry) {
+    self.entries.push(entry);
+  }
+
+  /// Manifest of the files in `info`, with the MD5 checksums it contains, if
+  /// any. Paths of files in multi-file torrents are relative to the torrent's
+  /// top-level directory.
+  pub(crate) fn from_info(info: &Info) -> Self {
+    let entries = match &info.mode {
+      Mode::Single { length, md5sum } => vec![ManifestEntry {
+        path: info.name.clone(),
+        length: *length,
+        md5: *md5sum,
+        sha256: None,
+        crc32: None,
+      }],
+      Mode::Multiple { files } => files
+        .iter()
+        .map(|file| ManifestEntry {
+          path: file.path.to_string(),
+          length: file.length,
+          md5: file.md5sum,
+          sha256: None,
+          crc32: None,
+        })
+        .collect(),
+    };
+
+    Self { entries }
+  }
+
+  pub(crate) fn render(&self, format: ManifestFormat) -> Result<String, Error> {
+    let mut text = String::new();
+
+    match format {
+      ManifestFormat::Csv => {
+        text.push_str("path,length,md5,sha256,crc32\n");
+        for entry in &self.entries {
+          text.push_str(&format!(
+            "{},{},{},{},{}\n",
+            Self::csv_field(&entry.path),
+            entry.length.count(),
+            entry.md5.map(|md5| md5.to_string()).unwrap_or_default(),
+            entry
+              .sha256
+              .map(|sha256| sha256.to_string())
+              .unwrap_or_default(),
+            entry
+              .crc32
+              .map(|crc32| format!("{:08X}", crc32))
+              .unwrap_or_default(),
+          ));
+        }
+      }
+      ManifestFormat::Json => {
+        text.push_str(&serde_json::to_string_pretty(&self.entries).context(error::JsonSerialize)?);
+        text.push('\n');
+      }
+      ManifestFormat::Md5sum => {
+        for entry in &self.entries {
+          let md5 = entry
+            .md5
+            .ok_or_else(|| Self::missing(format, "MD5", entry))?;
+          text.push_str(&format!("{}  {}\n", md5, entry.path));
+        }
+      }
+      ManifestFormat::Sfv => {
+        for entry in &self.entries {
+          let crc32 = entry
+            .crc32
+            .ok_or_else(|| Self::missing(format, "CRC32", entry))?;
+          text.push_str(&format!("{} {:08X}\n", entry.path, crc32));
+        }
+      }
+      ManifestFormat::Sha256sum => {
+        for entry in &self.entries {
+          let sha256 = entry
+            .sha256
+            .ok_or_else(|| Self::missing(format, "SHA-256", entry))?;
+          text.push_str(&format!("{}  {}\n", sha256, entry.path));
+        }
+      }
+    }
+
+    Ok(text)
+  }
+
+  fn missing(format: ManifestFormat, checksum: &'static str, entry: &ManifestEntry) -> Error {
+    Error::ManifestChecksumMissing {
+      path: entry.path.clone(),
+      checksum,
+      format,
+    }
+  }
+
+  fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+      Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+      Cow::Borrowed(field)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn manifest() -> Manifest {
+    let mut manifest = Manifest::new();
+
+    manifest.push(ManifestEntry {
+      path: "a".into(),
+      length: Bytes(3),
+      md5: Some(Md5Digest::from_data("abc")),
+      sha256: Some(Sha256::digest(b"abc").into()),
+      crc32: Some(crc32fast::hash(b"abc")),
+    });
+
+    manifest.push(ManifestEntry {
+      path: "b,\"c\"".into(),
+      length: Bytes(0),
+      md5: None,
+      sha256: None,
+      crc32: None,
+    });
+
+    manifest
+  }
+
+  #[test]
+  fn csv() {
+    assert_eq!(
+      manifest().render(ManifestFormat::Csv).unwrap(),
+      "path,length,md5,sha256,crc32\na,3,900150983cd24fb0d6963f7d28e17f72,\
+       ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad,352441C2\n\"b,\"\"c\"\"\",\
+       0,,,\n"
+    );
+  }
+
+  #[test]
+  fn json() {
+    let json: serde_json::Value =
+      serde_json::from_str(&manifest().render(ManifestFormat::Json).unwrap()).unwrap();
+
+    assert_eq!(
+      json,
+      serde_json::json!([
+        {
+          "path": "a",
+          "length": 3,
+          "md5": "900150983cd24fb0d6963f7d28e17f72",
+          "sha256": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+          "crc32": "352441C2",
+        },
+        {
+          "path": "b,\"c\"",
+          "length": 0,
+        },
+      ])
+    );
+  }
+
+  #[test]
+  fn checksum_formats() {
+    let mut manifest = manifest();
+    manifest.entries.pop();
+
+    assert_eq!(
+      manifest.render(ManifestFormat::Md5sum).unwrap(),
+      "900150983cd24fb0d6963f7d28e17f72  a\n"
+    );
+    assert_eq!(
+      manifest.render(ManifestFormat::Sha256sum).unwrap(),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  a\n"
+    );
+    assert_eq!(
+      manifest.render(ManifestFormat::Sfv).unwrap(),
+      "a 352441C2\n"
+    );
+  }
+
+  #[test]
+  fn checksum_missing() {
+    assert_matches!(
+      manifest().render(ManifestFormat::Sfv),
+      Err(Error::ManifestChecksumMissing { path, checksum: "CRC32", format: ManifestFormat::Sfv })
+        if path == "b,\"c\""
+    );
+  }
+
+  #[test]
+  fn from_info() {
+    let metainfo = Metainfo::test_value_multiple();
+
+    assert_eq!(
+      Manifest::from_info(&metainfo.info).entries,
+      &[ManifestEntry {
+        path: "DIR/FILE".into(),
+        length: Bytes(32 * 1024),
+        md5: Some(Md5Digest::from_hex("000102030405060708090a0b0c0d0e0f")),
+        sha256: None,
+        crc32: None,
+      }]
+    );
+  }
+}
@@ -0,0 +1,38 @@
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, EnumVariantNames, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum ManifestFormat {
+  Csv,
+  Json,
+  Md5sum,
+  Sfv,
+  Sha256sum,
+}
+
+impl ManifestFormat {
+  pub(crate) fn name(self) -> &'static str {
+    self.into()
+  }
+}
+
+impl Display for ManifestFormat {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn variants() {
+    assert_eq!(
+      ManifestFormat::VARIANTS,
+      &["csv", "json", "md5sum", "sfv", "sha256sum"]
+    );
+  }
+}
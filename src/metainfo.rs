@@ -48,6 +48,10 @@ pub(crate) struct Metainfo {
     with = "unwrap_or_skip"
   )]
   pub(crate) nodes: Option<Vec<HostPort>>,
+  /// Top-level keys not otherwise recognized, which are preserved when
+  /// serializing.
+  #[serde(skip)]
+  pub(crate) extra: BTreeMap<Vec<u8>, Bencode>,
 }
 
 impl Metainfo {
@@ -56,20 +60,86 @@ impl Metainfo {
   }
 
   pub(crate) fn deserialize(source: &InputTarget, data: &[u8]) -> Result<Metainfo, Error> {
-    let metainfo = bendy::serde::de::from_bytes(&data).context(error::MetainfoDeserialize {
-      input: source.clone(),
-    })?;
+    let mut metainfo: Metainfo =
+      bendy::serde::de::from_bytes(&data).context(error::MetainfoDeserialize {
+        input: source.clone(),
+      })?;
+
+    let raw = BencodeParser::new(data)
+      .parse()
+      .context(error::MetainfoDecode {
+        input: source.clone(),
+      })?;
+
+    let known = BencodeParser::new(
+      &bendy::serde::ser::to_bytes(&metainfo).context(error::MetainfoSerialize)?,
+    )
+    .parse()
+    .invariant("Serialized metainfo is valid bencode")?;
+
+    if let (Bencode::Dict(raw), Bencode::Dict(known)) = (raw, known) {
+      if let (Some(Bencode::Dict(raw)), Some(Bencode::Dict(known))) =
+        (raw.get(b"info".as_ref()), known.get(b"info".as_ref()))
+      {
+        metainfo.info.extra = Self::unknown(raw, known);
+
+        if let (Mode::Multiple { files }, Some(Bencode::List(raw)), Some(Bencode::List(known))) = (
+          &mut metainfo.info.mode,
+          raw.get(b"files".as_ref()),
+          known.get(b"files".as_ref()),
+        ) {
+          for ((file, raw), known) in files.iter_mut().zip(raw).zip(known) {
+            if let (Bencode::Dict(raw), Bencode::Dict(known)) = (raw, known) {
+              file.extra = Self::unknown(raw, known);
+            }
+          }
+        }
+      }
+
+      metainfo.extra = Self::unknown(&raw, &known);
+    }
+
     Ok(metainfo)
   }
 
+  /// Entries of `raw` whose keys do not appear in `known`.
+  fn unknown(
+    raw: &BTreeMap<Vec<u8>, Bencode>,
+    known: &BTreeMap<Vec<u8>, Bencode>,
+  ) -> BTreeMap<Vec<u8>, Bencode> {
+    raw
+      .iter()
+      .filter(|(key, _)| !known.contains_key(*key))
+      .map(|(key, value)| (key.clone(), value.clone()))
+      .collect()
+  }
+
   pub(crate) fn serialize(&self) -> Result<Vec<u8>, Error> {
-    bendy::serde::ser::to_bytes(&self).context(error::MetainfoSerialize)
+    let bytes = bendy::serde::ser::to_bytes(&self).context(error::MetainfoSerialize)?;
+
+    if self.extra.is_empty() && !self.info.has_extra() {
+      return Ok(bytes);
+    }
+
+    let mut value = BencodeParser::new(&bytes)
+      .parse()
+      .invariant("Serialized metainfo is valid bencode")?;
+
+    if let Bencode::Dict(dict) = &mut value {
+      if let Some(info) = dict.get_mut(b"info".as_ref()) {
+        self.info.insert_extra(info);
+      }
+    }
+
+    value.insert_missing(&self.extra);
+
+    Ok(value.encode())
   }
 
   #[cfg(test)]
   pub(crate) fn dump(&self, path: impl AsRef<Path>) -> Result<(), Error> {
     let path = path.as_ref();
-    let bencode = bendy::serde::ser::to_bytes(&self).context(error::MetainfoSerialize)?;
+    let bencode = self.serialize()?;
     fs::write(path, &bencode).context(error::Filesystem { path })?;
     Ok(())
   }
@@ -91,8 +161,13 @@ impl Metainfo {
     paths
   }
 
-  pub(crate) fn verify(&self, base: &Path, progress_bar: Option<ProgressBar>) -> Result<Status> {
-    Verifier::verify(self, base, progress_bar)
+  pub(crate) fn verify(
+    &self,
+    base: &Path,
+    reader: FileReader,
+    progress_bar: Option<ProgressBar>,
+  ) -> Result<Status> {
+    Verifier::verify(self, base, reader, progress_bar)
   }
 
   pub(crate) fn content_size(&self) -> Bytes {
@@ -121,6 +196,7 @@ impl Metainfo {
   #[cfg(test)]
   pub(crate) fn test_value_single() -> Metainfo {
     Metainfo {
+      extra: BTreeMap::new(),
       announce: Some("udp://announce.example:1337".into()),
       announce_list: Some(vec![
         vec![
@@ -139,6 +215,7 @@ impl Metainfo {
       creation_date: Some(1),
       encoding: Some("UTF-8".into()),
       info: Info {
+        extra: BTreeMap::new(),
         private: Some(true),
         piece_length: Bytes(16 * 1024),
         source: Some("SOURCE".into()),
@@ -171,6 +248,7 @@ impl Metainfo {
         length: Bytes(32 * 1024),
         path: FilePath::from_components(&["DIR", "FILE"]),
         md5sum: Some(Md5Digest::from_hex("000102030405060708090a0b0c0d0e0f")),
+        extra: BTreeMap::new(),
       }],
     };
     instance
@@ -179,6 +257,7 @@ impl Metainfo {
   #[cfg(test)]
   pub(crate) fn test_value_single_unset() -> Metainfo {
     Metainfo {
+      extra: BTreeMap::new(),
       announce: None,
       announce_list: None,
       nodes: None,
@@ -187,6 +266,7 @@ impl Metainfo {
       creation_date: None,
       encoding: None,
       info: Info {
+        extra: BTreeMap::new(),
         private: None,
         piece_length: Bytes(1024),
         source: None,
@@ -214,6 +294,7 @@ impl Metainfo {
   #[cfg(test)]
   pub(crate) fn test_value_multiple_unset() -> Metainfo {
     Metainfo {
+      extra: BTreeMap::new(),
       announce: None,
       announce_list: None,
       nodes: None,
@@ -222,6 +303,7 @@ impl Metainfo {
       creation_date: None,
       encoding: None,
       info: Info {
+        extra: BTreeMap::new(),
         private: None,
         piece_length: Bytes(1024),
         source: None,
@@ -232,6 +314,7 @@ impl Metainfo {
             length: Bytes(1024),
             md5sum: None,
             path: FilePath::from_components(&["a", "b"]),
+            extra: BTreeMap::new(),
           }],
         },
         update_url: None,
@@ -268,6 +351,89 @@ mod tests {
     assert_eq!(value, deserialized);
   }
 
+  #[test]
+  fn round_trip_unknown_keys() {
+    let bencode = concat!(
+      "d",
+      "4:infod",
+      "6:lengthi0e",
+      "4:name3:foo",
+      "12:piece lengthi16384e",
+      "6:pieces0:",
+      "7:x-extrali1ei2ee",
+      "e",
+      "10:similarityd1:ai1ee",
+      "8:url-listl18:http://foo.examplee",
+      "e"
+    );
+
+    let metainfo = Metainfo::from_bytes(bencode.as_bytes());
+
+    assert_eq!(metainfo.extra.len(), 2);
+    assert_eq!(
+      metainfo.info.extra.get(b"x-extra".as_ref()),
+      Some(&Bencode::List(vec![
+        Bencode::Integer(1),
+        Bencode::Integer(2)
+      ]))
+    );
+
+    assert_eq!(
+      String::from_utf8(metainfo.serialize().unwrap()).unwrap(),
+      bencode
+    );
+
+    let input = Input::new(InputTarget::Path("foo.torrent".into()), bencode.into());
+
+    assert_eq!(
+      metainfo.infohash().unwrap(),
+      Infohash::from_input(&input).unwrap()
+    );
+  }
+
+  #[test]
+  fn round_trip_unknown_file_keys() {
+    let bencode = concat!(
+      "d",
+      "4:infod",
+      "5:filesl",
+      "d4:attr1:x6:lengthi1e4:pathl1:aee",
+      "d6:lengthi2e4:pathl1:be4:sha120:aaaaaaaaaaaaaaaaaaaa",
+      "12:symlink pathl1:aee",
+      "e",
+      "4:name3:foo",
+      "12:piece lengthi16384e",
+      "6:pieces0:",
+      "e",
+      "e"
+    );
+
+    let metainfo = Metainfo::from_bytes(bencode.as_bytes());
+
+    match &metainfo.info.mode {
+      Mode::Multiple { files } => {
+        assert_eq!(
+          files[0].extra.get(b"attr".as_ref()),
+          Some(&Bencode::String(b"x".to_vec()))
+        );
+        assert_eq!(files[1].extra.len(), 2);
+      }
+      Mode::Single { .. } => panic!("Expected multi-file torrent"),
+    }
+
+    assert_eq!(
+      String::from_utf8(metainfo.serialize().unwrap()).unwrap(),
+      bencode
+    );
+
+    let input = Input::new(InputTarget::Path("foo.torrent".into()), bencode.into());
+
+    assert_eq!(
+      metainfo.infohash().unwrap(),
+      Infohash::from_input(&input).unwrap()
+    );
+  }
+
   fn representation(value: Metainfo, want: &str) {
     let have = value.serialize().unwrap();
 
@@ -86,6 +86,7 @@ mod tests {
         length: Bytes(10),
         path: FilePath::from_components(&["foo", "bar"]),
         md5sum: Some(Md5Digest::from_hex("000102030405060708090a0b0c0d0e0f")),
+        extra: BTreeMap::new(),
       }],
     };
 
@@ -0,0 +1,40 @@
+use crate::common::*;
+
+/// Bencode that can be parsed, but that is not in canonical form, with the
+/// byte offset at which it occurs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum NonCanonical {
+  DuplicateKey { offset: usize },
+  LeadingZero { offset: usize },
+  NegativeZero { offset: usize },
+  TrailingData { offset: usize },
+  UnsortedKey { offset: usize },
+}
+
+impl NonCanonical {
+  pub(crate) fn offset(self) -> usize {
+    match self {
+      Self::DuplicateKey { offset }
+      | Self::LeadingZero { offset }
+      | Self::NegativeZero { offset }
+      | Self::TrailingData { offset }
+      | Self::UnsortedKey { offset } => offset,
+    }
+  }
+
+  fn message(self) -> &'static str {
+    match self {
+      Self::DuplicateKey { .. } => "Duplicate dictionary key",
+      Self::LeadingZero { .. } => "Number with leading zero",
+      Self::NegativeZero { .. } => "Negative zero",
+      Self::TrailingData { .. } => "Trailing data",
+      Self::UnsortedKey { .. } => "Dictionary key out of order",
+    }
+  }
+}
+
+impl Display for NonCanonical {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{} at byte offset {}", self.message(), self.offset())
+  }
+}
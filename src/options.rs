@@ -2,6 +2,24 @@ use crate::common::*;
 
 #[derive(StructOpt)]
 pub(crate) struct Options {
+  #[structopt(
+    long = "config",
+    value_name = "PATH",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Load config file from `PATH`. If not given, imdl looks for a file named `imdl.toml` \
+            in the current directory and its ancestors, and uses the first one found."
+  )]
+  pub(crate) config: Option<PathBuf>,
+  #[structopt(
+    long = "profile",
+    value_name = "NAME",
+    empty_values(false),
+    help = "Use settings from profile `NAME` in the config file as defaults for `imdl torrent \
+            create` and `imdl torrent watch`. Flags given on the command line take precedence \
+            over the profile. If not given, the profile named `default` is used, if present."
+  )]
+  pub(crate) profile: Option<String>,
   #[structopt(long = "quiet", short = "q", help = "Suppress normal output.")]
   pub(crate) quiet: bool,
   #[structopt(
@@ -10,9 +28,10 @@ pub(crate) struct Options {
     help = "Enable unstable features. To avoid premature stabilization and excessive version \
             churn, unstable features are unavailable unless this flag is set. Unstable features \
             are not bound by semantic versioning stability guarantees, and may be changed or \
-            removed at any time."
+            removed at any time. There are currently no unstable features, so this flag has no \
+            effect."
   )]
-  unstable: bool,
+  _unstable: bool,
   #[structopt(
     long = "color",
     short = "c",
@@ -33,13 +52,3 @@ pub(crate) struct Options {
   )]
   pub(crate) terminal: bool,
 }
-
-impl Options {
-  pub(crate) fn require_unstable(&self, feature: &'static str) -> Result<(), Error> {
-    if self.unstable {
-      Ok(())
-    } else {
-      Err(Error::Unstable { feature })
-    }
-  }
-}
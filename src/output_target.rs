@@ -14,6 +14,15 @@ impl OutputTarget {
     }
   }
 
+  /// Write `bytes` to this target, overwriting the file if this target is a
+  /// path.
+  pub(crate) fn write(&self, env: &mut Env, bytes: &[u8]) -> Result<()> {
+    match self.resolve(env)? {
+      Self::Path(path) => fs::write(&path, bytes).context(error::Filesystem { path }),
+      Self::Stdout => env.out_mut().write_all(bytes).context(error::Stdout),
+    }
+  }
+
   pub(crate) fn try_from_os_str(text: &OsStr) -> Result<Self, OsString> {
     text
       .try_into()
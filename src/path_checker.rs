@@ -0,0 +1,145 @@
+use crate::common::*;
+
+/// Checks the paths of files in a torrent for violations of denied path
+/// lints one at a time, so that files whose paths are only known once they
+/// have been found can be checked before they are hashed.
+pub(crate) struct PathChecker {
+  linter: Linter,
+  /// Unicode-normalized paths checked so far, and the original paths.
+  normalized: BTreeMap<FilePath, FilePath>,
+  /// Unicode-normalized and lowercased paths checked so far, and the original
+  /// paths.
+  folded: BTreeMap<FilePath, FilePath>,
+}
+
+impl PathChecker {
+  pub(crate) fn new(linter: Linter) -> Self {
+    Self {
+      normalized: BTreeMap::new(),
+      folded: BTreeMap::new(),
+      linter,
+    }
+  }
+
+  /// Check `name`, the name of the torrent.
+  pub(crate) fn check_name(&self, name: &str) -> Result<(), Error> {
+    match PathViolation::find_in_name(name) {
+      Some(violation) => self.deny(violation),
+      None => Ok(()),
+    }
+  }
+
+  /// Check `path`, including for collisions with the paths checked before
+  /// it.
+  pub(crate) fn check(&mut self, path: &FilePath) -> Result<(), Error> {
+    for violation in self.violations(path) {
+      self.deny(violation)?;
+    }
+
+    Ok(())
+  }
+
+  /// Violations by `path`, regardless of whether they are denied.
+  pub(crate) fn violations(&mut self, path: &FilePath) -> Vec<PathViolation> {
+    let mut violations = Vec::new();
+
+    if path
+      .components()
+      .iter()
+      .any(|component| PathViolation::windows_incompatible(component))
+    {
+      violations.push(PathViolation::WindowsIncompatible { path: path.clone() });
+    }
+
+    let nfc = path.nfc();
+
+    if let Some(first) = self.normalized.get(&nfc) {
+      if first != path {
+        violations.push(PathViolation::NormalizationCollision {
+          first: first.clone(),
+          second: path.clone(),
+        });
+      }
+      return violations;
+    }
+
+    let lowercase = nfc.to_lowercase();
+
+    if let Some(first) = self.folded.get(&lowercase) {
+      violations.push(PathViolation::CaseInsensitiveCollision {
+        first: first.clone(),
+        second: path.clone(),
+      });
+    } else {
+      self.folded.insert(lowercase, path.clone());
+    }
+
+    self.normalized.insert(nfc, path.clone());
+
+    violations
+  }
+
+  fn deny(&self, violation: PathViolation) -> Result<(), Error> {
+    if self.linter.is_denied(violation.lint()) {
+      Err(Error::PathLint { violation })
+    } else {
+      Ok(())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn path(text: &str) -> FilePath {
+    FilePath::from_components(&text.split('/').collect::<Vec<&str>>())
+  }
+
+  #[test]
+  fn check() {
+    let mut checker = PathChecker::new(Linter::new());
+
+    checker.check(&path("a/Foo")).unwrap();
+    checker.check(&path("a/b")).unwrap();
+
+    assert_matches!(
+      checker.check(&path("A/foo")),
+      Err(Error::PathLint {
+        violation: PathViolation::CaseInsensitiveCollision { first, second },
+      }) if first == path("a/Foo") && second == path("A/foo")
+    );
+
+    assert_matches!(
+      checker.check(&path("a/con")),
+      Err(Error::PathLint {
+        violation: PathViolation::WindowsIncompatible { .. },
+      })
+    );
+  }
+
+  #[test]
+  fn check_allowed() {
+    let mut linter = Linter::new();
+    linter.allow(vec![Lint::CaseInsensitiveCollision]);
+
+    let mut checker = PathChecker::new(linter);
+
+    checker.check(&path("a")).unwrap();
+    checker.check(&path("A")).unwrap();
+  }
+
+  #[test]
+  fn check_name() {
+    let checker = PathChecker::new(Linter::new());
+
+    checker.check_name("foo").unwrap();
+
+    assert_matches!(
+      checker.check_name("foo."),
+      Err(Error::PathLint {
+        violation: PathViolation::WindowsIncompatible { .. },
+      })
+    );
+  }
+}
@@ -0,0 +1,207 @@
+use crate::common::*;
+
+/// Names that Windows reserves for devices, with or without an extension.
+const WINDOWS_RESERVED: &[&str] = &[
+  "AUX", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "CON", "LPT1",
+  "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9", "NUL", "PRN",
+];
+
+/// Characters that may not appear in Windows file names.
+const WINDOWS_FORBIDDEN: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum PathViolation {
+  CaseInsensitiveCollision { first: FilePath, second: FilePath },
+  NormalizationCollision { first: FilePath, second: FilePath },
+  WindowsIncompatible { path: FilePath },
+}
+
+impl PathViolation {
+  /// Find paths that cannot be created on all platforms. `name` is the name
+  /// of the torrent, and `paths` are the paths of files in the torrent,
+  /// relative to `name`.
+  pub(crate) fn find<'a>(
+    name: &str,
+    paths: impl IntoIterator<Item = &'a FilePath>,
+  ) -> Vec<PathViolation> {
+    let mut violations = Self::find_in_name(name).into_iter().collect::<Vec<Self>>();
+
+    let mut checker = PathChecker::new(Linter::new());
+
+    for path in paths {
+      violations.extend(checker.violations(path));
+    }
+
+    violations
+  }
+
+  /// Find a violation in `name`, the name of a torrent.
+  pub(crate) fn find_in_name(name: &str) -> Option<PathViolation> {
+    if Self::windows_incompatible(name) {
+      Some(Self::WindowsIncompatible {
+        path: FilePath::from_component(name),
+      })
+    } else {
+      None
+    }
+  }
+
+  pub(crate) fn lint(&self) -> Lint {
+    match self {
+      Self::CaseInsensitiveCollision { .. } => Lint::CaseInsensitiveCollision,
+      Self::NormalizationCollision { .. } => Lint::NormalizationCollision,
+      Self::WindowsIncompatible { .. } => Lint::WindowsIncompatiblePath,
+    }
+  }
+
+  pub(crate) fn windows_incompatible(component: &str) -> bool {
+    let stem = component.split('.').next().unwrap_or_default().trim_end();
+
+    WINDOWS_RESERVED
+      .iter()
+      .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+      || component.ends_with('.')
+      || component.ends_with(' ')
+      || component
+        .chars()
+        .any(|c| c.is_ascii_control() || WINDOWS_FORBIDDEN.contains(&c))
+  }
+}
+
+impl Display for PathViolation {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::CaseInsensitiveCollision { first, second } => write!(
+        f,
+        "Paths `{}` and `{}` differ only in case, and collide on case-insensitive filesystems",
+        first, second
+      ),
+      Self::NormalizationCollision { first, second } => write!(
+        f,
+        "Paths `{}` and `{}` differ only in Unicode normalization, and collide on filesystems \
+         that normalize file names",
+        first, second
+      ),
+      Self::WindowsIncompatible { path } => write!(f, "Path `{}` is not valid on Windows", path),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn path(text: &str) -> FilePath {
+    FilePath::from_components(&text.split('/').collect::<Vec<&str>>())
+  }
+
+  #[test]
+  fn windows_incompatible() {
+    for component in &[
+      "CON",
+      "con",
+      "nul.txt",
+      "Com1",
+      "lpt9.tar.gz",
+      "aux ",
+      "foo.",
+      "foo ",
+      "a:b",
+      "a?",
+      "a*b",
+      "a\"b",
+      "a<b",
+      "a>b",
+      "a|b",
+      "a\\b",
+      "a\u{1}b",
+    ] {
+      assert!(
+        PathViolation::windows_incompatible(component),
+        "{}",
+        component
+      );
+    }
+
+    for component in &[
+      "foo", "CONSOLE", "com0", "com10", ".foo", "foo.bar", "nulx.txt",
+    ] {
+      assert!(
+        !PathViolation::windows_incompatible(component),
+        "{}",
+        component
+      );
+    }
+  }
+
+  #[test]
+  fn find_windows_incompatible() {
+    let paths = [path("a/con/b"), path("c")];
+    assert_eq!(
+      PathViolation::find("foo.", &paths),
+      &[
+        PathViolation::WindowsIncompatible { path: path("foo.") },
+        PathViolation::WindowsIncompatible {
+          path: path("a/con/b")
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn find_case_insensitive_collision() {
+    let paths = [path("a/Foo"), path("a/b"), path("A/foo")];
+    assert_eq!(
+      PathViolation::find("foo", &paths),
+      &[PathViolation::CaseInsensitiveCollision {
+        first: path("a/Foo"),
+        second: path("A/foo"),
+      }]
+    );
+  }
+
+  #[test]
+  fn find_normalization_collision() {
+    let paths = [path("\u{e4}"), path("a\u{308}")];
+    assert_eq!(
+      PathViolation::find("foo", &paths),
+      &[PathViolation::NormalizationCollision {
+        first: path("\u{e4}"),
+        second: path("a\u{308}"),
+      }]
+    );
+  }
+
+  #[test]
+  fn find_none() {
+    let paths = [path("a/b"), path("a/c"), path("b")];
+    assert_eq!(PathViolation::find("foo", &paths), &[]);
+  }
+
+  #[test]
+  fn lint() {
+    let path = path("a");
+    assert_eq!(
+      PathViolation::WindowsIncompatible { path: path.clone() }.lint(),
+      Lint::WindowsIncompatiblePath
+    );
+    assert_eq!(
+      PathViolation::CaseInsensitiveCollision {
+        first: path.clone(),
+        second: path.clone()
+      }
+      .lint(),
+      Lint::CaseInsensitiveCollision
+    );
+    assert_eq!(
+      PathViolation::NormalizationCollision {
+        first: path.clone(),
+        second: path
+      }
+      .lint(),
+      Lint::NormalizationCollision
+    );
+  }
+}
@@ -1,6 +1,6 @@
 use crate::common::*;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Ord, PartialOrd)]
 pub(crate) struct PieceList {
   piece_hashes: Vec<Sha1Digest>,
 }
@@ -20,6 +20,10 @@ impl PieceList {
     self.piece_hashes.push(digest);
   }
 
+  pub(crate) fn get(&self, index: usize) -> Option<Sha1Digest> {
+    self.piece_hashes.get(index).copied()
+  }
+
   #[cfg(test)]
   pub(crate) fn from_pieces<I, B>(pieces: I) -> Self
   where
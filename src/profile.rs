@@ -0,0 +1,196 @@
+use crate::common::*;
+
+/// Default settings for `imdl torrent create`, loaded from a named profile in
+/// a config file. Keys mirror the long names of the corresponding flags.
+#[derive(Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Profile {
+  #[serde(default)]
+  pub(crate) allow: Vec<Lint>,
+  #[serde(default)]
+  pub(crate) announce: Option<Url>,
+  #[serde(default)]
+  pub(crate) announce_tier: Vec<String>,
+  #[serde(default)]
+  pub(crate) comment: Option<String>,
+  #[serde(default)]
+  pub(crate) glob: Vec<String>,
+  #[serde(default)]
+  pub(crate) include_hidden: Option<bool>,
+  #[serde(default)]
+  pub(crate) include_junk: Option<bool>,
+  #[serde(default)]
+  pub(crate) md5: Option<bool>,
+  #[serde(default, with = "serde_with::rust::seq_display_fromstr")]
+  pub(crate) node: Vec<HostPort>,
+  #[serde(default, deserialize_with = "deserialize_bytes")]
+  pub(crate) piece_length: Option<Bytes>,
+  #[serde(default)]
+  pub(crate) private: Option<bool>,
+  #[serde(default)]
+  pub(crate) source: Option<String>,
+  #[serde(default)]
+  pub(crate) update_url: Option<Url>,
+}
+
+fn deserialize_bytes<'de, D>(deserializer: D) -> Result<Option<Bytes>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let text = String::deserialize(deserializer)?;
+  text.parse().map(Some).map_err(D::Error::custom)
+}
+
+impl Profile {
+  /// Add the settings that `imdl torrent create` will use to `table`,
+  /// including the defaults of settings that this profile does not set.
+  pub(crate) fn table(&self, table: &mut Table) {
+    fn flag(table: &mut Table, name: &'static str, value: Option<bool>) {
+      table.row(name, if value.unwrap_or(false) { "yes" } else { "no" });
+    }
+
+    fn list(table: &mut Table, name: &'static str, values: Vec<String>) {
+      if values.is_empty() {
+        table.row(name, NONE);
+      } else {
+        table.list(name, values);
+      }
+    }
+
+    fn optional(table: &mut Table, name: &'static str, value: Option<&dyn Display>) {
+      match value {
+        Some(value) => table.row(name, value),
+        None => table.row(name, NONE),
+      }
+    }
+
+    const NONE: &str = "none";
+
+    optional(
+      table,
+      "Announce",
+      self.announce.as_ref().map(|url| url as &dyn Display),
+    );
+
+    list(table, "Announce Tiers", self.announce_tier.clone());
+
+    optional(
+      table,
+      "Comment",
+      self.comment.as_ref().map(|comment| comment as &dyn Display),
+    );
+
+    optional(
+      table,
+      "Source",
+      self.source.as_ref().map(|source| source as &dyn Display),
+    );
+
+    flag(table, "Private", self.private);
+
+    optional(
+      table,
+      "Update URL",
+      self.update_url.as_ref().map(|url| url as &dyn Display),
+    );
+
+    list(
+      table,
+      "DHT Nodes",
+      self.node.iter().map(ToString::to_string).collect(),
+    );
+
+    match self.piece_length {
+      Some(piece_length) => table.size("Piece Size", piece_length),
+      None => table.row("Piece Size", "automatic"),
+    }
+
+    flag(table, "MD5", self.md5);
+
+    flag(table, "Include Hidden", self.include_hidden);
+
+    flag(table, "Include Junk", self.include_junk);
+
+    list(table, "Globs", self.glob.clone());
+
+    list(
+      table,
+      "Allowed Lints",
+      self.allow.iter().map(ToString::to_string).collect(),
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn empty() {
+    assert_eq!(toml::from_str::<Profile>("").unwrap(), Profile::default());
+  }
+
+  #[test]
+  fn all_keys() {
+    let profile = toml::from_str::<Profile>(
+      r#"
+        allow = ["small-piece-length", "private-trackerless"]
+        announce = "https://example.com/announce"
+        announce-tier = ["udp://a.example:1,udp://b.example:2", "udp://c.example:3"]
+        comment = "COMMENT"
+        glob = ["!*.nfo"]
+        include-hidden = true
+        include-junk = false
+        md5 = true
+        node = ["node.example:12", "1.1.1.1:16"]
+        piece-length = "1mib"
+        private = true
+        source = "SOURCE"
+        update-url = "https://update.example"
+      "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      profile,
+      Profile {
+        allow: vec![Lint::SmallPieceLength, Lint::PrivateTrackerless],
+        announce: Some("https://example.com/announce".parse().unwrap()),
+        announce_tier: vec![
+          "udp://a.example:1,udp://b.example:2".into(),
+          "udp://c.example:3".into(),
+        ],
+        comment: Some("COMMENT".into()),
+        glob: vec!["!*.nfo".into()],
+        include_hidden: Some(true),
+        include_junk: Some(false),
+        md5: Some(true),
+        node: vec![
+          "node.example:12".parse().unwrap(),
+          "1.1.1.1:16".parse().unwrap(),
+        ],
+        piece_length: Some(Bytes::mib()),
+        private: Some(true),
+        source: Some("SOURCE".into()),
+        update_url: Some("https://update.example".parse().unwrap()),
+      }
+    );
+  }
+
+  #[test]
+  fn unknown_key() {
+    assert!(toml::from_str::<Profile>("foo = 1").is_err());
+  }
+
+  #[test]
+  fn unknown_lint() {
+    assert!(toml::from_str::<Profile>("allow = [\"foo\"]").is_err());
+  }
+
+  #[test]
+  fn bad_piece_length() {
+    assert!(toml::from_str::<Profile>("piece-length = \"1xib\"").is_err());
+  }
+}
@@ -0,0 +1,228 @@
+use crate::common::*;
+
+/// Transmission tracks progress in blocks of this size, as well as in pieces.
+const TRANSMISSION_BLOCK_SIZE: u64 = 16 * 1024;
+
+/// Resume data for a torrent whose content has been verified, which lets
+/// clients start seeding the content without checking it again.
+pub(crate) struct Resume<'a> {
+  bitfield: &'a [bool],
+  content_size: Bytes,
+  infohash: Infohash,
+  name: &'a str,
+  piece_length: Bytes,
+  save_path: &'a str,
+  time: i64,
+}
+
+impl<'a> Resume<'a> {
+  /// Resume data for `metainfo`, whose infohash is `infohash`, with content
+  /// at `content`, from the result of verifying it. Clients look for content in
+  /// a directory, the save path, under the torrent's name, so the save path
+  /// is the directory containing `content`.
+  pub(crate) fn new(
+    metainfo: &'a Metainfo,
+    infohash: Infohash,
+    status: &'a Status,
+    content: &'a Path,
+    time: i64,
+  ) -> Result<Self> {
+    let save_path = content.parent().unwrap_or(content);
+
+    let save_path = save_path
+      .to_str()
+      .ok_or_else(|| Error::ResumeSavePathDecode {
+        path: save_path.to_owned(),
+      })?;
+
+    let bitfield = status.bitfield();
+
+    Ok(Self {
+      bitfield: &bitfield[..bitfield.len().min(metainfo.info.pieces.count())],
+      content_size: metainfo.content_size(),
+      infohash,
+      name: &metainfo.info.name,
+      piece_length: metainfo.info.piece_length,
+      save_path,
+      time,
+    })
+  }
+
+  pub(crate) fn encode(&self, format: ResumeFormat) -> Vec<u8> {
+    match format {
+      ResumeFormat::Libtorrent => self.libtorrent(),
+      ResumeFormat::Transmission => self.transmission(),
+    }
+    .encode()
+  }
+
+  /// A libtorrent `.fastresume` file, as read by libtorrent based clients
+  /// like qBittorrent and Deluge. Pieces are stored one per byte, with `1`
+  /// meaning the piece is present.
+  fn libtorrent(&self) -> Bencode {
+    let infohash: Sha1Digest = self.infohash.into();
+
+    let pieces = self
+      .bitfield
+      .iter()
+      .map(|good| if *good { 1 } else { 0 })
+      .collect();
+
+    Self::dict(vec![
+      ("file-format", Self::string("libtorrent resume file")),
+      ("file-version", Bencode::Integer(1)),
+      ("info-hash", Bencode::String(infohash.bytes().to_vec())),
+      ("name", Self::string(self.name)),
+      ("pieces", Bencode::String(pieces)),
+      ("qBt-savePath", Self::string(self.save_path)),
+      ("save_path", Self::string(self.save_path)),
+    ])
+  }
+
+  /// A Transmission `.resume` file. Progress is recorded both per piece and
+  /// per block, so that both older and newer versions of Transmission can read
+  /// it.
+  fn transmission(&self) -> Bencode {
+    let mut progress = vec![
+      ("pieces", Self::bitfield(self.bitfield)),
+      ("time-checked", Bencode::Integer(self.time)),
+    ];
+
+    if let Some(blocks) = self.blocks() {
+      progress.push(("blocks", Self::bitfield(&blocks)));
+    }
+
+    Self::dict(vec![
+      ("added-date", Bencode::Integer(self.time)),
+      ("destination", Self::string(self.save_path)),
+      ("name", Self::string(self.name)),
+      ("progress", Self::dict(progress)),
+    ])
+  }
+
+  /// Whether each Transmission block is present, or `None` if pieces are not
+  /// made up of whole blocks.
+  fn blocks(&self) -> Option<Vec<bool>> {
+    let piece_length = self.piece_length.count();
+
+    if piece_length % TRANSMISSION_BLOCK_SIZE != 0 {
+      return None;
+    }
+
+    let blocks_per_piece = piece_length / TRANSMISSION_BLOCK_SIZE;
+
+    let block_count =
+      (self.content_size.count() + TRANSMISSION_BLOCK_SIZE - 1) / TRANSMISSION_BLOCK_SIZE;
+
+    (0..block_count)
+      .map(|block| {
+        usize::try_from(block / blocks_per_piece)
+          .ok()
+          .and_then(|piece| self.bitfield.get(piece).copied())
+      })
+      .collect()
+  }
+
+  /// A bitfield, with the first item in the most significant bit of the first
+  /// byte, or `all` or `none` if every item is the same.
+  fn bitfield(items: &[bool]) -> Bencode {
+    if items.iter().all(|item| *item) {
+      return Self::string("all");
+    }
+
+    if items.iter().all(|item| !*item) {
+      return Self::string("none");
+    }
+
+    let mut bytes = vec![0u8; (items.len() + 7) / 8];
+
+    for (i, item) in items.iter().enumerate() {
+      if *item {
+        bytes[i / 8] |= 0x80 >> (i % 8);
+      }
+    }
+
+    Bencode::String(bytes)
+  }
+
+  fn dict(entries: Vec<(&str, Bencode)>) -> Bencode {
+    Bencode::Dict(
+      entries
+        .into_iter()
+        .map(|(key, value)| (key.as_bytes().to_vec(), value))
+        .collect(),
+    )
+  }
+
+  fn string(text: &str) -> Bencode {
+    Bencode::String(text.as_bytes().to_vec())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn resume(bitfield: &[bool], piece_length: u64, content_size: u64) -> Resume<'_> {
+    Resume {
+      bitfield,
+      content_size: Bytes(content_size),
+      infohash: Infohash::from_bencoded_info_dict(b"de"),
+      name: "foo",
+      piece_length: Bytes(piece_length),
+      save_path: "/bar",
+      time: 1,
+    }
+  }
+
+  #[test]
+  fn bitfield() {
+    assert_eq!(Resume::bitfield(&[true, true]), Resume::string("all"));
+    assert_eq!(Resume::bitfield(&[false, false]), Resume::string("none"));
+    assert_eq!(
+      Resume::bitfield(&[true, false, false, false, false, false, false, true, false, true]),
+      Bencode::String(vec![0b1000_0001, 0b0100_0000])
+    );
+  }
+
+  #[test]
+  fn blocks() {
+    let bitfield = [true, false];
+
+    assert_eq!(
+      resume(&bitfield, 32 * 1024, 48 * 1024).blocks(),
+      Some(vec![true, true, false])
+    );
+    assert_eq!(resume(&bitfield, 1000, 1500).blocks(), None);
+  }
+
+  #[test]
+  fn libtorrent() {
+    let resume = resume(&[true, false, true], 16 * 1024, 40 * 1024);
+
+    let infohash: Sha1Digest = resume.infohash.into();
+
+    let mut want =
+      b"d11:file-format22:libtorrent resume file12:file-versioni1e9:info-hash20:".to_vec();
+    want.extend_from_slice(&infohash.bytes());
+    want.extend_from_slice(
+      b"4:name3:foo6:pieces3:\x01\x00\x0112:qBt-savePath4:/bar9:save_path4:/bare",
+    );
+
+    assert_eq!(resume.encode(ResumeFormat::Libtorrent), want);
+  }
+
+  #[test]
+  fn transmission() {
+    let resume = resume(&[true, false, true], 16 * 1024, 40 * 1024);
+
+    assert_eq!(
+      resume.encode(ResumeFormat::Transmission),
+      b"d10:added-datei1e11:destination4:/bar4:name3:foo8:progressd6:blocks1:\xa06:pieces1:\xa0\
+        12:time-checkedi1eee"
+        .to_vec()
+    );
+  }
+}
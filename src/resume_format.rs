@@ -0,0 +1,32 @@
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, EnumVariantNames, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum ResumeFormat {
+  Libtorrent,
+  Transmission,
+}
+
+impl ResumeFormat {
+  pub(crate) fn name(self) -> &'static str {
+    self.into()
+  }
+}
+
+impl Display for ResumeFormat {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn variants() {
+    assert_eq!(ResumeFormat::VARIANTS, &["libtorrent", "transmission"]);
+  }
+}
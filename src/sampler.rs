@@ -0,0 +1,156 @@
+use crate::common::*;
+
+/// Checks individual pieces of a torrent against content on disk, without
+/// hashing the rest of the content.
+pub(crate) struct Sampler<'a> {
+  files: Vec<(PathBuf, u64)>,
+  pieces: &'a PieceList,
+  piece_length: u64,
+  total_size: u64,
+}
+
+impl<'a> Sampler<'a> {
+  pub(crate) fn new(info: &'a Info, base: &Path) -> Sampler<'a> {
+    let files = match &info.mode {
+      Mode::Single { length, .. } => vec![(base.to_owned(), length.count())],
+      Mode::Multiple { files } => files
+        .iter()
+        .map(|file| (file.path.absolute(base), file.length.count()))
+        .collect(),
+    };
+
+    Sampler {
+      total_size: files.iter().map(|(_, length)| length).sum(),
+      piece_length: info.piece_length.count(),
+      pieces: &info.pieces,
+      files,
+    }
+  }
+
+  /// Indices of `count` pieces, spread evenly over `piece_count` pieces, and
+  /// including the first and last piece.
+  pub(crate) fn indices(count: usize, piece_count: usize) -> Vec<usize> {
+    if count >= piece_count {
+      return (0..piece_count).collect();
+    }
+
+    if count == 1 {
+      return vec![0];
+    }
+
+    let mut indices = (0..count)
+      .map(|i| i * (piece_count - 1) / (count - 1))
+      .collect::<Vec<usize>>();
+
+    indices.dedup();
+
+    indices
+  }
+
+  /// Returns true if the content of piece `index` matches its hash. Pieces
+  /// whose content cannot be read do not match.
+  pub(crate) fn check(&self, index: usize) -> bool {
+    match (self.hash(index), self.pieces.get(index)) {
+      (Ok(have), Some(want)) => have == want,
+      _ => false,
+    }
+  }
+
+  fn hash(&self, index: usize) -> io::Result<Sha1Digest> {
+    let start = index.into_u64() * self.piece_length;
+    let end = (start + self.piece_length).min(self.total_size);
+
+    let mut sha1 = Sha1::new();
+    let mut buffer = Vec::new();
+    let mut offset = 0;
+
+    for (path, length) in &self.files {
+      let file_start = offset;
+      let file_end = offset + length;
+      offset = file_end;
+
+      if file_end <= start || file_start >= end {
+        continue;
+      }
+
+      let read_start = start.max(file_start) - file_start;
+      let read_end = end.min(file_end) - file_start;
+
+      let mut file = File::open(path)?;
+      file.seek(SeekFrom::Start(read_start))?;
+
+      let length = read_end - read_start;
+
+      buffer.clear();
+      if file.take(length).read_to_end(&mut buffer)?.into_u64() != length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+      }
+
+      sha1.update(&buffer);
+    }
+
+    Ok(sha1.digest().into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn indices() {
+    assert_eq!(Sampler::indices(8, 3), &[0, 1, 2]);
+    assert_eq!(Sampler::indices(1, 3), &[0]);
+    assert_eq!(Sampler::indices(2, 10), &[0, 9]);
+    assert_eq!(Sampler::indices(4, 10), &[0, 3, 6, 9]);
+    assert!(Sampler::indices(0, 10).is_empty());
+  }
+
+  #[test]
+  fn check() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "https://bar",
+        "--piece-length",
+        "16KiB",
+      ],
+      tree: {
+        foo: {
+          a: "a",
+          b: "b",
+          c: "c",
+        },
+      },
+    };
+
+    env.write("foo/a", "a".repeat(20 * 1024));
+    env.write("foo/b", "b".repeat(20 * 1024));
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    let base = env.resolve("foo").unwrap();
+
+    let sampler = Sampler::new(&metainfo.info, &base);
+
+    assert!((0..3).all(|index| sampler.check(index)));
+    assert!(!sampler.check(3));
+
+    env.write("foo/b", "x".to_owned() + &"b".repeat(20 * 1024 - 1));
+
+    assert!(sampler.check(0));
+    assert!(!sampler.check(1));
+    assert!(sampler.check(2));
+
+    env.write("foo/c", "");
+
+    assert!(!sampler.check(2));
+  }
+}
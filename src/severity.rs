@@ -0,0 +1,22 @@
+use crate::common::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd)]
+pub(crate) enum Severity {
+  Warning,
+  Error,
+}
+
+impl Severity {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Self::Warning => "warning",
+      Self::Error => "error",
+    }
+  }
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
@@ -1,6 +1,6 @@
 use crate::common::*;
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
 pub(crate) struct Sha1Digest {
   bytes: [u8; Self::LENGTH],
 }
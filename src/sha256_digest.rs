@@ -0,0 +1,50 @@
+use crate::common::*;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub(crate) struct Sha256Digest {
+  bytes: [u8; 32],
+}
+
+impl From<sha2::digest::Output<Sha256>> for Sha256Digest {
+  fn from(output: sha2::digest::Output<Sha256>) -> Self {
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(&output);
+    Self { bytes }
+  }
+}
+
+impl Display for Sha256Digest {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    for byte in &self.bytes {
+      write!(f, "{:02x}", byte)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl Serialize for Sha256Digest {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn display() {
+    let digest = Sha256Digest::from(Sha256::digest(b"abc"));
+
+    assert_eq!(
+      digest.to_string(),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+  }
+}
@@ -0,0 +1,28 @@
+use crate::common::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SkipReason {
+  Glob,
+  Junk,
+  Small { min: Bytes },
+  Large { max: Bytes },
+  Old,
+  New,
+  Submodule,
+  Symlink,
+}
+
+impl Display for SkipReason {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Glob => write!(f, "excluded by `--glob`"),
+      Self::Junk => write!(f, "junk file"),
+      Self::Small { min } => write!(f, "smaller than minimum file size {}", min),
+      Self::Large { max } => write!(f, "larger than maximum file size {}", max),
+      Self::Old => write!(f, "not modified after `--newer-than` time"),
+      Self::New => write!(f, "not modified before `--older-than` time"),
+      Self::Submodule => write!(f, "git submodule"),
+      Self::Symlink => write!(f, "symbolic link"),
+    }
+  }
+}
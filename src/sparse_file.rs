@@ -0,0 +1,227 @@
+use crate::common::*;
+
+/// Reads a file, returning zeros for holes in sparse files instead of reading
+/// them from disk. Holes are found with `SEEK_HOLE` and `SEEK_DATA` on Linux.
+/// On other platforms, and on Linux if holes can't be found, the whole file
+/// is read.
+pub(crate) struct SparseFile {
+  file: File,
+  /// Length of the file when it was opened. Holes that extend to the end of
+  /// the file end here.
+  len: u64,
+  position: u64,
+  /// Start of the next hole at or after `position`, or `u64::MAX` if there
+  /// are no more holes.
+  hole: u64,
+  /// End of the hole at `position`, once it has been found.
+  data: Option<u64>,
+}
+
+impl SparseFile {
+  pub(crate) fn open(path: &Path) -> io::Result<Self> {
+    Self::new(File::open(path)?)
+  }
+
+  pub(crate) fn new(mut file: File) -> io::Result<Self> {
+    let len = file.metadata()?.len();
+
+    let hole = match Self::seek(&file, Whence::Hole, 0) {
+      Ok(Some(hole)) => hole,
+      Ok(None) | Err(_) => u64::max_value(),
+    };
+
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(Self {
+      data: None,
+      position: 0,
+      file,
+      hole,
+      len,
+    })
+  }
+
+  pub(crate) fn file(&self) -> &File {
+    &self.file
+  }
+
+  /// Offset of the next hole or data at or after `offset`, or `None` if
+  /// there is none.
+  #[cfg(target_os = "linux")]
+  fn seek(file: &File, whence: Whence, offset: u64) -> io::Result<Option<u64>> {
+    use std::os::unix::io::AsRawFd;
+
+    let whence = match whence {
+      Whence::Data => libc::SEEK_DATA,
+      Whence::Hole => libc::SEEK_HOLE,
+    };
+
+    let offset = libc::off64_t::try_from(offset)
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset out of range"))?;
+
+    // SAFETY: `lseek64` takes no pointers, and `file` owns a valid file
+    // descriptor.
+    let result = unsafe { libc::lseek64(file.as_raw_fd(), offset, whence) };
+
+    if result < 0 {
+      let error = io::Error::last_os_error();
+      if error.raw_os_error() == Some(libc::ENXIO) {
+        return Ok(None);
+      }
+      return Err(error);
+    }
+
+    Ok(Some(
+      u64::try_from(result).invariant_unwrap("offset is non-negative"),
+    ))
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  fn seek(_file: &File, _whence: Whence, _offset: u64) -> io::Result<Option<u64>> {
+    Ok(None)
+  }
+}
+
+enum Whence {
+  Data,
+  Hole,
+}
+
+impl Read for SparseFile {
+  fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+    if buffer.is_empty() {
+      return Ok(0);
+    }
+
+    if self.position >= self.hole {
+      let data = match self.data {
+        Some(data) => data,
+        None => {
+          let data = Self::seek(&self.file, Whence::Data, self.position)?.unwrap_or(self.len);
+          self.data = Some(data);
+          data
+        }
+      };
+
+      if self.position < data {
+        let zeros = buffer
+          .len()
+          .min(usize::try_from(data - self.position).unwrap_or(usize::max_value()));
+
+        for byte in &mut buffer[..zeros] {
+          *byte = 0;
+        }
+
+        self.position += zeros.into_u64();
+
+        return Ok(zeros);
+      }
+
+      self.data = None;
+      self.hole = Self::seek(&self.file, Whence::Hole, self.position)?.unwrap_or(u64::max_value());
+      self.file.seek(SeekFrom::Start(self.position))?;
+    }
+
+    let limit = buffer
+      .len()
+      .min(usize::try_from(self.hole - self.position).unwrap_or(usize::max_value()));
+
+    let bytes_read = self.file.read(&mut buffer[..limit])?;
+
+    self.position += bytes_read.into_u64();
+
+    Ok(bytes_read)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn sparse(tempdir: &TempDir, len: u64, writes: &[(u64, &[u8])]) -> PathBuf {
+    let path = tempdir.path().join("sparse");
+
+    let mut file = File::create(&path).unwrap();
+    file.set_len(len).unwrap();
+
+    for (offset, bytes) in writes {
+      file.seek(SeekFrom::Start(*offset)).unwrap();
+      file.write_all(bytes).unwrap();
+    }
+
+    path
+  }
+
+  fn read(path: &Path, chunk: usize) -> Vec<u8> {
+    let mut file = SparseFile::open(path).unwrap();
+    let mut contents = Vec::new();
+    let mut buffer = vec![0xff; chunk];
+
+    loop {
+      let bytes_read = file.read(&mut buffer).unwrap();
+      if bytes_read == 0 {
+        break;
+      }
+      contents.extend_from_slice(&buffer[..bytes_read]);
+    }
+
+    contents
+  }
+
+  #[test]
+  fn holes() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let mib = 1024 * 1024;
+
+    let path = sparse(
+      &tempdir,
+      16 * mib,
+      &[
+        (0, b"start"),
+        (3 * mib + 17, b"middle"),
+        (16 * mib - 3, b"end"),
+      ],
+    );
+
+    let expected = fs::read(&path).unwrap();
+
+    for chunk in &[4093, 64 * 1024, 5 * 1024 * 1024] {
+      assert!(read(&path, *chunk) == expected, "chunk size {}", chunk);
+    }
+  }
+
+  #[test]
+  fn trailing_hole() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let path = sparse(&tempdir, 1024 * 1024, &[(100, b"data")]);
+
+    let expected = fs::read(&path).unwrap();
+
+    for chunk in &[1, 64 * 1024] {
+      assert!(read(&path, *chunk) == expected, "chunk size {}", chunk);
+    }
+  }
+
+  #[test]
+  fn empty() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let path = sparse(&tempdir, 0, &[]);
+
+    assert_eq!(read(&path, 10), b"");
+  }
+
+  #[test]
+  fn dense() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let path = tempdir.path().join("dense");
+    fs::write(&path, "dense").unwrap();
+
+    assert_eq!(read(&path, 2), b"dense");
+  }
+}
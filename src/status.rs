@@ -3,30 +3,36 @@ use crate::common::*;
 #[derive(Debug)]
 pub(crate) enum Status {
   Single {
-    pieces: bool,
+    bitfield: Vec<bool>,
     error: Option<FileError>,
   },
   Multiple {
-    pieces: bool,
+    bitfield: Vec<bool>,
     files: Vec<FileStatus>,
   },
 }
 
 impl Status {
-  pub(crate) fn single(pieces: bool, error: Option<FileError>) -> Self {
-    Status::Single { pieces, error }
+  pub(crate) fn single(bitfield: Vec<bool>, error: Option<FileError>) -> Self {
+    Status::Single { bitfield, error }
   }
 
-  pub(crate) fn multiple(pieces: bool, files: Vec<FileStatus>) -> Self {
-    Status::Multiple { pieces, files }
+  pub(crate) fn multiple(bitfield: Vec<bool>, files: Vec<FileStatus>) -> Self {
+    Status::Multiple { bitfield, files }
   }
 
-  pub(crate) fn pieces(&self) -> bool {
+  /// Whether each piece of the torrent was found to be intact, in order.
+  /// Content longer than the torrent produces extra pieces, which are bad.
+  pub(crate) fn bitfield(&self) -> &[bool] {
     match self {
-      Self::Single { pieces, .. } | Self::Multiple { pieces, .. } => *pieces,
+      Self::Single { bitfield, .. } | Self::Multiple { bitfield, .. } => bitfield,
     }
   }
 
+  pub(crate) fn pieces(&self) -> bool {
+    self.bitfield().iter().all(|good| *good)
+  }
+
   pub(crate) fn good(&self) -> bool {
     self.pieces()
       && match self {
@@ -1,19 +1,25 @@
 use crate::common::*;
 
+mod bencode;
 mod completions;
+mod config;
 mod torrent;
 
 #[derive(StructOpt)]
 pub(crate) enum Subcommand {
   Torrent(torrent::Torrent),
+  Bencode(bencode::Bencode),
   Completions(completions::Completions),
+  Config(config::Config),
 }
 
 impl Subcommand {
   pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
     match self {
       Self::Torrent(torrent) => torrent.run(env, options),
+      Self::Bencode(bencode) => bencode.run(env),
       Self::Completions(completions) => completions.run(env),
+      Self::Config(config) => config.run(env, options),
     }
   }
 }
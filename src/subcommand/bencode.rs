@@ -0,0 +1,33 @@
+use crate::common::*;
+
+mod delete;
+mod from_json;
+mod get;
+mod set;
+mod show;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Subcommands for inspecting and editing bencoded data.")
+)]
+pub(crate) enum Bencode {
+  Delete(delete::Delete),
+  FromJson(from_json::FromJson),
+  Get(get::Get),
+  Set(set::Set),
+  Show(show::Show),
+}
+
+impl Bencode {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    match self {
+      Self::Delete(delete) => delete.run(env),
+      Self::FromJson(from_json) => from_json.run(env),
+      Self::Get(get) => get.run(env),
+      Self::Set(set) => set.run(env),
+      Self::Show(show) => show.run(env),
+    }
+  }
+}
@@ -0,0 +1,117 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Delete the value at a key path in bencoded data.")
+)]
+pub(crate) struct Delete {
+  #[structopt(
+    name = "PATH",
+    value_name = "PATH",
+    help = "Delete values at key path `PATH`. Components of `PATH` are separated by `/`, and \
+            select dictionary values by key and list items by index. A `*` after a component \
+            selects every item of a list."
+  )]
+  path: KeyPath,
+  #[structopt(
+    name = "INPUT",
+    value_name = "INPUT",
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = "Read bencoded data from `INPUT`. If `INPUT` is `-`, read bencoded data from standard \
+            input."
+  )]
+  input: InputTarget,
+  #[structopt(
+    long = "output",
+    short = "o",
+    value_name = "TARGET",
+    empty_values(false),
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    required_if("INPUT", "-"),
+    help = "Write edited data to `TARGET`, or to standard output if `TARGET` is `-`. Defaults to \
+            overwriting `INPUT`. Required when `INPUT` is `-`."
+  )]
+  output: Option<OutputTarget>,
+}
+
+impl Delete {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let input = env.read(self.input)?;
+    let mut value = Bencode::from_input(&input)?;
+
+    value.delete(&self.path)?;
+
+    let output = self
+      .output
+      .map_or_else(|| super::set::output_for(&input), Ok)?;
+
+    output.write(env, &value.encode())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn in_place() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "delete",
+        "a/0",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ali1ei2ee1:bi3ee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.read_to_string("foo"), "d1:ali2ee1:bi3ee");
+  }
+
+  #[test]
+  fn output() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "delete",
+        "b",
+        "foo",
+        "--output",
+        "bar",
+      ],
+      tree: {
+        foo: "d1:ai1e1:bi2ee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.read_to_string("foo"), "d1:ai1e1:bi2ee");
+    assert_eq!(env.read_to_string("bar"), "d1:ai1ee");
+  }
+
+  #[test]
+  fn missing() {
+    test_env! {
+      args: [
+        "bencode",
+        "delete",
+        "c",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ai1ee",
+      },
+      matches: Err(Error::KeyPathNotFound { .. }),
+    };
+  }
+}
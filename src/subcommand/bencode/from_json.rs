@@ -0,0 +1,163 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Convert JSON to bencode.")
+)]
+pub(crate) struct FromJson {
+  #[structopt(
+    name = "INPUT",
+    value_name = "INPUT",
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = "Read JSON from `INPUT`. If `INPUT` is `-`, read JSON from standard input. Objects \
+            with a single `$bytes` key, whose value is a hex-encoded string, are converted to \
+            byte strings, and object keys of the form `$bytes:HEX` are converted to the \
+            hex-decoded keys, as produced by `imdl bencode show --json`."
+  )]
+  input: InputTarget,
+  #[structopt(
+    long = "output",
+    short = "o",
+    value_name = "TARGET",
+    empty_values(false),
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    help = "Write bencode to `TARGET`, or to standard output if `TARGET` is `-`."
+  )]
+  output: OutputTarget,
+}
+
+impl FromJson {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let input = env.read(self.input)?;
+    let json = serde_json::from_slice(input.data()).context(error::JsonParse)?;
+    let value = Bencode::from_json(&json)?;
+    self.output.write(env, &value.encode())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn output_required() {
+    test_env! {
+      args: [
+        "bencode",
+        "from-json",
+        "foo",
+      ],
+      tree: {},
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn convert() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "from-json",
+        "foo.json",
+        "--output",
+        "foo",
+      ],
+      tree: {
+        "foo.json": r#"{"b": {"$bytes": "ff00"}, "a": [1, "x"]}"#,
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      fs::read(env.resolve("foo").unwrap()).unwrap(),
+      b"d1:ali1e1:xe1:b2:\xff\x00e"
+    );
+  }
+
+  #[test]
+  fn round_trip() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "from-json",
+        "-",
+        "--output",
+        "-",
+      ],
+      input: r#"{"a": [1, {"b": "c"}]}"#,
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "d1:ali1ed1:b1:ceee");
+  }
+
+  fn show_round_trip(bencode: &[u8]) {
+    let mut show = test_env! {
+      args: [
+        "bencode",
+        "show",
+        "--json",
+        "foo",
+      ],
+      tree: {
+        foo: "",
+      },
+    };
+
+    show.write("foo", bencode);
+    show.assert_ok();
+
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "from-json",
+        "foo.json",
+        "--output",
+        "foo",
+      ],
+      tree: {
+        "foo.json": "",
+      },
+    };
+
+    env.write("foo.json", show.out());
+    env.assert_ok();
+
+    assert_eq!(fs::read(env.resolve("foo").unwrap()).unwrap(), bencode);
+  }
+
+  #[test]
+  fn show_round_trip_non_utf8_key() {
+    show_round_trip(b"d2:\xff\x00l1:ai1eee");
+  }
+
+  #[test]
+  fn show_round_trip_bytes_key() {
+    show_round_trip(b"d6:$bytes2:00e");
+  }
+
+  #[test]
+  fn unrepresentable() {
+    test_env! {
+      args: [
+        "bencode",
+        "from-json",
+        "foo.json",
+        "--output",
+        "foo",
+      ],
+      tree: {
+        "foo.json": "[1.5]",
+      },
+      matches: Err(Error::JsonUnrepresentable { .. }),
+    };
+  }
+}
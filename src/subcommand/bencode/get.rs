@@ -0,0 +1,138 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Display the value at a key path in bencoded data.")
+)]
+pub(crate) struct Get {
+  #[structopt(
+    name = "PATH",
+    value_name = "PATH",
+    help = "Display values at key path `PATH`. Components of `PATH` are separated by `/`, and \
+            select dictionary values by key and list items by index. A `*` after a component \
+            selects every item of a list, as in the key paths displayed by `imdl torrent stats`. \
+            For example, `info/files/0/length` is the length of the first file of a multi-file \
+            torrent, and `info/files*length` is the length of every file. Each selected value is \
+            displayed in turn."
+  )]
+  path: KeyPath,
+  #[structopt(
+    name = "INPUT",
+    value_name = "INPUT",
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = "Read bencoded data from `INPUT`. If `INPUT` is `-`, read bencoded data from standard \
+            input."
+  )]
+  input: InputTarget,
+  #[structopt(long = "json", short = "j", help = "Print value as JSON.")]
+  json: bool,
+}
+
+impl Get {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let input = env.read(self.input)?;
+    let value = Bencode::from_input(&input)?;
+
+    let path = self.path;
+
+    let values = value.get(&path);
+
+    if values.is_empty() {
+      return Err(Error::KeyPathNotFound { path });
+    }
+
+    for value in values {
+      super::show::print(env, value, self.json)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn scalar() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "get",
+        "a/1",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ali1ei2eee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "2\n");
+  }
+
+  #[test]
+  fn json() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "get",
+        "--json",
+        "a",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:al3:bari2eee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "[\n  \"bar\",\n  2\n]\n");
+  }
+
+  #[test]
+  fn each() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "get",
+        "a*b",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ald1:bi1eed1:bi2eeee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "1\n2\n");
+  }
+
+  #[test]
+  fn missing() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "get",
+        "a/b",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ai1ee",
+      },
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(Error::KeyPathNotFound { path }) if path.to_string() == "a/b"
+    );
+  }
+}
@@ -0,0 +1,164 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Set the value at a key path in bencoded data.")
+)]
+pub(crate) struct Set {
+  #[structopt(
+    name = "PATH",
+    value_name = "PATH",
+    help = "Set values at key path `PATH`. Components of `PATH` are separated by `/`, and select \
+            dictionary values by key and list items by index. A `*` after a component selects \
+            every item of a list. Missing dictionaries are created, and an index one past the end \
+            of a list appends to the list."
+  )]
+  path: KeyPath,
+  #[structopt(
+    name = "VALUE",
+    value_name = "VALUE",
+    help = "Set value to `VALUE`, given as JSON. For example, `42`, `\"foo\"`, or `[1, 2]`."
+  )]
+  value: String,
+  #[structopt(
+    name = "INPUT",
+    value_name = "INPUT",
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = "Read bencoded data from `INPUT`. If `INPUT` is `-`, read bencoded data from standard \
+            input."
+  )]
+  input: InputTarget,
+  #[structopt(
+    long = "output",
+    short = "o",
+    value_name = "TARGET",
+    empty_values(false),
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    required_if("INPUT", "-"),
+    help = "Write edited data to `TARGET`, or to standard output if `TARGET` is `-`. Defaults to \
+            overwriting `INPUT`. Required when `INPUT` is `-`."
+  )]
+  output: Option<OutputTarget>,
+}
+
+impl Set {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let json = serde_json::from_str(&self.value).context(error::JsonParse)?;
+    let new = Bencode::from_json(&json)?;
+
+    let input = env.read(self.input)?;
+    let mut value = Bencode::from_input(&input)?;
+
+    value.set(&self.path, new)?;
+
+    let output = self.output.map_or_else(|| output_for(&input), Ok)?;
+
+    output.write(env, &value.encode())
+  }
+}
+
+/// Output target for edits to `input` when `--output` is not given.
+pub(crate) fn output_for(input: &Input) -> Result<OutputTarget, Error> {
+  match input.source() {
+    InputTarget::Path(path) => Ok(OutputTarget::Path(path.clone())),
+    InputTarget::Stdin => Err(Error::internal(
+      "Expected `--output` to be set when `INPUT` is `-`.",
+    )),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn in_place() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "set",
+        "a/b",
+        "[1, \"x\"]",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ai1ee",
+      },
+    };
+
+    assert_matches!(env.run(), Err(Error::KeyPathNotFound { .. }));
+
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "set",
+        "b/c",
+        "[1, \"x\"]",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ai1ee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.read_to_string("foo"), "d1:ai1e1:bd1:cli1e1:xeee");
+  }
+
+  #[test]
+  fn stdio() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "set",
+        "a",
+        "\"bar\"",
+        "-",
+        "--output",
+        "-",
+      ],
+      input: "d1:ai1ee",
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "d1:a3:bare");
+  }
+
+  #[test]
+  fn output_required_for_stdin() {
+    test_env! {
+      args: [
+        "bencode",
+        "set",
+        "a",
+        "1",
+        "-",
+      ],
+      tree: {},
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn invalid_json() {
+    test_env! {
+      args: [
+        "bencode",
+        "set",
+        "a",
+        "foo",
+        "bar",
+      ],
+      tree: {},
+      matches: Err(Error::JsonParse { .. }),
+    };
+  }
+}
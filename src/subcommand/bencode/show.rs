@@ -0,0 +1,112 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Display bencoded data as a tree or as JSON.")
+)]
+pub(crate) struct Show {
+  #[structopt(
+    name = "INPUT",
+    value_name = "INPUT",
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = "Show bencoded data at `INPUT`. If `INPUT` is `-`, read bencoded data from standard \
+            input."
+  )]
+  input: InputTarget,
+  #[structopt(
+    long = "json",
+    short = "j",
+    help = "Print data as JSON. Byte strings that are not valid UTF-8 are printed as objects with \
+            a single `$bytes` key, whose value is the hex-encoded bytes. Dictionary keys that are \
+            not valid UTF-8, or that start with `$bytes`, are printed as `$bytes:` followed by \
+            the hex-encoded key."
+  )]
+  json: bool,
+}
+
+impl Show {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let input = env.read(self.input)?;
+    let value = Bencode::from_input(&input)?;
+    print(env, &value, self.json)
+  }
+}
+
+pub(crate) fn print(env: &mut Env, value: &Bencode, json: bool) -> Result<(), Error> {
+  if json {
+    outln!(env, "{:#}", value.to_json())
+  } else {
+    out!(env, "{}", value.tree())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn tree() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "show",
+        "foo",
+      ],
+      tree: {
+        foo: "d1:ali1ei2ee1:b3:bare",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "a:\n  - 1\n  - 2\nb: \"bar\"\n");
+  }
+
+  #[test]
+  fn json() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "show",
+        "--json",
+        "-",
+      ],
+      input: "d1:ali1ei2ee1:b3:bare",
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.out(),
+      "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": \"bar\"\n}\n"
+    );
+  }
+
+  #[test]
+  fn invalid() {
+    let mut env = test_env! {
+      args: [
+        "bencode",
+        "show",
+        "foo",
+      ],
+      tree: {
+        foo: "x",
+      },
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(Error::BencodeDecode {
+        source: BencodeError::UnexpectedByte { offset: 0, .. },
+        ..
+      })
+    );
+  }
+}
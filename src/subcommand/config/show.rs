@@ -0,0 +1,180 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about(
+    "Show the settings that `imdl torrent create` will use, including defaults for settings that \
+     the config file does not set."
+  )
+)]
+pub(crate) struct Show {}
+
+impl Show {
+  pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
+    let config = Config::load(env, options)?;
+
+    let mut table = Table::new();
+
+    let selected = if let Some(config) = &config {
+      table.row("Config File", config.path().display());
+      config.selected_profile(options)?
+    } else {
+      if let Some(profile) = &options.profile {
+        return Err(Error::ConfigMissing {
+          profile: profile.clone(),
+        });
+      }
+
+      errln!(env, "No `{}` config file found.", Config::FILENAME)?;
+
+      table.row("Config File", "none");
+      None
+    };
+
+    let default = Profile::default();
+
+    let profile = if let Some((name, profile)) = selected {
+      table.row("Profile", name);
+      profile
+    } else {
+      table.row("Profile", "none");
+      &default
+    };
+
+    profile.table(&mut table);
+
+    if env.out().is_term() {
+      let style = env.out().style();
+      table
+        .write_human_readable(env.out_mut(), style)
+        .context(error::Stdout)?;
+    } else {
+      table
+        .write_tab_delimited(env.out_mut())
+        .context(error::Stdout)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn no_config() {
+    let mut env = test_env! {
+      args: [
+        "config",
+        "show",
+      ],
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.out(),
+      "config file\tnone\nprofile\tnone\nannounce\tnone\nannounce \
+       tiers\tnone\ncomment\tnone\nsource\tnone\nprivate\tno\nupdate url\tnone\ndht \
+       nodes\tnone\npiece size\tautomatic\nmd5\tno\ninclude hidden\tno\ninclude \
+       junk\tno\nglobs\tnone\nallowed lints\tnone\n"
+    );
+    assert_eq!(env.err(), "No `imdl.toml` config file found.\n");
+  }
+
+  #[test]
+  fn no_config_profile() {
+    test_env! {
+      args: [
+        "--profile",
+        "foo",
+        "config",
+        "show",
+      ],
+      tree: {},
+      matches: Err(Error::ConfigMissing { .. }),
+    };
+  }
+
+  #[test]
+  fn default_profile() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "config",
+        "show",
+      ],
+      tree: {
+        "imdl.toml": "
+          [profile.default]
+          announce = \"https://bar/\"
+          announce-tier = [\"https://a,https://b\", \"https://c\"]
+          private = true
+          piece-length = \"1mib\"
+          allow = [\"small-piece-length\"]
+
+          [profile.other]
+          source = \"OTHER\"
+        ",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.out(),
+      format!(
+        "config file\t{}\nprofile\tdefault\nannounce\thttps://bar/\nannounce \
+         tiers\thttps://a,https://b\thttps://c\ncomment\tnone\nsource\tnone\nprivate\tyes\nupdate \
+         url\tnone\ndht nodes\tnone\npiece size\t1048576\nmd5\tno\ninclude hidden\tno\ninclude \
+         junk\tno\nglobs\tnone\nallowed lints\tsmall-piece-length\n",
+        env.resolve("imdl.toml")?.display()
+      )
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn selected_profile() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "--terminal",
+        "--profile",
+        "other",
+        "config",
+        "show",
+      ],
+      tree: {
+        "imdl.toml": "
+          [profile.default]
+          source = \"DEFAULT\"
+
+          [profile.other]
+          source = \"OTHER\"
+          md5 = true
+        ",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.out(),
+      format!(
+        "   Config File  {}\n       Profile  other\n      Announce  none\nAnnounce Tiers  none\n       \
+         Comment  none\n        Source  OTHER\n       Private  no\n    Update URL  none\n     DHT \
+         Nodes  none\n    Piece Size  automatic\n           MD5  yes\nInclude Hidden  no\n  Include \
+         Junk  no\n         Globs  none\n Allowed Lints  none\n",
+        env.resolve("imdl.toml")?.display()
+      )
+    );
+
+    Ok(())
+  }
+}
@@ -1,11 +1,19 @@
 use crate::common::*;
 
 mod create;
+mod dedupe;
+mod export;
+mod feed;
 mod link;
+mod lint;
+mod normalize;
 mod piece_length;
 mod show;
+mod site;
 mod stats;
 mod verify;
+#[cfg(target_os = "linux")]
+mod watch;
 
 #[derive(StructOpt)]
 #[structopt(
@@ -15,23 +23,39 @@ mod verify;
 )]
 pub(crate) enum Torrent {
   Create(create::Create),
+  Dedupe(dedupe::Dedupe),
+  Export(export::Export),
+  Feed(feed::Feed),
   Link(link::Link),
+  Lint(lint::Lint),
+  Normalize(normalize::Normalize),
   #[structopt(alias = "piece-size")]
   PieceLength(piece_length::PieceLength),
   Show(show::Show),
+  Site(site::Site),
   Stats(stats::Stats),
   Verify(verify::Verify),
+  #[cfg(target_os = "linux")]
+  Watch(watch::Watch),
 }
 
 impl Torrent {
   pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
     match self {
       Self::Create(create) => create.run(env, options),
+      Self::Dedupe(dedupe) => dedupe.run(env),
+      Self::Export(export) => export.run(env),
+      Self::Feed(feed) => feed.run(env),
       Self::Link(link) => link.run(env),
+      Self::Lint(lint) => lint.run(env),
+      Self::Normalize(normalize) => normalize.run(env, options),
       Self::PieceLength(piece_length) => piece_length.run(env),
       Self::Show(show) => show.run(env),
-      Self::Stats(stats) => stats.run(env, options),
+      Self::Site(site) => site.run(env),
+      Self::Stats(stats) => stats.run(env),
       Self::Verify(verify) => verify.run(env, options),
+      #[cfg(target_os = "linux")]
+      Self::Watch(watch) => watch.run(env, options),
     }
   }
 }
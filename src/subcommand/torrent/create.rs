@@ -1,7 +1,9 @@
 use crate::common::*;
+use batch_report::BatchReport;
 use create_content::CreateContent;
 use create_step::CreateStep;
 
+mod batch_report;
 mod create_content;
 mod create_step;
 
@@ -13,9 +15,11 @@ const INPUT_HELP: &str = "Read torrent contents from `INPUT`. If `INPUT` is a fi
 
 const INPUT_FLAG: &str = "input-flag";
 
+const SAMPLE_DEFAULT: usize = 8;
+
 const INPUT_POSITIONAL: &str = "<INPUT>";
 
-#[derive(StructOpt)]
+#[derive(StructOpt, Clone)]
 #[structopt(
   help_message(consts::HELP_MESSAGE),
   version_message(consts::VERSION_MESSAGE),
@@ -34,7 +38,7 @@ pub(crate) struct Create {
     long = "allow",
     short = "A",
     value_name = "LINT",
-    possible_values = Lint::VARIANTS,
+    possible_values = Lint::CREATE,
     help = "Allow `LINT`. Lints check for conditions which, although permitted, are not usually \
             desirable. For example, piece length can be any non-zero value, but probably \
             shouldn't be below 16 KiB. The lint `small-piece-size` checks for this, and \
@@ -60,6 +64,40 @@ pub(crate) struct Create {
             https://github.com/bittorrent/bittorrent.org/issues/82"
   )]
   announce_tiers: Vec<String>,
+  #[structopt(
+    long = "batch",
+    value_name = "DIR",
+    empty_values(false),
+    parse(from_os_str),
+    conflicts_with_all = &[INPUT_FLAG, INPUT_POSITIONAL, "name", "output"],
+    requires = "batch-output",
+    help = "Create one torrent for each file and directory immediately inside `DIR`, using the \
+            other options given for every torrent. Torrents are written to the directory given \
+            by `--batch-output`. Children whose `.torrent` file already exists are skipped, \
+            unless `--force` is given, as is the child containing `--batch-output`. If a torrent \
+            cannot be created, the remaining children are still processed. A summary of \
+            created, skipped, and failed torrents is printed when done."
+  )]
+  batch: Option<PathBuf>,
+  #[structopt(
+    long = "batch-output",
+    value_name = "DIR",
+    empty_values(false),
+    parse(from_os_str),
+    requires = "batch",
+    help = "Write torrents created with `--batch` to `DIR`."
+  )]
+  batch_output: Option<PathBuf>,
+  #[structopt(
+    long = "batch-template",
+    value_name = "TEMPLATE",
+    default_value = "{name}.torrent",
+    empty_values(false),
+    help = "Name torrents created with `--batch` according to `TEMPLATE`. The string `{name}` in \
+            `TEMPLATE` is replaced with the name of the file or directory that the torrent was \
+            created from. `TEMPLATE` may not contain path separators."
+  )]
+  batch_template: String,
   #[structopt(
     long = "comment",
     short = "c",
@@ -90,6 +128,38 @@ Examples:
     help = "Skip writing `.torrent` file to disk."
   )]
   dry_run: bool,
+  #[structopt(
+    long = "file-list",
+    value_name = "LIST",
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    conflicts_with_all = &[
+      INPUT_FLAG,
+      INPUT_POSITIONAL,
+      "batch",
+      "follow-symlinks",
+      "from",
+      "globs",
+      "ignore",
+      "include-hidden",
+      "include-junk",
+      "max-file-size",
+      "min-file-size",
+      "newer-than",
+      "older-than",
+      "reproducible",
+      "sort-by",
+    ],
+    requires = "name",
+    help = "Create a multi-file torrent from the files listed in `LIST`, instead of searching \
+            `INPUT` for files. Each line of `LIST` contains the path of a file in the torrent, \
+            with components separated by `/`, followed by a tab, followed by the path of the file \
+            on disk that holds its contents. Relative paths on disk are relative to the current \
+            directory. Files are added to the torrent, and hashed, in the order they are listed. \
+            Empty lines and lines starting with `#` are ignored. If `LIST` is `-`, read the list \
+            from standard input. Requires `--name`."
+  )]
+  file_list: Option<InputTarget>,
   #[structopt(
     long = "follow-symlinks",
     short = "F",
@@ -103,6 +173,56 @@ Examples:
     help = "Overwrite the destination `.torrent` file, if it exists."
   )]
   force: bool,
+  #[structopt(
+    long = "from",
+    value_name = "PATH",
+    empty_values(false),
+    parse(from_os_str),
+    conflicts_with_all = &[
+      "batch",
+      "follow-symlinks",
+      "globs",
+      "ignore",
+      "include-hidden",
+      "include-junk",
+      "max-file-size",
+      "min-file-size",
+      "newer-than",
+      "older-than",
+      "manifest",
+      "md5sum",
+      "name",
+      "piece-length",
+      "reproducible",
+      "sort-by",
+    ],
+    help = "Copy the name, piece length, files, and piece hashes from the `.torrent` file at \
+            `PATH`, instead of hashing content. Useful for cross-seeding the same content with a \
+            different source or announce URL. Other settings are not copied, but may be given on \
+            the command line, or copied with `--like`. If `INPUT` is given, sampled pieces are \
+            checked against the content at `INPUT` before the torrent is created."
+  )]
+  from: Option<PathBuf>,
+  #[structopt(
+    long = "git-comment",
+    requires = "git-rev",
+    conflicts_with = "comment",
+    help = "Set the torrent comment to the full hash of the commit given with `--git-rev`."
+  )]
+  git_comment: bool,
+  #[structopt(
+    long = "git-rev",
+    value_name = "REV",
+    empty_values(false),
+    conflicts_with_all = &["batch", "file-list", "from", "tar"],
+    help = "Create a torrent from the files in commit `REV` of the git repository at `INPUT`, \
+            instead of the files on disk. `REV` may be any revision that names a commit, like a \
+            branch, tag, or commit hash. Files are read from the repository, so untracked, \
+            ignored, and modified files in the working tree do not affect the torrent. All \
+            files in the commit are included, except symbolic links and submodules. The torrent \
+            name defaults to the name of the repository."
+  )]
+  git_rev: Option<String>,
   #[structopt(
     long = "glob",
     short = "g",
@@ -124,11 +244,24 @@ Examples:
     help = "Include junk files that would otherwise be skipped."
   )]
   include_junk: bool,
+  #[structopt(
+    long = "io",
+    value_name = "BACKEND",
+    default_value = IoBackend::Buffered.into(),
+    possible_values = IoBackend::VARIANTS,
+    help = "Read files with `BACKEND`. `buffered` reads files through the page cache. `direct` \
+            opens files with `O_DIRECT`, bypassing the page cache, which avoids evicting other \
+            data from it when hashing content larger than memory. `fadvise` reads files through \
+            the page cache, but advises the kernel with `posix_fadvise` to drop the pages of \
+            each piece once it has been read. `direct` and `fadvise` fall back to `buffered` on \
+            platforms and filesystems that do not support them."
+  )]
+  io: IoBackend,
   #[structopt(
     name = INPUT_POSITIONAL,
     value_name = "INPUT",
     empty_values = false,
-    required_unless = INPUT_FLAG,
+    required_unless_one = &[INPUT_FLAG, "batch", "file-list", "from"],
     conflicts_with = INPUT_FLAG,
     parse(try_from_os_str = InputTarget::try_from_os_str),
     help = INPUT_HELP,
@@ -144,11 +277,51 @@ Examples:
     help = INPUT_HELP,
   )]
   input_flag: Option<InputTarget>,
+  #[structopt(
+    long = "like",
+    value_name = "PATH",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Copy settings from the `.torrent` file at `PATH`. The announce URL, announce tiers, \
+            DHT bootstrap nodes, comment, source, update URL, piece length, and private flag are \
+            copied. Settings given on the command line take precedence over those copied from \
+            `PATH`."
+  )]
+  like: Option<PathBuf>,
   #[structopt(
     long = "link",
     help = "Print created torrent `magnet:` URL to standard output"
   )]
   print_magnet_link: bool,
+  #[structopt(
+    long = "manifest",
+    value_name = "TARGET",
+    empty_values(false),
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    conflicts_with = "batch",
+    help = "Write a manifest of torrent files, with their lengths and checksums, to `TARGET`, or \
+            to standard output if `TARGET` is `-`. Checksums are calculated while hashing \
+            pieces, without reading the content again."
+  )]
+  manifest: Option<OutputTarget>,
+  #[structopt(
+    long = "manifest-format",
+    value_name = "FORMAT",
+    default_value = ManifestFormat::Json.into(),
+    possible_values = ManifestFormat::VARIANTS,
+    help = "Write manifest in `FORMAT`. `csv` and `json` manifests include MD5 checksums when \
+            `--md5` is given, and SHA-256 checksums when `--sha256` is given. `md5sum` and \
+            `sha256sum` manifests can be checked with `md5sum -c` and `sha256sum -c`. `sfv` \
+            manifests include CRC32 checksums."
+  )]
+  manifest_format: ManifestFormat,
+  #[structopt(
+    long = "max-file-size",
+    value_name = "BYTES",
+    help = "Skip files larger than `BYTES` when searching `INPUT` for files. Accepts SI units, \
+            e.g. kib, mib, and gib."
+  )]
+  max_file_size: Option<Bytes>,
   #[structopt(
     long = "md5",
     short = "M",
@@ -156,6 +329,13 @@ Examples:
             broken and only suitable for checking for accidental corruption."
   )]
   md5sum: bool,
+  #[structopt(
+    long = "min-file-size",
+    value_name = "BYTES",
+    help = "Skip files smaller than `BYTES` when searching `INPUT` for files. Accepts SI units, \
+            e.g. kib, mib, and gib."
+  )]
+  min_file_size: Option<Bytes>,
   #[structopt(
     long = "name",
     short = "N",
@@ -166,6 +346,16 @@ Examples:
     required_if(INPUT_POSITIONAL, "-")
   )]
   name: Option<String>,
+  #[structopt(
+    long = "newer-than",
+    value_name = "TIME",
+    help = "Skip files last modified at or before `TIME` when searching `INPUT` for files. `TIME` \
+            may be an RFC 3339 timestamp, like `2020-04-01T12:00:00Z`, a date, like `2020-04-01`, \
+            which is taken to be midnight UTC, or an age, given as a number followed by `s`, `m`, \
+            `h`, `d`, or `w`, for seconds, minutes, hours, days, or weeks before the present. For \
+            example, `--newer-than 7d` includes only files modified in the last week."
+  )]
+  newer_than: Option<TimeSpec>,
   #[structopt(
     long = "no-created-by",
     help = "Do not populate `created by` key of generated torrent with imdl version information."
@@ -176,6 +366,38 @@ Examples:
     help = "Do not populate `creation date` key of generated torrent with current time."
   )]
   no_creation_date: bool,
+  #[structopt(
+    long = "no-include-hidden",
+    conflicts_with = "include-hidden",
+    help = "Skip hidden files, even if the config file profile sets `include-hidden`."
+  )]
+  no_include_hidden: bool,
+  #[structopt(
+    long = "no-include-junk",
+    conflicts_with = "include-junk",
+    help = "Skip junk files, even if the config file profile sets `include-junk`."
+  )]
+  no_include_junk: bool,
+  #[structopt(
+    long = "no-md5",
+    conflicts_with = "md5sum",
+    help = "Do not include MD5 checksums, even if the config file profile sets `md5`."
+  )]
+  no_md5sum: bool,
+  #[structopt(
+    long = "no-private",
+    conflicts_with = "private",
+    help = "Do not set the `private` flag, even if the config file profile sets `private`, or the \
+            torrent given to `--like` has the flag set."
+  )]
+  no_private: bool,
+  #[structopt(
+    long = "older-than",
+    value_name = "TIME",
+    help = "Skip files last modified at or after `TIME` when searching `INPUT` for files. `TIME` \
+            is given in the same formats as for `--newer-than`."
+  )]
+  older_than: Option<TimeSpec>,
   #[structopt(
     long = "open",
     short = "O",
@@ -229,7 +451,11 @@ Sort in ascending order by size, break ties in descending path order:
     long = "piece-length",
     short = "p",
     value_name = "BYTES",
-    help = "Set piece length to `BYTES`. Accepts SI units, e.g. kib, mib, and gib."
+    help = "Set piece length to `BYTES`. Accepts SI units, e.g. kib, mib, and gib. Files are only \
+            hashed while `INPUT` is being searched, instead of after the search has finished, \
+            when the piece length is given, files are sorted by path, and `--verbose` is not \
+            given. Otherwise, all of `INPUT` is searched before hashing starts, since the total \
+            size of the content is needed to pick a piece length."
   )]
   piece_length: Option<Bytes>,
   #[structopt(
@@ -242,6 +468,40 @@ Sort in ascending order by size, break ties in descending path order:
             more information."
   )]
   private: bool,
+  #[structopt(
+    long = "read-size",
+    value_name = "BYTES",
+    help = "Read at most `BYTES` from files at a time. Accepts SI units, e.g. kib, mib, and gib. \
+            Must be a multiple of 4 KiB with `--io direct`. Defaults to 1 MiB with `--io direct`, \
+            and to as much of the current piece as possible otherwise."
+  )]
+  read_size: Option<Bytes>,
+  #[structopt(
+    long = "reproducible",
+    conflicts_with_all = &["sort-by", "include-junk", "follow-symlinks"],
+    help = "Create a torrent that is byte-for-byte identical when created from the same content \
+            with the same arguments, on any platform and with any version of `imdl`. Omits the \
+            creation date, unless the `SOURCE_DATE_EPOCH` environment variable is set, in which \
+            case it is used as the creation date. Omits the `imdl` version from `created by`. \
+            Sorts files in the default order and normalizes file names to Unicode Normalization \
+            Form C. Fails if the content contains symlinks or files hidden by file attributes, \
+            since they are treated differently on different platforms."
+  )]
+  reproducible: bool,
+  #[structopt(
+    long = "sample",
+    value_name = "N",
+    requires = "from",
+    help = "Check `N` pieces, spread evenly through the content, against the content at `INPUT` \
+            when `--from` is given. Defaults to 8."
+  )]
+  sample: Option<usize>,
+  #[structopt(
+    long = "sha256",
+    requires = "manifest",
+    help = "Include SHA-256 checksum of each file in the manifest written with `--manifest`."
+  )]
+  sha256: bool,
   #[structopt(
     long = "show",
     short = "S",
@@ -260,6 +520,15 @@ Sort in ascending order by size, break ties in descending path order:
             download and upload statistics to multiple trackers."
   )]
   source: Option<String>,
+  #[structopt(
+    long = "tar",
+    conflicts_with_all = &["batch", "file-list", "from"],
+    help = "Read a tar archive from standard input, and create a multi-file torrent containing \
+            the regular files in the archive. Files are added to the torrent, and hashed, in the \
+            order they appear in the archive. Directories, links, and other special files are \
+            skipped. Requires `--input -`."
+  )]
+  tar: bool,
   #[structopt(
     long = "ignore",
     help = "Skip files listed in `.gitignore`, `.ignore`, `.git/info/exclude`, and `git config \
@@ -274,16 +543,279 @@ Sort in ascending order by size, break ties in descending path order:
             the torret's metainfo. Note that BEP 39 is not widely supported."
   )]
   update_url: Option<Url>,
+  #[structopt(
+    long = "verbose",
+    short = "v",
+    help = "Print the path of each file skipped while searching `INPUT` for files, and the reason \
+            it was skipped, to standard error."
+  )]
+  verbose: bool,
 }
 
 impl Create {
-  pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
-    let input = xor_args(
-      "input_positional",
-      &self.input_positional,
-      "input_flag",
-      &self.input_flag,
-    )?;
+  pub(crate) fn run(mut self, env: &mut Env, options: &Options) -> Result<(), Error> {
+    if let Some(like) = self.like.clone() {
+      let input = env.read(InputTarget::Path(like))?;
+      self.apply_like(Metainfo::from_input(&input)?)?;
+    }
+
+    if let Some(profile) = Config::load_profile(env, options)? {
+      self.apply_profile(profile)?;
+    }
+
+    if let Some(batch) = self.batch.clone() {
+      return self.run_batch(env, options, &batch);
+    }
+
+    self.create(env, options)?;
+
+    Ok(())
+  }
+
+  /// Fill in settings that were not given on the command line from an
+  /// existing torrent.
+  fn apply_like(&mut self, metainfo: Metainfo) -> Result<(), Error> {
+    if self.announce.is_none() {
+      if let Some(announce) = metainfo.announce {
+        self.announce = Some(announce.parse().context(error::AnnounceUrlParse)?);
+      }
+    }
+
+    if self.announce_tiers.is_empty() {
+      self.announce_tiers = metainfo
+        .announce_list
+        .unwrap_or_default()
+        .into_iter()
+        .map(|tier| tier.join(","))
+        .collect();
+    }
+
+    if self.dht_nodes.is_empty() {
+      self.dht_nodes = metainfo.nodes.unwrap_or_default();
+    }
+
+    if self.comment.is_none() {
+      self.comment = metainfo.comment;
+    }
+
+    if self.source.is_none() {
+      self.source = metainfo.info.source;
+    }
+
+    if self.update_url.is_none() {
+      self.update_url = metainfo.info.update_url;
+    }
+
+    if self.piece_length.is_none() {
+      self.piece_length = Some(metainfo.info.piece_length);
+    }
+
+    if !self.no_private {
+      self.private |= metainfo.info.private.unwrap_or(false);
+    }
+
+    Ok(())
+  }
+
+  /// Fill in settings that were not given on the command line from `profile`.
+  fn apply_profile(&mut self, profile: Profile) -> Result<(), Error> {
+    let Profile {
+      allow,
+      announce,
+      announce_tier,
+      comment,
+      glob,
+      include_hidden,
+      include_junk,
+      md5,
+      node,
+      piece_length,
+      private,
+      source,
+      update_url,
+    } = profile;
+
+    if let Some(lint) = allow.iter().find(|lint| !lint.create()) {
+      return Err(Error::LintNotCreate { lint: *lint });
+    }
+
+    if self.allowed_lints.is_empty() {
+      self.allowed_lints = allow;
+    }
+
+    if self.announce.is_none() {
+      self.announce = announce;
+    }
+
+    if self.announce_tiers.is_empty() {
+      self.announce_tiers = announce_tier;
+    }
+
+    if self.comment.is_none() {
+      self.comment = comment;
+    }
+
+    if self.globs.is_empty() {
+      self.globs = glob;
+    }
+
+    if !self.no_include_hidden {
+      self.include_hidden |= include_hidden.unwrap_or(false);
+    }
+
+    if !self.no_include_junk {
+      self.include_junk |= include_junk.unwrap_or(false);
+    }
+
+    if !self.no_md5sum {
+      self.md5sum |= md5.unwrap_or(false);
+    }
+
+    if self.dht_nodes.is_empty() {
+      self.dht_nodes = node;
+    }
+
+    if self.piece_length.is_none() {
+      self.piece_length = piece_length;
+    }
+
+    if !self.no_private {
+      self.private |= private.unwrap_or(false);
+    }
+
+    if self.source.is_none() {
+      self.source = source;
+    }
+
+    if self.update_url.is_none() {
+      self.update_url = update_url;
+    }
+
+    Ok(())
+  }
+
+  fn run_batch(self, env: &mut Env, options: &Options, batch: &Path) -> Result<(), Error> {
+    let dir = env.resolve(batch)?;
+
+    let output_dir = env.resolve(self.batch_output.as_ref().ok_or_else(|| {
+      Error::internal("Expected `--batch-output` to be set when `--batch` is given.")
+    })?)?;
+
+    if self.batch_template.contains(|c| c == '/' || c == '\\')
+      || self.batch_template == "."
+      || self.batch_template == ".."
+    {
+      return Err(Error::BatchTemplate {
+        template: self.batch_template.clone(),
+      });
+    }
+
+    let mut children = Vec::new();
+    for result in fs::read_dir(&dir).context(error::Filesystem { path: &dir })? {
+      let entry = result.context(error::Filesystem { path: &dir })?;
+      children.push(entry.path());
+    }
+    children.sort();
+
+    let mut report = BatchReport::new();
+
+    for child in children {
+      let filename = child
+        .file_name()
+        .ok_or_else(|| Error::FilenameExtract {
+          path: child.clone(),
+        })?
+        .to_str()
+        .ok_or_else(|| Error::FilenameDecode {
+          filename: child.clone(),
+        })?
+        .to_owned();
+
+      if !self.include_hidden && (filename.starts_with('.') || Platform::hidden(&child)?) {
+        continue;
+      }
+
+      if !self.include_junk && Walker::is_junk(&filename) {
+        continue;
+      }
+
+      if !self.follow_symlinks
+        && child
+          .symlink_metadata()
+          .context(error::Filesystem { path: &child })?
+          .file_type()
+          .is_symlink()
+      {
+        continue;
+      }
+
+      if output_dir.starts_with(&child) {
+        continue;
+      }
+
+      let torrent = output_dir.join(self.batch_template.replace("{name}", &filename));
+
+      if !self.force && torrent.exists() {
+        report.skipped(filename, torrent);
+        continue;
+      }
+
+      let mut create = self.clone();
+      create.batch = None;
+      create.input_flag = Some(InputTarget::Path(child));
+      create.output = Some(OutputTarget::Path(torrent.clone()));
+
+      match create
+        .create(env, options)
+        .and_then(|metainfo| metainfo.infohash())
+      {
+        Ok(infohash) => report.created(filename, infohash, torrent),
+        Err(error) => {
+          errln!(
+            env,
+            "Failed to create torrent for `{}`: {}",
+            filename,
+            error
+          )?;
+          report.failed(filename, torrent);
+        }
+      }
+    }
+
+    report.write(env)?;
+
+    match report.failures() {
+      0 => Ok(()),
+      count => Err(Error::BatchFailed { count }),
+    }
+  }
+
+  fn source_date_epoch(value: Option<&OsStr>) -> Result<Option<u64>, Error> {
+    match value {
+      Some(value) => {
+        let text = value.to_string_lossy();
+        Ok(Some(text.parse().context(error::SourceDateEpochParse {
+          text: text.as_ref(),
+        })?))
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn create(self, env: &mut Env, options: &Options) -> Result<Metainfo, Error> {
+    let input = if self.from.is_some() || self.file_list.is_some() {
+      self
+        .input_positional
+        .clone()
+        .or_else(|| self.input_flag.clone())
+    } else {
+      Some(xor_args(
+        "input_positional",
+        &self.input_positional,
+        "input_flag",
+        &self.input_flag,
+      )?)
+    };
 
     let mut linter = Linter::new();
     linter.allow(self.allowed_lints.iter().cloned());
@@ -307,6 +839,8 @@ impl Create {
 
     let creation_date = if self.no_creation_date {
       None
+    } else if self.reproducible {
+      Self::source_date_epoch(env.var("SOURCE_DATE_EPOCH"))?
     } else {
       Some(
         SystemTime::now()
@@ -315,75 +849,61 @@ impl Create {
       )
     };
 
-    if !options.quiet {
-      CreateStep::Searching { input: &input }.print(env)?;
-    }
+    let reader = FileReader::new(self.io, self.read_size)?;
 
-    let content = CreateContent::from_create(&self, &input, env)?;
+    let mut commit = None;
 
-    let output = content.output.resolve(env)?;
+    let (name, piece_length, mode, pieces, output) = match (&self.from, &self.file_list, &input) {
+      (Some(from), _, input) => self.copy_from(env, from, input.as_ref())?,
+      (None, Some(list), _) => {
+        if !options.quiet {
+          CreateStep::Reading { list }.print(env)?;
+        }
 
-    if content.piece_length.count() == 0 {
-      return Err(Error::PieceLengthZero);
-    }
+        let content = CreateContent::from_file_list(&self, list, env)?;
 
-    if linter.is_denied(Lint::UnevenPieceLength) && !content.piece_length.count().is_power_of_two()
-    {
-      return Err(Error::PieceLengthUneven {
-        bytes: content.piece_length,
-      });
-    }
+        self.hash(env, options, &linter, reader, content)?
+      }
+      (None, None, Some(input)) => {
+        if !options.quiet {
+          CreateStep::Searching { input }.print(env)?;
+        }
 
-    if linter.is_denied(Lint::SmallPieceLength) && content.piece_length.count() < 16 * 1024 {
-      return Err(Error::PieceLengthSmall);
-    }
+        let content = match &self.git_rev {
+          Some(rev) => CreateContent::from_git(&self, input, rev, env)?,
+          None => CreateContent::from_create(&self, input, env)?,
+        };
 
-    if let OutputTarget::Path(path) = &output {
-      if !self.force && path.exists() {
-        return Err(Error::OutputExists {
-          path: path.to_owned(),
-        });
+        commit = content
+          .git_tree
+          .as_ref()
+          .map(|tree| tree.commit().to_owned());
+
+        self.hash(env, options, &linter, reader, content)?
       }
-    }
+      (None, None, None) => return Err(Error::internal("Expected `INPUT` to be set.")),
+    };
 
     let private = if self.private { Some(true) } else { None };
 
     let created_by = if self.no_created_by {
       None
+    } else if self.reproducible {
+      Some(String::from(consts::CREATED_BY_REPRODUCIBLE))
     } else {
       Some(String::from(consts::CREATED_BY_DEFAULT))
     };
 
     if !options.quiet {
-      CreateStep::Hashing.print(env)?;
+      CreateStep::Writing { output: &output }.print(env)?;
     }
 
-    let hasher = Hasher::new(
-      self.md5sum,
-      content.piece_length.as_piece_length()?.into_usize(),
-      if env.err().is_styled_term() && !options.quiet {
-        Some(content.progress_bar)
-      } else {
-        None
-      },
-    );
-
-    let (mode, pieces) = if let Some(files) = content.files {
-      hasher.hash_files(&files)?
-    } else {
-      hasher.hash_stdin(&mut env.input())?
-    };
-
-    if !options.quiet {
-      CreateStep::Writing {
-        output: &content.output,
-      }
-      .print(env)?;
-    }
+    let output = output.resolve(env)?;
 
     let info = Info {
-      name: content.name,
-      piece_length: content.piece_length,
+      extra: BTreeMap::new(),
+      name,
+      piece_length,
       source: self.source,
       update_url: self.update_url,
       mode,
@@ -392,7 +912,12 @@ impl Create {
     };
 
     let metainfo = Metainfo {
-      comment: self.comment,
+      extra: BTreeMap::new(),
+      comment: if self.git_comment {
+        commit
+      } else {
+        self.comment
+      },
       encoding: Some(consts::ENCODING_UTF8.to_string()),
       announce: self.announce.map(|url| url.to_string()),
       announce_list: if announce_list.is_empty() {
@@ -434,12 +959,13 @@ impl Create {
 
     #[cfg(test)]
     {
-      if let InputTarget::Path(path) = &input {
+      // Content in a git commit may differ from the working tree.
+      if let (Some(InputTarget::Path(path)), None) = (&input, &self.git_rev) {
         let deserialized = bendy::serde::de::from_bytes::<Metainfo>(&bytes).unwrap();
 
         assert_eq!(deserialized, metainfo);
 
-        let status = metainfo.verify(&env.resolve(path)?, None)?;
+        let status = metainfo.verify(&env.resolve(path)?, FileReader::default(), None)?;
 
         status.print(env)?;
 
@@ -471,55 +997,222 @@ impl Create {
       }
     }
 
-    Ok(())
+    Ok(metainfo)
   }
-}
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+  /// Check `content` for lint violations and hash it.
+  fn hash(
+    &self,
+    env: &mut Env,
+    options: &Options,
+    linter: &Linter,
+    reader: FileReader,
+    content: CreateContent,
+  ) -> Result<(String, Bytes, Mode, PieceList, OutputTarget), Error> {
+    if content.piece_length.count() == 0 {
+      return Err(Error::PieceLengthZero);
+    }
 
-  use pretty_assertions::assert_eq;
+    if linter.is_denied(Lint::UnevenPieceLength) && !content.piece_length.count().is_power_of_two()
+    {
+      return Err(Error::PieceLengthUneven {
+        bytes: content.piece_length,
+      });
+    }
 
-  #[test]
-  fn require_input_argument() {
-    let mut env = test_env! { args: [], tree: {} };
-    assert!(matches!(env.run(), Err(Error::Clap { .. })));
-  }
+    if linter.is_denied(Lint::SmallPieceLength) && content.piece_length.count() < 16 * 1024 {
+      return Err(Error::PieceLengthSmall);
+    }
 
-  #[test]
-  fn input_arguments_conflict() {
-    let mut env = test_env! {
-      args: [
-        "torrent",
-        "create",
-        "--input",
-        "foo",
-        "bar",
-      ],
-      tree: {},
-    };
-    assert_matches!(env.run(), Err(Error::Clap { .. }));
-  }
+    if let Some(tree) = &content.git_tree {
+      Self::check_paths(
+        linter,
+        &content.name,
+        tree.files().iter().map(|file| &file.info.path),
+      )?;
+    } else {
+      let paths = content
+        .files
+        .as_ref()
+        .and_then(Files::contents)
+        .unwrap_or_default();
 
-  #[test]
-  fn require_name_if_input_flag_stdin() {
-    let mut env = test_env! {
-      args: [
-        "torrent",
-        "create",
-        "--input",
-        "-",
-      ],
-      tree: {},
-    };
-    assert_matches!(env.run(), Err(Error::Clap { .. }));
-  }
+      Self::check_paths(linter, &content.name, paths)?;
+    }
 
-  #[test]
-  fn require_name_if_input_positional_stdin() {
-    let mut env = test_env! {
-      args: [
+    self.check_output(env, &content.output)?;
+
+    if let Some(manifest) = &self.manifest {
+      self.check_output(env, manifest)?;
+    }
+
+    if !options.quiet {
+      CreateStep::Hashing.print(env)?;
+    }
+
+    let mut hasher = Hasher::new(
+      self.md5sum,
+      content.piece_length.as_piece_length()?.into_usize(),
+      if env.err().is_styled_term() && !options.quiet {
+        Some(content.progress_bar)
+      } else {
+        None
+      },
+    );
+
+    hasher = hasher.reader(reader);
+
+    if self.manifest.is_some() {
+      hasher = hasher.manifest(self.manifest_format, self.sha256);
+    }
+
+    // Paths in a tar archive, or of streamed files, are only known once they
+    // have been found, so they are checked as they are hashed.
+    if self.tar || content.files.as_ref().map_or(false, Files::is_stream) {
+      hasher = hasher.path_checker(PathChecker::new(linter.clone()));
+    }
+
+    let (mode, pieces, manifest) = if let Some(tree) = &content.git_tree {
+      hasher.hash_git(tree)?
+    } else if let Some(files) = content.files {
+      hasher.hash_files(files, &content.name)?
+    } else if self.tar {
+      hasher.hash_tar(&mut env.input())?
+    } else {
+      hasher.hash_stdin(&mut env.input(), &content.name)?
+    };
+
+    if let (Some(target), Some(manifest)) = (&self.manifest, manifest) {
+      if !self.dry_run {
+        target.write(env, manifest.render(self.manifest_format)?.as_bytes())?;
+      }
+    }
+
+    Ok((
+      content.name,
+      content.piece_length,
+      mode,
+      pieces,
+      content.output,
+    ))
+  }
+
+  /// Copy the name, piece length, files, and piece hashes of the torrent at
+  /// `from`, after checking sampled pieces against the content at `input`, if
+  /// given.
+  fn copy_from(
+    &self,
+    env: &mut Env,
+    from: &Path,
+    input: Option<&InputTarget>,
+  ) -> Result<(String, Bytes, Mode, PieceList, OutputTarget), Error> {
+    let info = Metainfo::from_input(&env.read(InputTarget::Path(from.to_owned()))?)?.info;
+
+    let output = match (&self.output, input) {
+      (Some(output), _) => output.clone(),
+      (None, Some(InputTarget::Path(path))) => {
+        OutputTarget::Path(CreateContent::torrent_path(path, &info.name))
+      }
+      (None, _) => OutputTarget::Path(PathBuf::from(format!("{}.torrent", info.name))),
+    };
+
+    self.check_output(env, &output)?;
+
+    if let Some(InputTarget::Path(path)) = input {
+      let base = env.resolve(path)?;
+      let sampler = Sampler::new(&info, &base);
+
+      let indices = Sampler::indices(self.sample.unwrap_or(SAMPLE_DEFAULT), info.pieces.count());
+
+      let bad = indices
+        .iter()
+        .filter(|index| !sampler.check(**index))
+        .count();
+
+      if bad > 0 {
+        return Err(Error::SampleMismatch {
+          sampled: indices.len(),
+          path: base,
+          bad,
+        });
+      }
+    }
+
+    Ok((info.name, info.piece_length, info.mode, info.pieces, output))
+  }
+
+  fn check_paths<'a>(
+    linter: &Linter,
+    name: &str,
+    paths: impl IntoIterator<Item = &'a FilePath>,
+  ) -> Result<(), Error> {
+    let mut checker = PathChecker::new(linter.clone());
+
+    checker.check_name(name)?;
+
+    for path in paths {
+      checker.check(path)?;
+    }
+
+    Ok(())
+  }
+
+  fn check_output(&self, env: &Env, output: &OutputTarget) -> Result<(), Error> {
+    if let OutputTarget::Path(path) = output.resolve(env)? {
+      if !self.force && path.exists() {
+        return Err(Error::OutputExists { path });
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn require_input_argument() {
+    let mut env = test_env! { args: [], tree: {} };
+    assert!(matches!(env.run(), Err(Error::Clap { .. })));
+  }
+
+  #[test]
+  fn input_arguments_conflict() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "bar",
+      ],
+      tree: {},
+    };
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
+  #[test]
+  fn require_name_if_input_flag_stdin() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "-",
+      ],
+      tree: {},
+    };
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
+  #[test]
+  fn require_name_if_input_positional_stdin() {
+    let mut env = test_env! {
+      args: [
         "torrent",
         "create",
         "-",
@@ -1256,11 +1949,13 @@ mod tests {
             path: FilePath::from_components(&["bar"]),
             length: Bytes(4),
             md5sum: Some(Md5Digest::from_data("5678")),
+            extra: BTreeMap::new(),
           },
           FileInfo {
             path: FilePath::from_components(&["foo"]),
             length: Bytes(4),
             md5sum: Some(Md5Digest::from_data("1234")),
+            extra: BTreeMap::new(),
           },
         ],
       }
@@ -1405,6 +2100,7 @@ mod tests {
             length: Bytes(3),
             md5sum: Some(Md5Digest::from_hex("37b51d194a7513e45b56f6524f2d51f2")),
             path: FilePath::from_components(&["bar"]),
+            extra: BTreeMap::new(),
           },]
         );
       }
@@ -1440,6 +2136,7 @@ mod tests {
             length: Bytes(3),
             md5sum: None,
             path: FilePath::from_components(&["bar"]),
+            extra: BTreeMap::new(),
           },]
         );
       }
@@ -1479,16 +2176,19 @@ mod tests {
               length: Bytes(3),
               md5sum: Some(Md5Digest::from_hex("900150983cd24fb0d6963f7d28e17f72")),
               path: FilePath::from_components(&["a"]),
+              extra: BTreeMap::new(),
             },
             FileInfo {
               length: Bytes(3),
               md5sum: Some(Md5Digest::from_hex("857c4402ad934005eae4638a93812bf7")),
               path: FilePath::from_components(&["h"]),
+              extra: BTreeMap::new(),
             },
             FileInfo {
               length: Bytes(3),
               md5sum: Some(Md5Digest::from_hex("d16fb36f0911f878998c136191af705e")),
               path: FilePath::from_components(&["x"]),
+              extra: BTreeMap::new(),
             },
           ]
         );
@@ -1976,11 +2676,13 @@ Content Size  9 bytes
               length: Bytes(3),
               md5sum: Some(Md5Digest::from_hex("37b51d194a7513e45b56f6524f2d51f2")),
               path: FilePath::from_components(&["bar"]),
+              extra: BTreeMap::new(),
             },
             FileInfo {
               length: Bytes(3),
               md5sum: Some(Md5Digest::from_hex("73feffa4b7f6bb68e44cf984c85f6e88")),
               path: FilePath::from_components(&["dir", "baz"]),
+              extra: BTreeMap::new(),
             },
           ]
         );
@@ -2089,6 +2791,118 @@ Content Size  9 bytes
     Ok(())
   }
 
+  #[test]
+  fn file_size_filters() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--min-file-size",
+        "2",
+        "--max-file-size",
+        "3",
+        "--verbose",
+      ],
+      tree: {
+        foo: {
+          a: "a",
+          b: "bb",
+          c: "ccc",
+          d: "dddd",
+        },
+      }
+    };
+    env.assert_ok();
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_matches!(
+      metainfo.info.mode,
+      Mode::Multiple { files } if files.len() == 2
+    );
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["bbccc"]));
+    assert!(env.err().contains(
+      "Skipped `a`: smaller than minimum file size 2 bytes\nSkipped `d`: larger than maximum file \
+       size 3 bytes\n"
+    ));
+  }
+
+  #[test]
+  fn modification_time_filters() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--newer-than",
+        "2010-01-01",
+        "--older-than",
+        "1d",
+      ],
+      tree: {
+        foo: {
+          new: "new",
+          old: "old",
+          recent: "recent",
+        },
+      }
+    };
+
+    let set_modified = |path: &Path, time: SystemTime| {
+      File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(time)
+        .unwrap();
+    };
+
+    set_modified(
+      &env.resolve("foo/old").unwrap(),
+      "2000-01-01"
+        .parse::<TimeSpec>()
+        .unwrap()
+        .resolve(SystemTime::now()),
+    );
+    set_modified(
+      &env.resolve("foo/recent").unwrap(),
+      "2020-01-01"
+        .parse::<TimeSpec>()
+        .unwrap()
+        .resolve(SystemTime::now()),
+    );
+
+    env.assert_ok();
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_matches!(
+      metainfo.info.mode,
+      Mode::Multiple { files } if files.len() == 1
+    );
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["recent"]));
+    assert!(!env.err().contains("Skipped"));
+  }
+
+  #[test]
+  fn time_filter_invalid() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--newer-than",
+        "yesterday",
+      ],
+      tree: {},
+    };
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
   #[test]
   fn glob_exclude() {
     let mut env = test_env! {
@@ -3191,4 +4005,2064 @@ Content Size  9 bytes
       Some("https://www.a_real_url.com/".parse().unwrap())
     );
   }
+
+  #[test]
+  fn batch_requires_output() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+      ],
+      tree: {
+        dir: {
+          foo: "",
+        },
+      }
+    };
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
+  #[test]
+  fn batch_conflicts_with_input() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+        "--input",
+        "dir/foo",
+      ],
+      tree: {
+        dir: {
+          foo: "",
+        },
+        out: {},
+      }
+    };
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
+  #[test]
+  fn batch() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+        "--announce",
+        "https://bar",
+        "--private",
+      ],
+      tree: {
+        dir: {
+          foo: "abc",
+          bar: {
+            a: "xyz",
+            b: "123",
+          },
+          ".hidden": "",
+        },
+        out: {},
+      }
+    };
+
+    env.assert_ok();
+
+    let foo = env.load_metainfo("out/foo.torrent");
+    assert_eq!(foo.info.name, "foo");
+    assert_eq!(foo.announce, Some("https://bar/".into()));
+    assert_eq!(foo.info.private, Some(true));
+    assert_matches!(foo.info.mode, Mode::Single { .. });
+
+    let bar = env.load_metainfo("out/bar.torrent");
+    assert_eq!(bar.info.name, "bar");
+    assert_eq!(bar.announce, Some("https://bar/".into()));
+    assert_matches!(&bar.info.mode, Mode::Multiple { files } if files.len() == 2);
+
+    assert!(!env.resolve("out/.hidden.torrent").unwrap().exists());
+
+    assert_eq!(
+      env.out(),
+      format!(
+        "bar\tcreated\t{}\t{}\nfoo\tcreated\t{}\t{}\n",
+        bar.infohash().unwrap(),
+        env.resolve("out/bar.torrent").unwrap().display(),
+        foo.infohash().unwrap(),
+        env.resolve("out/foo.torrent").unwrap().display(),
+      )
+    );
+  }
+
+  #[test]
+  fn batch_template() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+        "--batch-template",
+        "release-{name}.torrent",
+      ],
+      tree: {
+        dir: {
+          foo: "abc",
+        },
+        out: {},
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("out/release-foo.torrent");
+    assert_eq!(metainfo.info.name, "foo");
+  }
+
+  #[test]
+  fn batch_skips_existing() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+      ],
+      tree: {
+        dir: {
+          foo: "abc",
+          bar: "xyz",
+        },
+        out: {
+          "foo.torrent": "existing",
+        },
+      }
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.read_to_string("out/foo.torrent"), "existing");
+
+    let bar = env.load_metainfo("out/bar.torrent");
+
+    assert_eq!(
+      env.out(),
+      format!(
+        "bar\tcreated\t{}\t{}\nfoo\tskipped\t-\t{}\n",
+        bar.infohash().unwrap(),
+        env.resolve("out/bar.torrent").unwrap().display(),
+        env.resolve("out/foo.torrent").unwrap().display(),
+      )
+    );
+  }
+
+  #[test]
+  fn batch_force() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+        "--force",
+      ],
+      tree: {
+        dir: {
+          foo: "abc",
+        },
+        out: {
+          "foo.torrent": "existing",
+        },
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("out/foo.torrent");
+    assert_eq!(metainfo.info.name, "foo");
+  }
+
+  #[test]
+  fn batch_skips_junk() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+      ],
+      tree: {
+        dir: {
+          "Desktop.ini": "x",
+          "Thumbs.db": "x",
+          foo: "abc",
+        },
+        out: {},
+      }
+    };
+
+    env.assert_ok();
+
+    env.load_metainfo("out/foo.torrent");
+    assert!(!env.resolve("out/Desktop.ini.torrent").unwrap().exists());
+    assert!(!env.resolve("out/Thumbs.db.torrent").unwrap().exists());
+  }
+
+  #[test]
+  fn batch_include_junk() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+        "--include-junk",
+      ],
+      tree: {
+        dir: {
+          "Thumbs.db": "x",
+        },
+        out: {},
+      }
+    };
+
+    env.assert_ok();
+
+    env.load_metainfo("out/Thumbs.db.torrent");
+  }
+
+  #[test]
+  fn batch_failure() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "out",
+      ],
+      tree: {
+        dir: {
+          bar: {
+            "bar.": "",
+          },
+          foo: "abc",
+        },
+        out: {},
+      }
+    };
+
+    assert_matches!(env.run(), Err(Error::BatchFailed { count: 1 }));
+
+    assert!(env.err().contains("Failed to create torrent for `bar`: "));
+
+    let foo = env.load_metainfo("out/foo.torrent");
+
+    assert_eq!(
+      env.out(),
+      format!(
+        "bar\tfailed\t-\t{}\nfoo\tcreated\t{}\t{}\n",
+        env.resolve("out/bar.torrent").unwrap().display(),
+        foo.infohash().unwrap(),
+        env.resolve("out/foo.torrent").unwrap().display(),
+      )
+    );
+  }
+
+  #[test]
+  fn batch_output_inside_batch() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--batch",
+        "dir",
+        "--batch-output",
+        "dir/out",
+      ],
+      tree: {
+        dir: {
+          foo: "abc",
+          out: {},
+        },
+      }
+    };
+
+    env.assert_ok();
+
+    env.load_metainfo("dir/out/foo.torrent");
+    assert!(!env.resolve("dir/out/out.torrent").unwrap().exists());
+  }
+
+  #[test]
+  fn batch_template_path() {
+    for template in &["../{name}.torrent", "sub/{name}.torrent", ".."] {
+      let mut env = test_env! {
+        args: [
+          "torrent",
+          "create",
+          "--batch",
+          "dir",
+          "--batch-output",
+          "out",
+          "--batch-template",
+          template,
+        ],
+        tree: {
+          dir: {
+            foo: "abc",
+          },
+          out: {},
+        }
+      };
+
+      assert_matches!(
+        env.run(),
+        Err(Error::BatchTemplate { template: have }) if &have == template
+      );
+    }
+  }
+
+  #[test]
+  fn profile_default() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+      ],
+      tree: {
+        foo: "",
+        "imdl.toml": "
+          [profile.default]
+          announce = \"https://bar/\"
+          announce-tier = [\"https://a,https://b\", \"https://c\"]
+          private = true
+          source = \"SOURCE\"
+          comment = \"COMMENT\"
+        ",
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.announce, Some("https://bar/".into()));
+    assert_eq!(
+      metainfo.announce_list,
+      Some(vec![
+        vec!["https://a".into(), "https://b".into()],
+        vec!["https://c".into()],
+      ])
+    );
+    assert_eq!(metainfo.info.private, Some(true));
+    assert_eq!(metainfo.info.source, Some("SOURCE".into()));
+    assert_eq!(metainfo.comment, Some("COMMENT".into()));
+  }
+
+  #[test]
+  fn profile_flags_take_precedence() {
+    let mut env = test_env! {
+      args: [
+        "--profile",
+        "tracker",
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--source",
+        "FLAG",
+        "--announce",
+        "https://flag/",
+      ],
+      tree: {
+        foo: "",
+        "imdl.toml": "
+          [profile.default]
+          source = \"DEFAULT\"
+
+          [profile.tracker]
+          announce = \"https://bar/\"
+          source = \"TRACKER\"
+          comment = \"COMMENT\"
+        ",
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.announce, Some("https://flag/".into()));
+    assert_eq!(metainfo.info.source, Some("FLAG".into()));
+    assert_eq!(metainfo.comment, Some("COMMENT".into()));
+  }
+
+  #[test]
+  fn profile_flags_can_be_disabled() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--no-private",
+        "--no-md5",
+        "--no-include-hidden",
+      ],
+      tree: {
+        foo: {
+          ".hidden": "a",
+          bar: "b",
+        },
+        "imdl.toml": "
+          [profile.default]
+          announce = \"https://bar/\"
+          include-hidden = true
+          md5 = true
+          private = true
+        ",
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.private, None);
+    match metainfo.info.mode {
+      Mode::Multiple { files } => {
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, FilePath::from_components(&["bar"]));
+        assert_eq!(files[0].md5sum, None);
+      }
+      Mode::Single { .. } => panic!("Expected multi-file torrent"),
+    }
+  }
+
+  #[test]
+  fn profile_globs() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+      ],
+      tree: {
+        foo: {
+          a: "",
+          "b.nfo": "",
+        },
+        "imdl.toml": "[profile.default]\nglob = [\"!*.nfo\"]\n",
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.file_paths(), &["a"]);
+  }
+
+  #[test]
+  fn profile_allow() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--piece-length",
+        "8kib",
+      ],
+      tree: {
+        foo: "",
+        "imdl.toml": "[profile.default]\nallow = [\"small-piece-length\"]\n",
+      }
+    };
+
+    env.assert_ok();
+  }
+
+  #[test]
+  fn profile_allow_lint_only() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+      ],
+      tree: {
+        foo: "",
+        "imdl.toml": "[profile.default]\nallow = [\"missing-announce\"]\n",
+      }
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(Error::LintNotCreate {
+        lint: Lint::MissingAnnounce
+      })
+    );
+  }
+
+  #[test]
+  fn allow_lint_only() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--allow",
+        "empty-file",
+      ],
+      tree: {
+        foo: "",
+      }
+    };
+
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
+  #[test]
+  fn profile_config_flag() {
+    let mut env = test_env! {
+      args: [
+        "--config",
+        "config.toml",
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+      ],
+      tree: {
+        foo: "",
+        "config.toml": "[profile.default]\nsource = \"SOURCE\"\n",
+        "imdl.toml": "[profile.default]\nsource = \"IGNORED\"\n",
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.source, Some("SOURCE".into()));
+  }
+
+  #[test]
+  fn profile_unknown() {
+    test_env! {
+      args: [
+        "--profile",
+        "bar",
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+      ],
+      tree: {
+        foo: "",
+        "imdl.toml": "[profile.default]\nsource = \"SOURCE\"\n",
+      },
+      matches: Err(Error::ConfigProfileUnknown { .. }),
+    };
+  }
+
+  #[test]
+  fn like() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--like",
+        "other.torrent",
+      ],
+      tree: {
+        foo: "",
+      }
+    };
+
+    let other = Metainfo::test_value_single();
+
+    other.dump(env.resolve("other.torrent").unwrap()).unwrap();
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.announce, other.announce);
+    assert_eq!(metainfo.announce_list, other.announce_list);
+    assert_eq!(metainfo.nodes, other.nodes);
+    assert_eq!(metainfo.comment, other.comment);
+    assert_eq!(metainfo.info.source, other.info.source);
+    assert_eq!(metainfo.info.private, other.info.private);
+    assert_eq!(metainfo.info.update_url, other.info.update_url);
+    assert_eq!(metainfo.info.name, "foo");
+    assert_ne!(metainfo.creation_date, other.creation_date);
+  }
+
+  #[test]
+  fn like_piece_length() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--like",
+        "other.torrent",
+      ],
+      tree: {
+        foo: "",
+      }
+    };
+
+    let mut other = Metainfo::test_value_single();
+    other.info.piece_length = Bytes::from(64 * 1024_u64);
+
+    other.dump(env.resolve("other.torrent").unwrap()).unwrap();
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.piece_length, Bytes::from(64 * 1024_u64));
+  }
+
+  #[test]
+  fn like_piece_length_flag_takes_precedence() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--like",
+        "other.torrent",
+        "--piece-length",
+        "32kib",
+      ],
+      tree: {
+        foo: "",
+      }
+    };
+
+    let mut other = Metainfo::test_value_single();
+    other.info.piece_length = Bytes::from(64 * 1024_u64);
+
+    other.dump(env.resolve("other.torrent").unwrap()).unwrap();
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.piece_length, Bytes::from(32 * 1024_u64));
+  }
+
+  #[test]
+  fn like_flags_take_precedence() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--like",
+        "other.torrent",
+        "--source",
+        "FLAG",
+        "--announce-tier",
+        "udp://flag.example:1",
+      ],
+      tree: {
+        foo: "",
+      }
+    };
+
+    let other = Metainfo::test_value_single();
+
+    other.dump(env.resolve("other.torrent").unwrap()).unwrap();
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.announce, other.announce);
+    assert_eq!(
+      metainfo.announce_list,
+      Some(vec![vec!["udp://flag.example:1".into()]])
+    );
+    assert_eq!(metainfo.info.source, Some("FLAG".into()));
+    assert_eq!(metainfo.comment, other.comment);
+  }
+
+  #[test]
+  fn like_unset() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--like",
+        "other.torrent",
+        "--allow",
+        "small-piece-length",
+      ],
+      tree: {
+        foo: "",
+      }
+    };
+
+    Metainfo::test_value_single_unset()
+      .dump(env.resolve("other.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.announce, None);
+    assert_eq!(metainfo.announce_list, None);
+    assert_eq!(metainfo.nodes, None);
+    assert_eq!(metainfo.comment, None);
+    assert_eq!(metainfo.info.source, None);
+    assert_eq!(metainfo.info.private, None);
+    assert_eq!(metainfo.info.piece_length, Bytes(1024));
+  }
+
+  #[test]
+  fn like_missing() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--like",
+        "other.torrent",
+      ],
+      tree: {
+        foo: "",
+      },
+      matches: Err(Error::Filesystem { .. }),
+    };
+  }
+
+  #[test]
+  fn reproducible() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+          b: "xyz",
+        },
+      },
+    };
+    env.assert_ok();
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.creation_date, None);
+    assert_eq!(
+      metainfo.created_by.unwrap(),
+      consts::CREATED_BY_REPRODUCIBLE
+    );
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["abcxyz"]));
+  }
+
+  #[test]
+  fn reproducible_source_date_epoch() {
+    let mut env = TestEnvBuilder::new()
+      .arg_slice(&[
+        "imdl",
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+      ])
+      .var("SOURCE_DATE_EPOCH", "1234")
+      .build();
+
+    env.write("foo", "abc");
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.creation_date, Some(1234));
+  }
+
+  #[test]
+  fn reproducible_output_identical() {
+    let torrent = || {
+      let mut env = test_env! {
+        args: [
+          "torrent",
+          "create",
+          "--input",
+          "foo",
+          "--announce",
+          "http://bar",
+          "--reproducible",
+        ],
+        tree: {
+          foo: {
+            a: "abc",
+            b: {
+              c: "xyz",
+            },
+          },
+        },
+      };
+      env.assert_ok();
+      fs::read(env.resolve("foo.torrent").unwrap()).unwrap()
+    };
+
+    assert_eq!(torrent(), torrent());
+  }
+
+  #[test]
+  fn reproducible_conflicts_with_sort_by() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+        "--sort-by",
+        "size",
+      ],
+      tree: {
+        foo: "",
+      },
+    };
+    assert_matches!(env.run(), Err(Error::Clap { .. }));
+  }
+
+  #[test]
+  fn reproducible_normalizes_name() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+        "--name",
+        "e\u{301}",
+        "--output",
+        "foo.torrent",
+      ],
+      tree: {
+        foo: {},
+      },
+    };
+    env.assert_ok();
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.name, "\u{e9}");
+    Ok(())
+  }
+
+  #[test]
+  #[cfg(target_os = "linux")]
+  fn reproducible_normalized_directory_names_collide() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+        "--piece-length",
+        "16KiB",
+      ],
+      tree: {
+        foo: {},
+      },
+    };
+    env.create_dir("foo/a\u{308}");
+    env.create_dir("foo/\u{e4}");
+    fs::write(env.resolve("foo/a\u{308}/x")?, "abc").unwrap();
+    fs::write(env.resolve("foo/a\u{308}/y")?, "abc").unwrap();
+    fs::write(env.resolve("foo/\u{e4}/x")?, "xyz").unwrap();
+    assert_matches!(
+      env.run(),
+      Err(Error::FilePathDuplicate { path })
+        if path == FilePath::from_components(&["\u{e4}", "x"])
+    );
+    Ok(())
+  }
+
+  #[test]
+  #[cfg(target_os = "linux")]
+  fn reproducible_normalized_names_collide() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+      ],
+      tree: {
+        foo: {},
+      },
+    };
+    fs::write(env.resolve("foo/a\u{308}")?, "abc").unwrap();
+    fs::write(env.resolve("foo/\u{e4}")?, "xyz").unwrap();
+    assert_matches!(
+      env.run(),
+      Err(Error::FilePathDuplicate { path }) if path == FilePath::from_components(&["\u{e4}"])
+    );
+    Ok(())
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn reproducible_symlink() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+      ],
+      tree: {},
+    };
+    populate_symlinks(&env)?;
+    assert_matches!(env.run(), Err(Error::ReproducibleSymlink { .. }));
+    Ok(())
+  }
+
+  #[test]
+  fn reproducible_skips_dotfiles() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--reproducible",
+      ],
+      tree: {
+        foo: {
+          ".bar": "abc",
+          baz: "xyz",
+        },
+      },
+    };
+    env.assert_ok();
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["xyz"]));
+  }
+
+  #[test]
+  fn source_date_epoch() {
+    assert_eq!(Create::source_date_epoch(None).unwrap(), None);
+    assert_eq!(
+      Create::source_date_epoch(Some(OsStr::new("1234"))).unwrap(),
+      Some(1234)
+    );
+    assert_matches!(
+      Create::source_date_epoch(Some(OsStr::new("foo"))),
+      Err(Error::SourceDateEpochParse { text, .. }) if text == "foo"
+    );
+  }
+
+  #[test]
+  fn windows_incompatible_path() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+      ],
+      tree: {
+        foo: {
+          "bar.": "",
+        },
+      },
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(error @ Error::PathLint { .. })
+      if error.lint() == Some(Lint::WindowsIncompatiblePath)
+    );
+  }
+
+  #[test]
+  fn windows_incompatible_path_allowed() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--allow",
+        "windows-incompatible-path",
+      ],
+      tree: {
+        foo: {
+          "bar.": "",
+        },
+      },
+    };
+
+    env.assert_ok();
+  }
+
+  #[test]
+  #[cfg(target_os = "linux")]
+  fn case_insensitive_collision() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+      ],
+      tree: {
+        foo: {
+          bar: "",
+          BAR: "",
+        },
+      },
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(error @ Error::PathLint { .. })
+      if error.lint() == Some(Lint::CaseInsensitiveCollision)
+    );
+  }
+
+  fn from_original(env: &Env) -> Metainfo {
+    let mut metainfo = Metainfo::test_value_single();
+    metainfo.info.name = "foo".into();
+    metainfo.info.source = Some("OLD".into());
+    metainfo.info.pieces = PieceList::from_pieces(&["abc"]);
+    metainfo.info.mode = Mode::Single {
+      length: Bytes(3),
+      md5sum: None,
+    };
+    metainfo
+      .dump(env.resolve("original.torrent").unwrap())
+      .unwrap();
+    metainfo
+  }
+
+  #[test]
+  fn from_copies_content() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--from",
+        "original.torrent",
+        "--source",
+        "NEW",
+        "--announce",
+        "https://new.example/announce",
+      ],
+      tree: {},
+    };
+
+    let original = from_original(&env);
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.name, original.info.name);
+    assert_eq!(metainfo.info.piece_length, original.info.piece_length);
+    assert_eq!(metainfo.info.mode, original.info.mode);
+    assert_eq!(metainfo.info.pieces, original.info.pieces);
+    assert_eq!(metainfo.info.source, Some("NEW".into()));
+    assert_eq!(metainfo.info.private, None);
+    assert_eq!(
+      metainfo.announce,
+      Some("https://new.example/announce".into())
+    );
+    assert_eq!(metainfo.announce_list, None);
+    assert_ne!(metainfo.infohash().unwrap(), original.infohash().unwrap());
+  }
+
+  #[test]
+  fn from_checks_sampled_pieces() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--from",
+        "original.torrent",
+        "--source",
+        "NEW",
+        "foo",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    let original = from_original(&env);
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+    assert_eq!(metainfo.info.pieces, original.info.pieces);
+  }
+
+  #[test]
+  fn from_sample_mismatch() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--from",
+        "original.torrent",
+        "--sample",
+        "1",
+        "foo",
+      ],
+      tree: {
+        foo: "abd",
+      },
+    };
+
+    from_original(&env);
+
+    assert_matches!(
+      env.run(),
+      Err(Error::SampleMismatch {
+        bad: 1,
+        sampled: 1,
+        ..
+      })
+    );
+
+    assert!(!env.resolve("foo.torrent").unwrap().exists());
+  }
+
+  #[test]
+  fn from_conflicts_with_piece_length() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--from",
+        "original.torrent",
+        "--piece-length",
+        "16KiB",
+      ],
+      tree: {},
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn sample_requires_from() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--sample",
+        "1",
+        "foo",
+      ],
+      tree: {
+        foo: "",
+      },
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn manifest_json() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--md5",
+        "--sha256",
+        "--manifest",
+        "foo.json",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+          b: "",
+        },
+      },
+    };
+
+    env.assert_ok();
+
+    let json: serde_json::Value = serde_json::from_str(&env.read_to_string("foo.json")).unwrap();
+
+    assert_eq!(
+      json,
+      serde_json::json!([
+        {
+          "path": "a",
+          "length": 3,
+          "md5": "900150983cd24fb0d6963f7d28e17f72",
+          "sha256": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        },
+        {
+          "path": "b",
+          "length": 0,
+          "md5": "d41d8cd98f00b204e9800998ecf8427e",
+          "sha256": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        },
+      ])
+    );
+  }
+
+  #[test]
+  fn manifest_sfv() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--manifest",
+        "foo.sfv",
+        "--manifest-format",
+        "sfv",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.read_to_string("foo.sfv"), "foo 352441C2\n");
+  }
+
+  #[test]
+  fn manifest_md5sum_without_md5() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--manifest",
+        "foo.md5",
+        "--manifest-format",
+        "md5sum",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+        },
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.read_to_string("foo.md5"),
+      "900150983cd24fb0d6963f7d28e17f72  a\n"
+    );
+
+    let metainfo = env.load_metainfo("foo.torrent");
+
+    assert_eq!(
+      metainfo.info.mode,
+      Mode::Multiple {
+        files: vec![FileInfo {
+          length: Bytes(3),
+          path: FilePath::from_components(&["a"]),
+          md5sum: None,
+          extra: BTreeMap::new(),
+        }],
+      }
+    );
+  }
+
+  #[test]
+  fn manifest_stdin() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "-",
+        "--name",
+        "foo",
+        "--output",
+        "foo.torrent",
+        "--announce",
+        "http://bar",
+        "--manifest",
+        "-",
+        "--manifest-format",
+        "sha256sum",
+      ],
+      input: "abc",
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.out(),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  foo\n"
+    );
+  }
+
+  #[test]
+  fn sha256_requires_manifest() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--sha256",
+      ],
+      tree: {
+        foo: "",
+      },
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn file_list() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--file-list",
+        "list",
+        "--name",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--md5",
+      ],
+      tree: {
+        list: "b/c\tmnt/x\n# comment\na\tother/y\n",
+        mnt: {
+          x: "xx",
+        },
+        other: {
+          y: "yyy",
+        },
+      },
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+
+    assert_eq!(metainfo.info.name, "foo");
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["xxyyy"]));
+    assert_eq!(
+      metainfo.info.mode,
+      Mode::Multiple {
+        files: vec![
+          FileInfo {
+            length: Bytes(2),
+            path: FilePath::from_components(&["b", "c"]),
+            md5sum: Some(Md5Digest::from_data("xx")),
+            extra: BTreeMap::new(),
+          },
+          FileInfo {
+            length: Bytes(3),
+            path: FilePath::from_components(&["a"]),
+            md5sum: Some(Md5Digest::from_data("yyy")),
+            extra: BTreeMap::new(),
+          },
+        ],
+      }
+    );
+  }
+
+  #[test]
+  fn file_list_stdin() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--file-list",
+        "-",
+        "--name",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--output",
+        "bar.torrent",
+      ],
+      input: "a\tx\n",
+      tree: {
+        x: "abc",
+      },
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("bar.torrent");
+
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["abc"]));
+  }
+
+  #[test]
+  fn file_list_requires_name() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--file-list",
+        "list",
+      ],
+      tree: {
+        list: "a\tx\n",
+      },
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn file_list_conflicts_with_input() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--file-list",
+        "list",
+        "--name",
+        "foo",
+        "x",
+      ],
+      tree: {
+        list: "a\tx\n",
+        x: "",
+      },
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn file_list_source_missing() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--file-list",
+        "list",
+        "--name",
+        "foo",
+      ],
+      tree: {
+        list: "a\tx\n",
+      },
+      matches: Err(Error::Filesystem { .. }),
+    };
+  }
+
+  #[test]
+  fn tar() {
+    let archive = TarReader::test_archive(&[
+      ("./dir/", b'5', b""),
+      ("./dir/b", b'0', b"bc"),
+      ("./dir/link", b'2', b""),
+      ("./a", b'0', b"a"),
+    ]);
+
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "-",
+        "--tar",
+        "--name",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--output",
+        "foo.torrent",
+        "--md5",
+      ],
+      input: archive,
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["bca"]));
+
+    assert_eq!(
+      metainfo.info.mode,
+      Mode::Multiple {
+        files: vec![
+          FileInfo {
+            path: FilePath::from_components(&["dir", "b"]),
+            length: Bytes(2),
+            md5sum: Some(Md5Digest::from_data("bc")),
+            extra: BTreeMap::new(),
+          },
+          FileInfo {
+            path: FilePath::from_components(&["a"]),
+            length: Bytes(1),
+            md5sum: Some(Md5Digest::from_data("a")),
+            extra: BTreeMap::new(),
+          },
+        ],
+      }
+    );
+  }
+
+  #[test]
+  fn tar_duplicate_path() {
+    let archive = TarReader::test_archive(&[("a", b'0', b"a"), ("./a", b'0', b"b")]);
+
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "-",
+        "--tar",
+        "--name",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--output",
+        "foo.torrent",
+      ],
+      input: archive,
+      tree: {},
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(Error::FilePathDuplicate { path }) if path == FilePath::from_components(&["a"])
+    );
+  }
+
+  #[test]
+  fn tar_invalid() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "-",
+        "--tar",
+        "--name",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--output",
+        "foo.torrent",
+      ],
+      input: "hello",
+      tree: {},
+      matches: Err(Error::Stdin { .. }),
+    };
+  }
+
+  #[test]
+  fn tar_requires_stdin() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--tar",
+        "--announce",
+        "http://bar",
+      ],
+      tree: {
+        foo: "",
+      },
+      matches: Err(Error::TarInput),
+    };
+  }
+
+  fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+      .arg("-C")
+      .arg(repo)
+      .args(&[
+        "-c",
+        "user.name=imdl",
+        "-c",
+        "user.email=imdl@example.com",
+        "-c",
+        "commit.gpgsign=false",
+        "-c",
+        "init.defaultBranch=master",
+      ])
+      .args(args)
+      .output()
+      .unwrap();
+
+    assert!(output.status.success(), "git {:?} failed", args);
+
+    String::from_utf8(output.stdout)
+      .unwrap()
+      .trim_end()
+      .to_owned()
+  }
+
+  fn git_env(args: &[&str]) -> TestEnv {
+    let tempdir = temptree! {
+      repo: {
+        ".gitignore": "ignored\n",
+        a: "old",
+        b: {
+          c: "c",
+        },
+      },
+    };
+
+    let repo = tempdir.path().join("repo");
+
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "first"]);
+
+    fs::write(repo.join("a"), "new").unwrap();
+    git(&repo, &["commit", "-q", "-a", "-m", "second"]);
+
+    fs::write(repo.join("a"), "modified").unwrap();
+    fs::write(repo.join("ignored"), "ignored").unwrap();
+    fs::write(repo.join("untracked"), "untracked").unwrap();
+
+    TestEnvBuilder::new()
+      .arg("imdl")
+      .arg_slice(args)
+      .tempdir(tempdir)
+      .build()
+  }
+
+  #[test]
+  fn git_rev() {
+    let mut env = git_env(&[
+      "torrent",
+      "create",
+      "--input",
+      "repo",
+      "--git-rev",
+      "HEAD~1",
+      "--announce",
+      "http://bar",
+      "--md5",
+    ]);
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("repo.torrent");
+
+    assert_eq!(metainfo.info.name, "repo");
+    assert_eq!(metainfo.comment, None);
+    assert_eq!(
+      metainfo.info.pieces,
+      PieceList::from_pieces(&["ignored\noldc"])
+    );
+
+    assert_eq!(
+      metainfo.info.mode,
+      Mode::Multiple {
+        files: vec![
+          FileInfo {
+            path: FilePath::from_components(&[".gitignore"]),
+            length: Bytes(8),
+            md5sum: Some(Md5Digest::from_data("ignored\n")),
+            extra: BTreeMap::new(),
+          },
+          FileInfo {
+            path: FilePath::from_components(&["a"]),
+            length: Bytes(3),
+            md5sum: Some(Md5Digest::from_data("old")),
+            extra: BTreeMap::new(),
+          },
+          FileInfo {
+            path: FilePath::from_components(&["b", "c"]),
+            length: Bytes(1),
+            md5sum: Some(Md5Digest::from_data("c")),
+            extra: BTreeMap::new(),
+          },
+        ],
+      }
+    );
+  }
+
+  #[test]
+  fn git_rev_comment() {
+    let mut env = git_env(&[
+      "torrent",
+      "create",
+      "--input",
+      "repo/b",
+      "--git-rev",
+      "master",
+      "--git-comment",
+      "--announce",
+      "http://bar",
+      "--output",
+      "out.torrent",
+    ]);
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("out.torrent");
+
+    let commit = git(&env.resolve("repo").unwrap(), &["rev-parse", "HEAD"]);
+
+    assert_eq!(metainfo.info.name, "repo");
+    assert_eq!(metainfo.comment, Some(commit));
+    assert_eq!(
+      metainfo.info.pieces,
+      PieceList::from_pieces(&["ignored\nnewc"])
+    );
+  }
+
+  #[test]
+  fn git_rev_unknown() {
+    let mut env = git_env(&[
+      "torrent",
+      "create",
+      "--input",
+      "repo",
+      "--git-rev",
+      "nope",
+      "--announce",
+      "http://bar",
+    ]);
+
+    let repo = env.resolve("repo").unwrap();
+
+    assert_matches!(
+      env.run(),
+      Err(Error::GitRevision { rev, repo: path }) if rev == "nope" && path == repo
+    );
+  }
+
+  #[test]
+  fn git_rev_requires_path() {
+    test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "-",
+        "--git-rev",
+        "HEAD",
+        "--name",
+        "foo",
+        "--output",
+        "foo.torrent",
+        "--announce",
+        "http://bar",
+      ],
+      tree: {},
+      matches: Err(Error::GitInput),
+    };
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn streamed_path_lints_are_checked_before_later_files_are_found() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--piece-length",
+        "16KiB",
+        "--follow-symlinks",
+      ],
+      tree: {
+        foo: {
+          "A": "a",
+          a: "b",
+        },
+      },
+    };
+
+    // Searching fails when the dangling symlink is reached, after the
+    // colliding paths have been found.
+    std::os::unix::fs::symlink(
+      env.resolve("missing").unwrap(),
+      env.resolve("foo/z").unwrap(),
+    )
+    .unwrap();
+
+    assert_matches!(
+      env.run(),
+      Err(Error::PathLint {
+        violation: PathViolation::CaseInsensitiveCollision { first, second },
+      }) if first == FilePath::from_components(&["A"])
+        && second == FilePath::from_components(&["a"])
+    );
+  }
+
+  #[test]
+  fn streamed_with_piece_length() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--piece-length",
+        "16KiB",
+        "--md5",
+      ],
+      tree: {
+        foo: {
+          b: {
+            c: "c",
+          },
+          a: "a",
+          "b-": "d",
+        },
+      },
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+
+    assert_eq!(metainfo.info.pieces, PieceList::from_pieces(&["acd"]));
+
+    assert_eq!(
+      metainfo.info.mode,
+      Mode::Multiple {
+        files: vec![
+          FileInfo {
+            path: FilePath::from_components(&["a"]),
+            length: Bytes(1),
+            md5sum: Some(Md5Digest::from_data("a")),
+            extra: BTreeMap::new(),
+          },
+          FileInfo {
+            path: FilePath::from_components(&["b", "c"]),
+            length: Bytes(1),
+            md5sum: Some(Md5Digest::from_data("c")),
+            extra: BTreeMap::new(),
+          },
+          FileInfo {
+            path: FilePath::from_components(&["b-"]),
+            length: Bytes(1),
+            md5sum: Some(Md5Digest::from_data("d")),
+            extra: BTreeMap::new(),
+          },
+        ],
+      }
+    );
+  }
+
+  #[test]
+  fn sparse() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--piece-length",
+        "64KiB",
+        "--md5",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+        },
+      },
+    };
+
+    let mut file = File::create(env.resolve("foo/b").unwrap()).unwrap();
+    file.set_len(4 * 1024 * 1024).unwrap();
+    file.seek(SeekFrom::Start(1024 * 1024 + 7)).unwrap();
+    file.write_all(b"data").unwrap();
+    drop(file);
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("foo.torrent");
+
+    let mut contents = b"abc".to_vec();
+    contents.extend(fs::read(env.resolve("foo/b").unwrap()).unwrap());
+
+    assert_eq!(
+      metainfo.info.pieces,
+      PieceList::from_pieces(contents.chunks(64 * 1024))
+    );
+
+    match metainfo.info.mode {
+      Mode::Multiple { files } => {
+        assert_eq!(files[1].md5sum, Some(Md5Digest::from_data(&contents[3..])))
+      }
+      Mode::Single { .. } => panic!("Expected multi-file torrent"),
+    }
+  }
+
+  #[test]
+  fn io_backends() {
+    let contents = (0..100_000)
+      .map(|i| u8::try_from(i % 251).unwrap())
+      .collect::<Vec<u8>>();
+
+    let mut expected = None;
+
+    for backend in IoBackend::VARIANTS {
+      let mut env = test_env! {
+        args: [
+          "torrent",
+          "create",
+          "--input",
+          "foo",
+          "--announce",
+          "http://bar",
+          "--piece-length",
+          "16KiB",
+          "--md5",
+          "--io",
+          backend,
+          "--read-size",
+          "4KiB",
+        ],
+        tree: {
+          foo: {
+            a: "abc",
+          },
+        },
+      };
+
+      fs::write(env.resolve("foo/b").unwrap(), &contents).unwrap();
+
+      env.assert_ok();
+
+      let info = env.load_metainfo("foo.torrent").info;
+
+      match &expected {
+        Some(expected) => assert_eq!(&info, expected, "{} backend", backend),
+        None => {
+          let mut data = b"abc".to_vec();
+          data.extend(&contents);
+          assert_eq!(info.pieces, PieceList::from_pieces(data.chunks(16 * 1024)));
+          expected = Some(info);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn read_size_unaligned_with_direct_io() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--io",
+        "direct",
+        "--read-size",
+        "1000",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(Error::ReadSizeUnaligned { bytes }) if bytes == Bytes(1000)
+    );
+  }
+
+  #[test]
+  fn read_size_zero() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "http://bar",
+        "--read-size",
+        "0",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    assert_matches!(env.run(), Err(Error::ReadSizeZero));
+  }
 }
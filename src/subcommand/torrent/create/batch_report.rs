@@ -0,0 +1,239 @@
+use crate::common::*;
+
+pub(crate) struct BatchReport {
+  entries: Vec<Entry>,
+}
+
+struct Entry {
+  name: String,
+  outcome: Outcome,
+  torrent: PathBuf,
+}
+
+#[derive(PartialEq)]
+enum Outcome {
+  Created(Infohash),
+  Failed,
+  Skipped,
+}
+
+impl Entry {
+  fn status(&self) -> &'static str {
+    match self.outcome {
+      Outcome::Created(_) => "created",
+      Outcome::Failed => "failed",
+      Outcome::Skipped => "skipped",
+    }
+  }
+
+  fn infohash(&self) -> String {
+    match self.outcome {
+      Outcome::Created(infohash) => infohash.to_string(),
+      Outcome::Failed | Outcome::Skipped => "-".to_owned(),
+    }
+  }
+}
+
+impl BatchReport {
+  pub(crate) fn new() -> Self {
+    Self {
+      entries: Vec::new(),
+    }
+  }
+
+  pub(crate) fn created(&mut self, name: String, infohash: Infohash, torrent: PathBuf) {
+    self.entries.push(Entry {
+      outcome: Outcome::Created(infohash),
+      name,
+      torrent,
+    });
+  }
+
+  pub(crate) fn failed(&mut self, name: String, torrent: PathBuf) {
+    self.entries.push(Entry {
+      outcome: Outcome::Failed,
+      name,
+      torrent,
+    });
+  }
+
+  pub(crate) fn skipped(&mut self, name: String, torrent: PathBuf) {
+    self.entries.push(Entry {
+      outcome: Outcome::Skipped,
+      name,
+      torrent,
+    });
+  }
+
+  /// Number of torrents that could not be created.
+  pub(crate) fn failures(&self) -> usize {
+    self
+      .entries
+      .iter()
+      .filter(|entry| entry.outcome == Outcome::Failed)
+      .count()
+  }
+
+  pub(crate) fn write(&self, env: &mut Env) -> Result<()> {
+    if env.out().is_term() {
+      let style = env.out().style();
+      self
+        .write_human_readable(env.out_mut(), style)
+        .context(error::Stdout)?;
+    } else {
+      self
+        .write_tab_delimited(env.out_mut())
+        .context(error::Stdout)?;
+    }
+
+    Ok(())
+  }
+
+  fn write_human_readable(&self, out: &mut dyn Write, style: Style) -> io::Result<()> {
+    const HEADER: [&str; 4] = ["Name", "Status", "Info Hash", "Torrent"];
+
+    let rows = self
+      .entries
+      .iter()
+      .map(|entry| {
+        [
+          entry.name.clone(),
+          entry.status().to_owned(),
+          entry.infohash(),
+          entry.torrent.display().to_string(),
+        ]
+      })
+      .collect::<Vec<[String; 4]>>();
+
+    let mut widths = [0; 4];
+    for (i, width) in widths.iter_mut().enumerate() {
+      *width = iter::once(HEADER[i])
+        .chain(rows.iter().map(|row| row[i].as_str()))
+        .map(UnicodeWidthStr::width)
+        .max()
+        .unwrap_or(0);
+    }
+
+    for (i, name) in HEADER.iter().enumerate() {
+      if i > 0 {
+        write!(out, "  ")?;
+      }
+      write!(out, "{}", style.blue().paint(*name))?;
+      if i < HEADER.len() - 1 {
+        write!(
+          out,
+          "{:width$}",
+          "",
+          width = widths[i] - UnicodeWidthStr::width(*name)
+        )?;
+      }
+    }
+    writeln!(out)?;
+
+    for row in &rows {
+      for (i, value) in row.iter().enumerate() {
+        if i > 0 {
+          write!(out, "  ")?;
+        }
+        if i < row.len() - 1 {
+          write!(
+            out,
+            "{}{:width$}",
+            value,
+            "",
+            width = widths[i] - UnicodeWidthStr::width(value.as_str())
+          )?;
+        } else {
+          write!(out, "{}", value)?;
+        }
+      }
+      writeln!(out)?;
+    }
+
+    let created = self
+      .entries
+      .iter()
+      .filter(|entry| matches!(entry.outcome, Outcome::Created(_)))
+      .count();
+
+    let failed = self.failures();
+
+    writeln!(
+      out,
+      "{} created, {} skipped, {} failed",
+      created,
+      self.entries.len() - created - failed,
+      failed,
+    )?;
+
+    Ok(())
+  }
+
+  fn write_tab_delimited(&self, out: &mut dyn Write) -> io::Result<()> {
+    for entry in &self.entries {
+      writeln!(
+        out,
+        "{}\t{}\t{}\t{}",
+        entry.name,
+        entry.status(),
+        entry.infohash(),
+        entry.torrent.display()
+      )?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn report() -> BatchReport {
+    let mut report = BatchReport::new();
+    report.created(
+      "foo".into(),
+      Infohash::from_bencoded_info_dict(b"de"),
+      "out/foo.torrent".into(),
+    );
+    report.skipped("barbaz".into(), "out/barbaz.torrent".into());
+    report.failed("qux".into(), "out/qux.torrent".into());
+    report
+  }
+
+  #[test]
+  fn tab_delimited() {
+    let mut cursor = Cursor::new(Vec::new());
+    report().write_tab_delimited(&mut cursor).unwrap();
+    let have = String::from_utf8(cursor.into_inner()).unwrap();
+    assert_eq!(
+      have,
+      concat!(
+        "foo\tcreated\t600ccd1b71569232d01d110bc63e906beab04d8c\tout/foo.torrent\n",
+        "barbaz\tskipped\t-\tout/barbaz.torrent\n",
+        "qux\tfailed\t-\tout/qux.torrent\n",
+      )
+    );
+  }
+
+  #[test]
+  fn human_readable() {
+    let mut cursor = Cursor::new(Vec::new());
+    report()
+      .write_human_readable(&mut cursor, Style::inactive())
+      .unwrap();
+    let have = String::from_utf8(cursor.into_inner()).unwrap();
+    assert_eq!(
+      have,
+      "\
+Name    Status   Info Hash                                 Torrent
+foo     created  600ccd1b71569232d01d110bc63e906beab04d8c  out/foo.torrent
+barbaz  skipped  -                                         out/barbaz.torrent
+qux     failed   -                                         out/qux.torrent
+1 created, 1 skipped, 1 failed
+"
+    );
+  }
+}
@@ -4,6 +4,7 @@ use super::Create;
 
 pub(crate) struct CreateContent {
   pub(crate) files: Option<Files>,
+  pub(crate) git_tree: Option<GitTree>,
   pub(crate) piece_length: Bytes,
   pub(crate) progress_bar: ProgressBar,
   pub(crate) name: String,
@@ -13,6 +14,7 @@ pub(crate) struct CreateContent {
 impl CreateContent {
   pub(crate) fn from_create(create: &Create, input: &InputTarget, env: &mut Env) -> Result<Self> {
     match input {
+      InputTarget::Path(_) if create.tar => Err(Error::TarInput),
       InputTarget::Path(path) => {
         let spinner = if env.err().is_styled_term() {
           let style = ProgressStyle::default_spinner()
@@ -24,26 +26,30 @@ impl CreateContent {
           None
         };
 
-        let files = Walker::new(&env.resolve(path)?)
+        let now = SystemTime::now();
+
+        let (files, skipped) = Walker::new(&env.resolve(path)?)
           .include_junk(create.include_junk)
           .include_hidden(create.include_hidden)
           .ignore(create.ignore)
           .follow_symlinks(create.follow_symlinks)
+          .max_file_size(create.max_file_size)
+          .min_file_size(create.min_file_size)
+          .newer_than(create.newer_than.as_ref().map(|time| time.resolve(now)))
+          .older_than(create.older_than.as_ref().map(|time| time.resolve(now)))
+          .reproducible(create.reproducible)
           .sort_by(create.sort_by.clone())
           .globs(&create.globs)?
           .spinner(spinner)
-          .files()?;
-
-        let piece_length = create
-          .piece_length
-          .unwrap_or_else(|| PieceLengthPicker::from_content_size(files.total_size()));
+          .files(create.piece_length.is_some() && !create.verbose)?;
 
-        let style = ProgressStyle::default_bar()
-          .template(consts::PROGRESS_STYLE)
-          .tick_chars(consts::TICK_CHARS)
-          .progress_chars(consts::PROGRESS_CHARS);
+        if create.verbose {
+          for (path, reason) in skipped {
+            errln!(env, "Skipped `{}`: {}", path.display(), reason)?;
+          }
+        }
 
-        let progress_bar = ProgressBar::new(files.total_size().count()).with_style(style);
+        let (piece_length, progress_bar) = Self::piece_length(create, files.total_size())?;
 
         let resolved = env.resolve(path)?;
 
@@ -61,6 +67,8 @@ impl CreateContent {
             .to_owned(),
         };
 
+        let name = Self::normalize_name(create, name);
+
         let output = create
           .output
           .clone()
@@ -68,6 +76,7 @@ impl CreateContent {
 
         Ok(Self {
           files: Some(files),
+          git_tree: None,
           piece_length,
           progress_bar,
           name,
@@ -80,7 +89,7 @@ impl CreateContent {
         let piece_length = create.piece_length.unwrap_or(Bytes::kib() * 256);
 
         let style = ProgressStyle::default_bar()
-          .template("{spinner:.green} ⟪{elapsed_precise}⟫ {binary_bytes} ⟨{binary_bytes_per_sec}⟩")
+          .template(consts::PROGRESS_SPINNER_STYLE)
           .tick_chars(consts::TICK_CHARS);
 
         let progress_bar = ProgressBar::new_spinner().with_style(style);
@@ -90,6 +99,8 @@ impl CreateContent {
           .clone()
           .ok_or_else(|| Error::internal("Expected `--name` to be set when `--input -`."))?;
 
+        let name = Self::normalize_name(create, name);
+
         let output = create
           .output
           .clone()
@@ -97,6 +108,7 @@ impl CreateContent {
 
         Ok(Self {
           files,
+          git_tree: None,
           piece_length,
           progress_bar,
           name,
@@ -106,7 +118,115 @@ impl CreateContent {
     }
   }
 
-  fn torrent_path(input: &Path, name: &str) -> PathBuf {
+  pub(crate) fn from_file_list(create: &Create, list: &InputTarget, env: &mut Env) -> Result<Self> {
+    let input = env.read(list.clone())?;
+
+    let files = FileList::parse(&input)?.files(env)?;
+
+    let (piece_length, progress_bar) = Self::piece_length(create, files.total_size())?;
+
+    let name = create
+      .name
+      .clone()
+      .ok_or_else(|| Error::internal("Expected `--name` to be set when `--file-list` is given."))?;
+
+    let output = create
+      .output
+      .clone()
+      .unwrap_or_else(|| OutputTarget::Path(PathBuf::from(format!("{}.torrent", name))));
+
+    Ok(Self {
+      files: Some(files),
+      git_tree: None,
+      piece_length,
+      progress_bar,
+      name,
+      output,
+    })
+  }
+
+  pub(crate) fn from_git(
+    create: &Create,
+    input: &InputTarget,
+    rev: &str,
+    env: &mut Env,
+  ) -> Result<Self> {
+    let path = match input {
+      InputTarget::Path(path) => path,
+      InputTarget::Stdin => return Err(Error::GitInput),
+    };
+
+    let tree = GitTree::load(&env.resolve(path)?, rev, &create.sort_by)?;
+
+    if create.verbose {
+      for (path, reason) in tree.skipped() {
+        errln!(env, "Skipped `{}`: {}", path.display(), reason)?;
+      }
+    }
+
+    let (piece_length, progress_bar) = Self::piece_length(create, Some(tree.total_size()))?;
+
+    let name = match &create.name {
+      Some(name) => name.clone(),
+      None => tree.name().to_owned(),
+    };
+
+    let name = Self::normalize_name(create, name);
+
+    let output = create
+      .output
+      .clone()
+      .unwrap_or_else(|| OutputTarget::Path(Self::torrent_path(path, &name)));
+
+    Ok(Self {
+      files: None,
+      git_tree: Some(tree),
+      piece_length,
+      progress_bar,
+      name,
+      output,
+    })
+  }
+
+  /// The piece length, picked from `total_size` if not given, and a progress
+  /// bar for hashing content of `total_size`, if known.
+  fn piece_length(create: &Create, total_size: Option<Bytes>) -> Result<(Bytes, ProgressBar)> {
+    let piece_length = match (create.piece_length, total_size) {
+      (Some(piece_length), _) => piece_length,
+      (None, Some(total_size)) => PieceLengthPicker::from_content_size(total_size),
+      (None, None) => return Err(Error::internal("Expected total size of files to be known.")),
+    };
+
+    let progress_bar = match total_size {
+      Some(total_size) => {
+        let style = ProgressStyle::default_bar()
+          .template(consts::PROGRESS_STYLE)
+          .tick_chars(consts::TICK_CHARS)
+          .progress_chars(consts::PROGRESS_CHARS);
+
+        ProgressBar::new(total_size.count()).with_style(style)
+      }
+      None => {
+        let style = ProgressStyle::default_bar()
+          .template(consts::PROGRESS_SPINNER_STYLE)
+          .tick_chars(consts::TICK_CHARS);
+
+        ProgressBar::new_spinner().with_style(style)
+      }
+    };
+
+    Ok((piece_length, progress_bar))
+  }
+
+  fn normalize_name(create: &Create, name: String) -> String {
+    if create.reproducible {
+      name.nfc().collect()
+    } else {
+      name
+    }
+  }
+
+  pub(crate) fn torrent_path(input: &Path, name: &str) -> PathBuf {
     input
       .join("..")
       .lexiclean()
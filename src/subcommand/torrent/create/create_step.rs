@@ -3,6 +3,7 @@ use crate::common::*;
 #[derive(Clone, Copy)]
 pub(crate) enum CreateStep<'a> {
   Searching { input: &'a InputTarget },
+  Reading { list: &'a InputTarget },
   Hashing,
   Writing { output: &'a OutputTarget },
 }
@@ -10,7 +11,7 @@ pub(crate) enum CreateStep<'a> {
 impl<'a> Step for CreateStep<'a> {
   fn n(&self) -> usize {
     match self {
-      Self::Searching { .. } => 1,
+      Self::Searching { .. } | Self::Reading { .. } => 1,
       Self::Hashing => 2,
       Self::Writing { .. } => 3,
     }
@@ -18,7 +19,7 @@ impl<'a> Step for CreateStep<'a> {
 
   fn symbol(&self) -> &str {
     match self {
-      Self::Searching { .. } => "\u{1F9FF}",
+      Self::Searching { .. } | Self::Reading { .. } => "\u{1F9FF}",
       Self::Hashing => "\u{1F9EE}",
       Self::Writing { .. } => "\u{1F4BE}",
     }
@@ -35,6 +36,7 @@ impl<'a> Step for CreateStep<'a> {
         InputTarget::Stdin => write!(write, "Creating single-file torrent from standard input…"),
       },
 
+      Self::Reading { list } => write!(write, "Reading file list {}…", list),
       Self::Hashing => write!(write, "Hashing pieces…"),
       Self::Writing { output } => write!(write, "Writing metainfo to {}…", output),
     }
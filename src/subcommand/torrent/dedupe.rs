@@ -0,0 +1,495 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Find duplicate and overlapping torrents in a collection of .torrent files.")
+)]
+pub(crate) struct Dedupe {
+  #[structopt(
+    name = "DIR",
+    value_name = "DIR",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Search `DIR` for `.torrent` files. Files that cannot be read or are not valid torrent \
+            metainfo are skipped with a message on standard error."
+  )]
+  dir: PathBuf,
+  #[structopt(long = "json", short = "j", help = "Print report as JSON.")]
+  json: bool,
+}
+
+/// A torrent in the collection, along with the paths of all `.torrent` files
+/// with the same infohash.
+struct Torrent {
+  infohash: Infohash,
+  metainfo: Metainfo,
+  paths: Vec<String>,
+}
+
+#[derive(Serialize, Default)]
+struct Report {
+  duplicates: Vec<Duplicate>,
+  cross_seedable: Vec<CrossSeedable>,
+  overlaps: Vec<Overlap>,
+}
+
+/// `.torrent` files with the same infohash.
+#[derive(Serialize)]
+struct Duplicate {
+  infohash: String,
+  torrents: Vec<String>,
+}
+
+/// Torrents with the same content and layout, but different infohashes.
+#[derive(Serialize)]
+struct CrossSeedable {
+  torrents: [String; 2],
+  infohashes: [String; 2],
+  differences: Vec<&'static str>,
+}
+
+/// Torrents that share files, or have identical pieces but a different
+/// layout.
+#[derive(Serialize)]
+struct Overlap {
+  torrents: [String; 2],
+  files: [u64; 2],
+  shared_files: u64,
+  shared_bytes: u64,
+  identical_pieces: bool,
+}
+
+impl Dedupe {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let root = env.resolve(&self.dir)?;
+
+    let torrents = Self::load(env, &root)?;
+
+    let report = Self::report(&torrents);
+
+    if self.json {
+      let json = serde_json::to_string_pretty(&report).context(error::JsonSerialize)?;
+      outln!(env, "{}", json)?;
+      return Ok(());
+    }
+
+    if report.duplicates.is_empty()
+      && report.cross_seedable.is_empty()
+      && report.overlaps.is_empty()
+    {
+      outln!(env, "No duplicates or overlaps found.")?;
+      return Ok(());
+    }
+
+    if !report.duplicates.is_empty() {
+      outln!(env, "Duplicates:")?;
+      for duplicate in &report.duplicates {
+        outln!(
+          env,
+          "  {}: {}",
+          duplicate.infohash,
+          Self::list(&duplicate.torrents)
+        )?;
+      }
+    }
+
+    if !report.cross_seedable.is_empty() {
+      outln!(env, "Cross-seedable:")?;
+      for pair in &report.cross_seedable {
+        outln!(
+          env,
+          "  {}: differ in {}",
+          Self::list(&pair.torrents),
+          pair.differences.join(", ")
+        )?;
+      }
+    }
+
+    if !report.overlaps.is_empty() {
+      outln!(env, "Overlaps:")?;
+      for overlap in &report.overlaps {
+        out!(
+          env,
+          "  {}: {} of {} and {} files shared, {}",
+          Self::list(&overlap.torrents),
+          overlap.shared_files,
+          overlap.files[0],
+          overlap.files[1],
+          Bytes(overlap.shared_bytes),
+        )?;
+        if overlap.identical_pieces {
+          out!(env, ", identical pieces")?;
+        }
+        outln!(env)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn list(paths: &[String]) -> String {
+    paths
+      .iter()
+      .map(|path| format!("`{}`", path))
+      .collect::<Vec<String>>()
+      .join(", ")
+  }
+
+  fn load(env: &mut Env, root: &Path) -> Result<Vec<Torrent>, Error> {
+    let mut torrents: BTreeMap<Infohash, Torrent> = BTreeMap::new();
+
+    for result in WalkBuilder::new(root)
+      .standard_filters(false)
+      .hidden(true)
+      .sort_by_file_name(|a, b| a.cmp(b))
+      .build()
+    {
+      let entry = result?;
+      let path = entry.path();
+
+      if !path.is_file() || path.extension() != Some(OsStr::new("torrent")) {
+        continue;
+      }
+
+      let display = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string();
+
+      let loaded = env
+        .read(InputTarget::Path(path.to_owned()))
+        .and_then(|input| {
+          let metainfo = Metainfo::from_input(&input)?;
+          let infohash = Infohash::from_input(&input)?;
+          Ok((metainfo, infohash))
+        });
+
+      let (metainfo, infohash) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+          errln!(env, "Skipping `{}`: {}", display, error)?;
+          continue;
+        }
+      };
+
+      torrents
+        .entry(infohash)
+        .or_insert_with(|| Torrent {
+          infohash,
+          metainfo,
+          paths: Vec::new(),
+        })
+        .paths
+        .push(display);
+    }
+
+    let mut torrents = torrents
+      .into_iter()
+      .map(|(_, torrent)| torrent)
+      .collect::<Vec<Torrent>>();
+
+    torrents.sort_by(|a, b| a.paths[0].cmp(&b.paths[0]));
+
+    Ok(torrents)
+  }
+
+  fn report(torrents: &[Torrent]) -> Report {
+    let mut report = Report::default();
+
+    for torrent in torrents {
+      if torrent.paths.len() > 1 {
+        report.duplicates.push(Duplicate {
+          infohash: torrent.infohash.to_string(),
+          torrents: torrent.paths.clone(),
+        });
+      }
+    }
+
+    let mut by_pieces: BTreeMap<(Bytes, &PieceList), Vec<usize>> = BTreeMap::new();
+    for (i, torrent) in torrents.iter().enumerate() {
+      by_pieces
+        .entry((
+          torrent.metainfo.info.piece_length,
+          &torrent.metainfo.info.pieces,
+        ))
+        .or_default()
+        .push(i);
+    }
+
+    let mut identical_pieces = BTreeSet::new();
+    for group in by_pieces.values() {
+      for (n, a) in group.iter().enumerate() {
+        for b in &group[n + 1..] {
+          identical_pieces.insert((*a, *b));
+        }
+      }
+    }
+
+    let files = torrents
+      .iter()
+      .map(|torrent| Self::files(&torrent.metainfo))
+      .collect::<Vec<Vec<(FilePath, Bytes)>>>();
+
+    let mut by_file: BTreeMap<&(FilePath, Bytes), Vec<usize>> = BTreeMap::new();
+    for (i, files) in files.iter().enumerate() {
+      for file in files {
+        if file.1 > Bytes(0) {
+          by_file.entry(file).or_default().push(i);
+        }
+      }
+    }
+
+    let mut shared: BTreeMap<(usize, usize), (u64, u64)> = BTreeMap::new();
+    for ((_, length), group) in &by_file {
+      for (n, a) in group.iter().enumerate() {
+        for b in &group[n + 1..] {
+          let entry = shared.entry((*a, *b)).or_default();
+          entry.0 += 1;
+          entry.1 += length.count();
+        }
+      }
+    }
+
+    let same_layout = |a: &Torrent, b: &Torrent| {
+      a.metainfo.info.name == b.metainfo.info.name && a.metainfo.info.mode == b.metainfo.info.mode
+    };
+
+    let mut overlaps = shared
+      .keys()
+      .filter(|(a, b)| a != b)
+      .cloned()
+      .collect::<BTreeSet<(usize, usize)>>();
+
+    for (a, b) in &identical_pieces {
+      let (ta, tb) = (&torrents[*a], &torrents[*b]);
+
+      if same_layout(ta, tb) {
+        report.cross_seedable.push(CrossSeedable {
+          torrents: [ta.paths[0].clone(), tb.paths[0].clone()],
+          infohashes: [ta.infohash.to_string(), tb.infohash.to_string()],
+          differences: Self::differences(&ta.metainfo, &tb.metainfo),
+        });
+        overlaps.remove(&(*a, *b));
+      } else {
+        overlaps.insert((*a, *b));
+      }
+    }
+
+    for (a, b) in overlaps {
+      let (shared_files, shared_bytes) = shared.get(&(a, b)).cloned().unwrap_or((0, 0));
+
+      report.overlaps.push(Overlap {
+        torrents: [torrents[a].paths[0].clone(), torrents[b].paths[0].clone()],
+        files: [files[a].len().into_u64(), files[b].len().into_u64()],
+        shared_files,
+        shared_bytes,
+        identical_pieces: identical_pieces.contains(&(a, b)),
+      });
+    }
+
+    report
+      .overlaps
+      .sort_by_key(|overlap| Reverse(overlap.shared_bytes));
+
+    report
+  }
+
+  /// Paths and lengths of the files in `metainfo`. The path of the file in a
+  /// single-file torrent is the torrent's name.
+  fn files(metainfo: &Metainfo) -> Vec<(FilePath, Bytes)> {
+    match &metainfo.info.mode {
+      Mode::Single { length, .. } => {
+        vec![(FilePath::from_component(&metainfo.info.name), *length)]
+      }
+      Mode::Multiple { files } => files
+        .iter()
+        .map(|file| (file.path.clone(), file.length))
+        .collect(),
+    }
+  }
+
+  /// Names of the fields that differ between `a` and `b`, which have the same
+  /// content and layout.
+  fn differences(a: &Metainfo, b: &Metainfo) -> Vec<&'static str> {
+    let trackers = |metainfo: &Metainfo| {
+      iter::once(&metainfo.announce)
+        .flatten()
+        .chain(metainfo.announce_list.iter().flatten().flatten())
+        .cloned()
+        .collect::<BTreeSet<String>>()
+    };
+
+    let mut differences = Vec::new();
+
+    if trackers(a) != trackers(b) {
+      differences.push("announce");
+    }
+
+    if a.info.private.unwrap_or(false) != b.info.private.unwrap_or(false) {
+      differences.push("private");
+    }
+
+    if a.info.source != b.info.source {
+      differences.push("source");
+    }
+
+    if a.info.update_url != b.info.update_url || a.info.extra != b.info.extra {
+      differences.push("other info keys");
+    }
+
+    differences
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn populate(env: &Env) {
+    let single = Metainfo::test_value_single();
+    single.dump(env.resolve("dir/a.torrent").unwrap()).unwrap();
+    single.dump(env.resolve("dir/b.torrent").unwrap()).unwrap();
+
+    let mut cross = Metainfo::test_value_single();
+    cross.announce = Some("https://other.example/announce".into());
+    cross.info.source = Some("OTHER".into());
+    cross.dump(env.resolve("dir/c.torrent").unwrap()).unwrap();
+
+    let mut multiple = Metainfo::test_value_multiple();
+    multiple.info.pieces = PieceList::from_pieces(&["xyz", "xyz", "xyz"]);
+    multiple.info.mode = Mode::Multiple {
+      files: vec![
+        FileInfo {
+          length: Bytes(32 * 1024),
+          path: FilePath::from_components(&["NAME"]),
+          md5sum: None,
+          extra: BTreeMap::new(),
+        },
+        FileInfo {
+          length: Bytes(16 * 1024),
+          path: FilePath::from_components(&["x"]),
+          md5sum: None,
+          extra: BTreeMap::new(),
+        },
+      ],
+    };
+    multiple
+      .dump(env.resolve("dir/d.torrent").unwrap())
+      .unwrap();
+
+    let mut renamed = Metainfo::test_value_single();
+    renamed.info.name = "RENAMED".into();
+    renamed.dump(env.resolve("dir/e.torrent").unwrap()).unwrap();
+  }
+
+  #[test]
+  fn text() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "dedupe",
+        "dir",
+      ],
+      tree: {
+        dir: {
+          "f.torrent": "x",
+        },
+      },
+    };
+
+    populate(&env);
+
+    env.assert_ok();
+
+    let infohash = Metainfo::test_value_single_infohash();
+
+    let mut lines = env
+      .out()
+      .lines()
+      .map(str::to_owned)
+      .collect::<Vec<String>>();
+    let overlaps = lines.split_off(5);
+    assert_eq!(
+      lines,
+      &[
+        "Duplicates:".to_owned(),
+        format!("  {}: `a.torrent`, `b.torrent`", infohash),
+        "Cross-seedable:".to_owned(),
+        "  `a.torrent`, `c.torrent`: differ in announce, source".to_owned(),
+        "Overlaps:".to_owned(),
+      ]
+    );
+
+    assert_eq!(
+      overlaps,
+      &[
+        "  `a.torrent`, `d.torrent`: 1 of 1 and 2 files shared, 32 KiB",
+        "  `c.torrent`, `d.torrent`: 1 of 1 and 2 files shared, 32 KiB",
+        "  `a.torrent`, `e.torrent`: 0 of 1 and 1 files shared, 0 bytes, identical pieces",
+        "  `c.torrent`, `e.torrent`: 0 of 1 and 1 files shared, 0 bytes, identical pieces",
+      ]
+    );
+
+    assert!(env.err().starts_with("Skipping `f.torrent`: "));
+  }
+
+  #[test]
+  fn none() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "dedupe",
+        "dir",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    Metainfo::test_value_single()
+      .dump(env.resolve("dir/a.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "No duplicates or overlaps found.\n");
+  }
+
+  #[test]
+  fn json() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "dedupe",
+        "--json",
+        "dir",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    populate(&env);
+
+    env.assert_ok();
+
+    let report: serde_json::Value = serde_json::from_str(&env.out()).unwrap();
+
+    assert_eq!(
+      report["duplicates"][0]["torrents"],
+      serde_json::json!(["a.torrent", "b.torrent"])
+    );
+    assert_eq!(report["cross_seedable"].as_array().unwrap().len(), 1);
+    assert_eq!(
+      report["cross_seedable"][0]["differences"],
+      serde_json::json!(["announce", "source"])
+    );
+    assert_eq!(report["overlaps"].as_array().unwrap().len(), 4);
+    assert_eq!(report["overlaps"][0]["shared_bytes"], 32 * 1024);
+  }
+}
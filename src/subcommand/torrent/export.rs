@@ -0,0 +1,213 @@
+use crate::common::*;
+
+const INPUT_HELP: &str =
+  "Export file list of torrent at `INPUT`. If `INPUT` is `-`, read metainfo from standard input.";
+
+const INPUT_FLAG: &str = "input-flag";
+
+const INPUT_POSITIONAL: &str = "<INPUT>";
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Export the list of files in a .torrent file, with their lengths and checksums.")
+)]
+pub(crate) struct Export {
+  #[structopt(
+    long = "format",
+    short = "f",
+    value_name = "FORMAT",
+    default_value = ManifestFormat::Json.into(),
+    possible_values = ManifestFormat::VARIANTS,
+    help = "Export file list in `FORMAT`. Torrent metainfo only contains MD5 checksums, and only \
+            if the torrent was created with `--md5`, so `md5sum` requires MD5 checksums, and \
+            `sfv` and `sha256sum` can only be produced while hashing, with `imdl torrent create \
+            --manifest`."
+  )]
+  format: ManifestFormat,
+  #[structopt(
+    name = INPUT_FLAG,
+    long = "input",
+    short = "i",
+    value_name = "INPUT",
+    empty_values = false,
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = INPUT_HELP,
+  )]
+  input_flag: Option<InputTarget>,
+  #[structopt(
+    name = INPUT_POSITIONAL,
+    value_name = "INPUT",
+    empty_values = false,
+    required_unless = INPUT_FLAG,
+    conflicts_with = INPUT_FLAG,
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = INPUT_HELP,
+  )]
+  input_positional: Option<InputTarget>,
+  #[structopt(
+    long = "output",
+    short = "o",
+    value_name = "TARGET",
+    empty_values(false),
+    default_value = "-",
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    help = "Write file list to `TARGET`, or to standard output if `TARGET` is `-`."
+  )]
+  output: OutputTarget,
+}
+
+impl Export {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let target = xor_args(
+      "input_flag",
+      &self.input_flag,
+      "input_positional",
+      &self.input_positional,
+    )?;
+
+    let input = env.read(target)?;
+
+    let metainfo = Metainfo::from_input(&input)?;
+
+    let text = Manifest::from_info(&metainfo.info).render(self.format)?;
+
+    self.output.write(env, text.as_bytes())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn md5sum() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "export",
+        "--format",
+        "md5sum",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    Metainfo::test_value_multiple()
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "000102030405060708090a0b0c0d0e0f  DIR/FILE\n");
+  }
+
+  #[test]
+  fn csv() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "export",
+        "--format",
+        "csv",
+        "--input",
+        "foo.torrent",
+        "--output",
+        "foo.csv",
+      ],
+      tree: {},
+    };
+
+    Metainfo::test_value_single_unset()
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.read_to_string("foo.csv"),
+      "path,length,md5,sha256,crc32\nNAME,5,,,\n"
+    );
+  }
+
+  #[test]
+  fn json() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "export",
+        "-",
+      ],
+      input: Metainfo::test_value_multiple().serialize().unwrap(),
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    let json: serde_json::Value = serde_json::from_str(&env.out()).unwrap();
+
+    assert_eq!(
+      json,
+      serde_json::json!([{
+        "path": "DIR/FILE",
+        "length": 32768,
+        "md5": "000102030405060708090a0b0c0d0e0f",
+      }])
+    );
+  }
+
+  #[test]
+  fn md5sum_missing() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "export",
+        "--format",
+        "md5sum",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    Metainfo::test_value_single_unset()
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    assert_matches!(
+      env.run(),
+      Err(Error::ManifestChecksumMissing {
+        format: ManifestFormat::Md5sum,
+        ..
+      })
+    );
+  }
+
+  #[test]
+  fn sfv_unavailable() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "export",
+        "--format",
+        "sfv",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    Metainfo::test_value_multiple()
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    assert_matches!(
+      env.run(),
+      Err(Error::ManifestChecksumMissing {
+        checksum: "CRC32",
+        ..
+      })
+    );
+  }
+}
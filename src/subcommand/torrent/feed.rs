@@ -0,0 +1,504 @@
+use crate::common::*;
+
+const TORRENT_NAMESPACE: &str = "http://xmlns.ezrss.it/0.1/";
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Generate an RSS feed from a directory of .torrent files.")
+)]
+pub(crate) struct Feed {
+  #[structopt(
+    long = "base-url",
+    value_name = "URL",
+    help = "Link to `.torrent` files relative to `URL`, which should be the URL at which `DIR` is \
+            published, and end with a `/`. The channel link is also set to `URL`. Without \
+            `--base-url`, `.torrent` files are linked by their paths relative to `DIR`."
+  )]
+  base_url: Option<Url>,
+  #[structopt(
+    long = "description",
+    value_name = "TEXT",
+    help = "Set feed description to `TEXT`. Defaults to the feed title."
+  )]
+  description: Option<String>,
+  #[structopt(
+    name = "DIR",
+    value_name = "DIR",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Include each `.torrent` file in `DIR` in the feed. Files that cannot be read or are \
+            not valid torrent metainfo are skipped with a message on standard error."
+  )]
+  dir: PathBuf,
+  #[structopt(
+    long = "output",
+    short = "o",
+    value_name = "TARGET",
+    empty_values(false),
+    default_value = "-",
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    help = "Write feed to `TARGET`, or to standard output if `TARGET` is `-`."
+  )]
+  output: OutputTarget,
+  #[structopt(
+    long = "title",
+    value_name = "TEXT",
+    help = "Set feed title to `TEXT`. Defaults to the name of `DIR`."
+  )]
+  title: Option<String>,
+}
+
+/// A `.torrent` file in the feed.
+struct Item {
+  date: Option<DateTime<Utc>>,
+  infohash: Infohash,
+  link: String,
+  magnet: Url,
+  metainfo: Metainfo,
+  size: usize,
+}
+
+impl Feed {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let root = env.resolve(&self.dir)?;
+
+    let items = self.load(env, &root)?;
+
+    let title = match &self.title {
+      Some(title) => title.clone(),
+      None => root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+    };
+
+    let description = self.description.as_ref().unwrap_or(&title);
+
+    let mut lines = vec![
+      r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_owned(),
+      format!(
+        r#"<rss version="2.0" xmlns:torrent="{}">"#,
+        TORRENT_NAMESPACE
+      ),
+      "  <channel>".to_owned(),
+      format!("    <title>{}</title>", xml_escape(&title)),
+    ];
+
+    if let Some(base_url) = &self.base_url {
+      lines.push(format!(
+        "    <link>{}</link>",
+        xml_escape(base_url.as_str())
+      ));
+    }
+
+    lines.push(format!(
+      "    <description>{}</description>",
+      xml_escape(description)
+    ));
+
+    if let Some(date) = items.iter().filter_map(|item| item.date).max() {
+      lines.push(format!(
+        "    <lastBuildDate>{}</lastBuildDate>",
+        date.to_rfc2822()
+      ));
+    }
+
+    for item in &items {
+      Self::item(&mut lines, item);
+    }
+
+    lines.push("  </channel>".to_owned());
+    lines.push("</rss>".to_owned());
+    lines.push(String::new());
+
+    self.output.write(env, lines.join("\n").as_bytes())
+  }
+
+  fn item(lines: &mut Vec<String>, item: &Item) {
+    let metainfo = &item.metainfo;
+    let magnet = xml_escape(item.magnet.as_str());
+    let size = metainfo.content_size();
+
+    lines.push("    <item>".to_owned());
+    lines.push(format!(
+      "      <title>{}</title>",
+      xml_escape(&metainfo.info.name)
+    ));
+    lines.push(format!("      <link>{}</link>", magnet));
+    lines.push(format!(
+      r#"      <guid isPermaLink="false">{}</guid>"#,
+      item.infohash
+    ));
+
+    if let Some(date) = item.date {
+      lines.push(format!("      <pubDate>{}</pubDate>", date.to_rfc2822()));
+    }
+
+    let description = match &metainfo.comment {
+      Some(comment) => format!("{} ({})", comment, size),
+      None => size.to_string(),
+    };
+
+    lines.push(format!(
+      "      <description>{}</description>",
+      xml_escape(&description)
+    ));
+    lines.push(format!(
+      r#"      <enclosure url="{}" length="{}" type="application/x-bittorrent"/>"#,
+      xml_escape(&item.link),
+      item.size
+    ));
+    lines.push(format!(
+      "      <torrent:contentLength>{}</torrent:contentLength>",
+      size.count()
+    ));
+    lines.push(format!(
+      "      <torrent:infoHash>{}</torrent:infoHash>",
+      item.infohash
+    ));
+    lines.push(format!(
+      "      <torrent:magnetURI>{}</torrent:magnetURI>",
+      magnet
+    ));
+    lines.push("    </item>".to_owned());
+  }
+
+  /// Load the `.torrent` files in `root`, newest first, as determined by
+  /// their creation dates. Files without a creation date come last. Ties are
+  /// broken by path.
+  fn load(&self, env: &mut Env, root: &Path) -> Result<Vec<Item>, Error> {
+    let mut items = Vec::new();
+
+    for result in WalkBuilder::new(root)
+      .standard_filters(false)
+      .hidden(true)
+      .sort_by_file_name(|a, b| a.cmp(b))
+      .build()
+    {
+      let entry = result?;
+      let path = entry.path();
+
+      if !path.is_file() || path.extension() != Some(OsStr::new("torrent")) {
+        continue;
+      }
+
+      let relative = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<Cow<str>>>()
+        .join("/");
+
+      let loaded = env
+        .read(InputTarget::Path(path.to_owned()))
+        .and_then(|input| {
+          let metainfo = Metainfo::from_input(&input)?;
+          let infohash = Infohash::from_input(&input)?;
+          let magnet = MagnetLink::from_metainfo_with_infohash(&metainfo, infohash)?.to_url();
+          Ok((metainfo, infohash, magnet, input.data().len()))
+        });
+
+      let (metainfo, infohash, magnet, size) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+          errln!(env, "Skipping `{}`: {}", relative, error)?;
+          continue;
+        }
+      };
+
+      let encoded = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| Self::percent_encode(&component.as_os_str().to_string_lossy()))
+        .collect::<Vec<String>>()
+        .join("/");
+
+      let link = match &self.base_url {
+        Some(base_url) => base_url
+          .join(&encoded)
+          .map(|url| url.to_string())
+          .unwrap_or(encoded),
+        None => encoded,
+      };
+
+      let date = metainfo
+        .creation_date
+        .and_then(|creation_date| i64::try_from(creation_date).ok())
+        .and_then(|creation_date| Utc.timestamp_opt(creation_date, 0).single());
+
+      items.push(Item {
+        date,
+        infohash,
+        link,
+        magnet,
+        metainfo,
+        size,
+      });
+    }
+
+    items.sort_by_key(|item| Reverse(item.date));
+
+    Ok(items)
+  }
+
+  /// Percent-encode every byte of the path segment `segment` that is not an
+  /// unreserved URL character, so that characters like `#`, `?`, and `%` in
+  /// file names don't change the meaning of the link.
+  fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in segment.bytes() {
+      if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+        encoded.push(char::from(byte));
+      } else {
+        encoded.push_str(&format!("%{:02X}", byte));
+      }
+    }
+
+    encoded
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn feed() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "feed",
+        "dir",
+        "--base-url",
+        "https://example.com/torrents/",
+        "--title",
+        "Foo & Bar",
+      ],
+      tree: {
+        dir: {
+          "invalid.torrent": "x",
+          "readme.txt": "x",
+          "sub dir": {},
+        },
+      },
+    };
+
+    let mut old = Metainfo::test_value_single();
+    old.comment = None;
+    old.dump(env.resolve("dir/old.torrent").unwrap()).unwrap();
+
+    let mut new = Metainfo::test_value_multiple();
+    new.creation_date = Some(86400);
+    new.info.name = "<NEW>".into();
+    new
+      .dump(env.resolve("dir/sub dir/new.torrent").unwrap())
+      .unwrap();
+
+    let mut undated = Metainfo::test_value_single();
+    undated.creation_date = None;
+    undated.info.name = "UNDATED".into();
+    undated
+      .dump(env.resolve("dir/undated.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    let item = |metainfo: &Metainfo, path: &str, date: Option<&str>, description: &str| {
+      let magnet = xml_escape(
+        MagnetLink::from_metainfo(metainfo)
+          .unwrap()
+          .to_url()
+          .as_str(),
+      )
+      .into_owned();
+      let infohash = metainfo.infohash().unwrap();
+      let size = fs::metadata(env.resolve(format!("dir/{}", path)).unwrap())
+        .unwrap()
+        .len();
+
+      let mut lines = vec![
+        "    <item>".to_owned(),
+        format!("      <title>{}</title>", xml_escape(&metainfo.info.name)),
+        format!("      <link>{}</link>", magnet),
+        format!(r#"      <guid isPermaLink="false">{}</guid>"#, infohash),
+      ];
+      if let Some(date) = date {
+        lines.push(format!("      <pubDate>{}</pubDate>", date));
+      }
+      lines.extend(vec![
+        format!("      <description>{}</description>", description),
+        format!(
+          r#"      <enclosure url="https://example.com/torrents/{}" length="{}" type="application/x-bittorrent"/>"#,
+          path.replace(' ', "%20"),
+          size
+        ),
+        format!(
+          "      <torrent:contentLength>{}</torrent:contentLength>",
+          metainfo.content_size().count()
+        ),
+        format!("      <torrent:infoHash>{}</torrent:infoHash>", infohash),
+        format!("      <torrent:magnetURI>{}</torrent:magnetURI>", magnet),
+        "    </item>".to_owned(),
+      ]);
+      lines.join("\n")
+    };
+
+    let want = [
+      r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_owned(),
+      r#"<rss version="2.0" xmlns:torrent="http://xmlns.ezrss.it/0.1/">"#.to_owned(),
+      "  <channel>".to_owned(),
+      "    <title>Foo &amp; Bar</title>".to_owned(),
+      "    <link>https://example.com/torrents/</link>".to_owned(),
+      "    <description>Foo &amp; Bar</description>".to_owned(),
+      "    <lastBuildDate>Fri, 02 Jan 1970 00:00:00 +0000</lastBuildDate>".to_owned(),
+      item(
+        &new,
+        "sub dir/new.torrent",
+        Some("Fri, 02 Jan 1970 00:00:00 +0000"),
+        "COMMENT (32 KiB)",
+      ),
+      item(
+        &old,
+        "old.torrent",
+        Some("Thu, 01 Jan 1970 00:00:01 +0000"),
+        "32 KiB",
+      ),
+      item(&undated, "undated.torrent", None, "COMMENT (32 KiB)"),
+      "  </channel>".to_owned(),
+      "</rss>".to_owned(),
+      String::new(),
+    ]
+    .join("\n");
+
+    assert_eq!(env.out(), want);
+    assert!(env.err().starts_with("Skipping `invalid.torrent`: "));
+  }
+
+  #[test]
+  fn output() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "feed",
+        "dir",
+        "--output",
+        "feed.xml",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    Metainfo::test_value_single()
+      .dump(env.resolve("dir/foo.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    let feed = env.read_to_string("feed.xml");
+    assert!(feed.contains("<title>dir</title>"));
+    assert!(feed.contains("<description>dir</description>"));
+    assert!(feed.contains(r#"<enclosure url="foo.torrent" "#));
+    assert!(!feed.contains("<link>https://"));
+    assert_eq!(env.out(), "");
+  }
+
+  #[test]
+  fn links_are_percent_encoded() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "feed",
+        "dir",
+        "--base-url",
+        "https://example.com/torrents/",
+      ],
+      tree: {
+        dir: {
+          "a%b": {},
+        },
+      },
+    };
+
+    for path in &["a#1.torrent", "b?.torrent", "a%b/c:d.torrent"] {
+      Metainfo::test_value_single()
+        .dump(env.resolve(format!("dir/{}", path)).unwrap())
+        .unwrap();
+    }
+
+    env.assert_ok();
+
+    let feed = env.out();
+    assert!(feed.contains(r#"<enclosure url="https://example.com/torrents/a%231.torrent" "#));
+    assert!(feed.contains(r#"<enclosure url="https://example.com/torrents/b%3F.torrent" "#));
+    assert!(feed.contains(r#"<enclosure url="https://example.com/torrents/a%25b/c%3Ad.torrent" "#));
+  }
+
+  #[test]
+  fn relative_links_are_percent_encoded() {
+    let mut env = test_env! {
+      args: ["torrent", "feed", "dir"],
+      tree: {
+        dir: {
+          "a%b": {},
+        },
+      },
+    };
+
+    for path in &["a#1.torrent", "a%b/c:d.torrent"] {
+      Metainfo::test_value_single()
+        .dump(env.resolve(format!("dir/{}", path)).unwrap())
+        .unwrap();
+    }
+
+    env.assert_ok();
+
+    let feed = env.out();
+    assert!(feed.contains(r#"<enclosure url="a%231.torrent" "#));
+    assert!(feed.contains(r#"<enclosure url="a%25b/c%3Ad.torrent" "#));
+  }
+
+  #[test]
+  fn infohash_correct_with_non_canonical_info_dict() -> Result<()> {
+    // The update URL is normalized when the info dictionary is re-serialized,
+    // which changes the infohash.
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "feed",
+        "dir",
+      ],
+      tree: {
+        dir: {
+          "foo.torrent": "d4:infod6:lengthi0e4:name3:foo12:piece lengthi16384e6:pieces0:\
+                          10:update-url18:HTTP://EXAMPLE.COMee",
+        },
+      },
+    };
+
+    env.assert_ok();
+
+    let infohash = Infohash::load(&env.resolve("dir/foo.torrent")?)?;
+    let reencoded = env.load_metainfo("dir/foo.torrent").infohash()?;
+
+    assert_ne!(infohash, reencoded);
+
+    let feed = env.out();
+    assert!(feed.contains(&format!(r#"<guid isPermaLink="false">{}</guid>"#, infohash)));
+    assert!(feed.contains(&format!(
+      "<torrent:infoHash>{}</torrent:infoHash>",
+      infohash
+    )));
+    assert!(feed.contains(&format!("<link>magnet:?xt=urn:btih:{}&amp;", infohash)));
+    assert!(!feed.contains(&reencoded.to_string()));
+
+    Ok(())
+  }
+}
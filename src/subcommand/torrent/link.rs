@@ -73,13 +73,7 @@ impl Link {
     let infohash = Infohash::from_input(&input)?;
     let metainfo = Metainfo::from_input(&input)?;
 
-    let mut link = MagnetLink::with_infohash(infohash);
-
-    link.set_name(&metainfo.info.name);
-
-    for result in metainfo.trackers() {
-      link.add_tracker(result?);
-    }
+    let mut link = MagnetLink::from_metainfo_with_infohash(&metainfo, infohash)?;
 
     for peer in self.peers {
       link.add_peer(peer);
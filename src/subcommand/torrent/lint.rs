@@ -0,0 +1,297 @@
+use crate::common::*;
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Check existing .torrent files for problems.")
+)]
+pub(crate) struct Lint {
+  #[structopt(
+    long = "allow",
+    short = "A",
+    value_name = "LINT",
+    number_of_values = 1,
+    possible_values = crate::lint::Lint::VARIANTS,
+    help = "Allow `LINT`. Violations of allowed lints are not reported."
+  )]
+  allowed_lints: Vec<crate::lint::Lint>,
+  #[structopt(
+    long = "deny",
+    short = "D",
+    value_name = "LINT",
+    number_of_values = 1,
+    possible_values = crate::lint::Lint::VARIANTS,
+    help = "Deny `LINT`. Violations of denied lints are reported as errors, and cause `imdl` to \
+            exit with a non-zero status code. Lints that would usually only produce warnings may \
+            be denied with this flag."
+  )]
+  denied_lints: Vec<crate::lint::Lint>,
+  #[structopt(
+    name = "INPUT",
+    value_name = "INPUT",
+    empty_values(false),
+    required = true,
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = "Check torrent metainfo at `INPUT`. If `INPUT` is `-`, read torrent metainfo from \
+            standard input. Inputs that cannot be read, or are not valid torrent metainfo, are \
+            reported on standard error, and the remaining inputs are still checked."
+  )]
+  inputs: Vec<InputTarget>,
+}
+
+impl Lint {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let mut linter = Linter::new();
+    linter.allow(self.allowed_lints.iter().cloned());
+    linter.deny(self.denied_lints.iter().cloned());
+
+    let mut count = 0;
+    let mut failures = 0;
+
+    for target in self.inputs {
+      let loaded = env.read(target.clone()).and_then(|input| {
+        let metainfo = Metainfo::from_input(&input)?;
+        Ok((input, metainfo))
+      });
+
+      let (input, metainfo) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+          errln!(env, "Failed to lint {}: {}", target, error)?;
+          failures += 1;
+          continue;
+        }
+      };
+
+      for violation in Violation::find(&input, &metainfo) {
+        let severity = match linter.severity(violation.lint) {
+          Some(severity) => severity,
+          None => continue,
+        };
+
+        outln!(
+          env,
+          "{}: {}[{}]: {}",
+          input.source(),
+          severity,
+          violation.lint,
+          violation.message
+        )?;
+
+        if severity == Severity::Error {
+          count += 1;
+        }
+      }
+    }
+
+    if failures > 0 {
+      return Err(Error::LintInput { count: failures });
+    }
+
+    if count > 0 {
+      return Err(Error::LintDenied { count });
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn multiple(name: &str, paths: &[&[&str]]) -> Metainfo {
+    let mut metainfo = Metainfo::test_value_multiple();
+    metainfo.info.name = name.into();
+    metainfo.info.pieces = PieceList::from_pieces(&["abc"]);
+    metainfo.info.mode = Mode::Multiple {
+      files: paths
+        .iter()
+        .map(|components| FileInfo {
+          length: Bytes(1),
+          md5sum: None,
+          path: FilePath::from_components(components),
+          extra: BTreeMap::new(),
+        })
+        .collect(),
+    };
+    metainfo
+  }
+
+  #[test]
+  fn input_required() {
+    test_env! {
+      args: [
+        "torrent",
+        "lint",
+      ],
+      tree: {},
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn clean() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "lint",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    multiple("foo", &[&["a"], &["b"]])
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "");
+  }
+
+  #[test]
+  fn errors() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "lint",
+        "foo.torrent",
+        "bar.torrent",
+      ],
+      tree: {},
+    };
+
+    multiple("foo", &[&["a"], &["A"]])
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    multiple("bar", &[&["con"], &["\u{e4}"], &["a\u{308}"]])
+      .dump(env.resolve("bar.torrent").unwrap())
+      .unwrap();
+
+    assert_matches!(env.run(), Err(Error::LintDenied { count: 3 }));
+
+    assert_eq!(
+      env.out(),
+      concat!(
+        "`foo.torrent`: error[case-insensitive-collision]: Paths `a` and `A` differ only in ",
+        "case, and collide on case-insensitive filesystems\n",
+        "`bar.torrent`: error[windows-incompatible-path]: Path `con` is not valid on Windows\n",
+        "`bar.torrent`: error[normalization-collision]: Paths `\u{e4}` and `a\u{308}` differ ",
+        "only in Unicode normalization, and collide on filesystems that normalize file names\n",
+      )
+    );
+  }
+
+  #[test]
+  fn invalid_inputs_are_skipped() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "lint",
+        "invalid.torrent",
+        "missing.torrent",
+        "foo.torrent",
+      ],
+      tree: {
+        "invalid.torrent": "x",
+      },
+    };
+
+    multiple("foo", &[&["a"], &["A"]])
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    assert_matches!(env.run(), Err(Error::LintInput { count: 2 }));
+
+    let err = env.err();
+    let mut lines = err.lines();
+    assert!(lines
+      .next()
+      .unwrap()
+      .starts_with("Failed to lint `invalid.torrent`: "));
+    assert!(lines
+      .next()
+      .unwrap()
+      .starts_with("Failed to lint `missing.torrent`: "));
+    assert_eq!(lines.next(), None);
+
+    assert!(env
+      .out()
+      .starts_with("`foo.torrent`: error[case-insensitive-collision]: "));
+  }
+
+  #[test]
+  fn warnings() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "lint",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    let mut metainfo = multiple("foo", &[&["a"]]);
+    metainfo.encoding = Some("latin-1".into());
+    metainfo.dump(env.resolve("foo.torrent").unwrap()).unwrap();
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.out(),
+      "`foo.torrent`: warning[non-utf8-encoding]: Encoding `latin-1` is not UTF-8\n"
+    );
+  }
+
+  #[test]
+  fn deny() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "lint",
+        "--deny",
+        "non-utf8-encoding",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    let mut metainfo = multiple("foo", &[&["a"]]);
+    metainfo.encoding = Some("latin-1".into());
+    metainfo.dump(env.resolve("foo.torrent").unwrap()).unwrap();
+
+    assert_matches!(env.run(), Err(Error::LintDenied { count: 1 }));
+
+    assert_eq!(
+      env.out(),
+      "`foo.torrent`: error[non-utf8-encoding]: Encoding `latin-1` is not UTF-8\n"
+    );
+  }
+
+  #[test]
+  fn allow() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "lint",
+        "--allow",
+        "windows-incompatible-path",
+        "foo.torrent",
+      ],
+      tree: {},
+    };
+
+    multiple("aux", &[&["a:b"]])
+      .dump(env.resolve("foo.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "");
+  }
+}
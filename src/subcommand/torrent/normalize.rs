@@ -0,0 +1,236 @@
+use crate::common::*;
+
+const INPUT_HELP: &str = "Normalize torrent metainfo at `INPUT`. If `INPUT` is `-`, read torrent \
+                          metainfo from standard input.";
+
+const INPUT_FLAG: &str = "input-flag";
+
+const INPUT_POSITIONAL: &str = "<INPUT>";
+
+const INPUT_VALUE: &str = "INPUT";
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Rewrite a .torrent file in canonical bencode.")
+)]
+pub(crate) struct Normalize {
+  #[structopt(
+    name = INPUT_FLAG,
+    long = "input",
+    short = "i",
+    value_name = INPUT_VALUE,
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    help = INPUT_HELP,
+  )]
+  input_flag: Option<InputTarget>,
+  #[structopt(
+    name = INPUT_POSITIONAL,
+    value_name = INPUT_VALUE,
+    empty_values(false),
+    parse(try_from_os_str = InputTarget::try_from_os_str),
+    required_unless = INPUT_FLAG,
+    conflicts_with = INPUT_FLAG,
+    help = INPUT_HELP,
+  )]
+  input_positional: Option<InputTarget>,
+  #[structopt(
+    long = "output",
+    short = "o",
+    value_name = "TARGET",
+    empty_values(false),
+    parse(try_from_os_str = OutputTarget::try_from_os_str),
+    required_if(INPUT_FLAG, "-"),
+    required_if(INPUT_POSITIONAL, "-"),
+    help = "Save normalized `.torrent` file to `TARGET`, or print to standard output if \
+            `TARGET` is `-`. Defaults to overwriting `INPUT`. Required when `INPUT` is `-`."
+  )]
+  output: Option<OutputTarget>,
+}
+
+impl Normalize {
+  pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
+    let target = xor_args(
+      "input_flag",
+      &self.input_flag,
+      "input_positional",
+      &self.input_positional,
+    )?;
+
+    let input = env.read(target)?;
+
+    let mut parser = BencodeParser::new(input.data());
+
+    let value = parser.parse().context(error::MetainfoDecode {
+      input: input.source().clone(),
+    })?;
+
+    let output = match self.output {
+      Some(output) => output,
+      None => match input.source() {
+        InputTarget::Path(path) => OutputTarget::Path(path.clone()),
+        InputTarget::Stdin => {
+          return Err(Error::internal(
+            "Expected `--output` to be set when `INPUT` is `-`.",
+          ))
+        }
+      },
+    };
+
+    let bytes = value.encode();
+
+    output.write(env, &bytes)?;
+
+    if options.quiet {
+      return Ok(());
+    }
+
+    if parser.non_canonical().is_empty() {
+      errln!(env, "Metainfo was already canonically encoded.")?;
+      return Ok(());
+    }
+
+    for non_canonical in parser.non_canonical() {
+      errln!(env, "Fixed: {}", non_canonical)?;
+    }
+
+    let before = Infohash::from_input(&input).ok();
+    let after = Infohash::from_input(&Input::new(input.source().clone(), bytes)).ok();
+
+    if let (Some(before), Some(after)) = (before, after) {
+      if before != after {
+        errln!(env, "Info hash changed from {} to {}.", before, after)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn input_required() {
+    test_env! {
+      args: [
+        "torrent",
+        "normalize",
+      ],
+      tree: {},
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn output_required_for_stdin() {
+    test_env! {
+      args: [
+        "torrent",
+        "normalize",
+        "-",
+      ],
+      tree: {},
+      matches: Err(Error::Clap { .. }),
+    };
+  }
+
+  #[test]
+  fn in_place() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "normalize",
+        "foo.torrent",
+      ],
+      tree: {
+        "foo.torrent": "d8:announce3:foo4:infod1:bi1e1:ai01eee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(
+      env.read_to_string("foo.torrent"),
+      "d8:announce3:foo4:infod1:ai1e1:bi1eee"
+    );
+
+    assert_eq!(
+      env.err(),
+      format!(
+        "Fixed: Dictionary key out of order at byte offset 29\nFixed: Number with leading zero at \
+         byte offset 33\nInfo hash changed from {} to {}.\n",
+        Infohash::from_bencoded_info_dict(b"d1:bi1e1:ai01ee"),
+        Infohash::from_bencoded_info_dict(b"d1:ai1e1:bi1ee"),
+      )
+    );
+  }
+
+  #[test]
+  fn already_canonical() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "normalize",
+        "--input",
+        "foo.torrent",
+        "--output",
+        "bar.torrent",
+      ],
+      tree: {
+        "foo.torrent": "d4:infod1:ai1eee",
+      },
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.read_to_string("bar.torrent"), "d4:infod1:ai1eee");
+    assert_eq!(env.err(), "Metainfo was already canonically encoded.\n");
+  }
+
+  #[test]
+  fn stdio() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "normalize",
+        "-",
+        "--output",
+        "-",
+      ],
+      input: "d1:bi0e1:ai0eexyz",
+      tree: {},
+    };
+
+    env.assert_ok();
+
+    assert_eq!(env.out(), "d1:ai0e1:bi0ee");
+  }
+
+  #[test]
+  fn invalid() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "normalize",
+        "foo.torrent",
+      ],
+      tree: {
+        "foo.torrent": "d1:a",
+      },
+    };
+
+    assert_matches!(
+      env.run(),
+      Err(Error::MetainfoDecode {
+        source: BencodeError::Eof { offset: 4 },
+        ..
+      })
+    );
+  }
+}
@@ -559,4 +559,37 @@ files\tNAME
 
     Ok(())
   }
+
+  #[test]
+  fn infohash_correct_with_non_canonical_info_dict() -> Result<()> {
+    // The update URL is normalized when the info dictionary is re-serialized,
+    // which changes the infohash.
+    let torrent = "d4:infod6:lengthi0e4:name3:foo12:piece \
+                   lengthi16384e6:pieces0:10:update-url18:HTTP://EXAMPLE.COMee";
+
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "show",
+        "--input",
+        "foo.torrent",
+      ],
+      tree: {
+        "foo.torrent": torrent,
+      }
+    };
+
+    env.assert_ok();
+
+    let infohash = Infohash::load(&env.resolve("foo.torrent")?)?;
+
+    assert_ne!(infohash, env.load_metainfo("foo.torrent").infohash()?);
+
+    assert!(env.out().contains(&format!("info hash\t{}\n", infohash)));
+    assert!(env
+      .out()
+      .contains(&format!("torrent size\t{}\n", torrent.len())));
+
+    Ok(())
+  }
 }
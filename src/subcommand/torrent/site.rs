@@ -0,0 +1,451 @@
+use crate::common::*;
+
+const STYLE: &str = "\
+html {
+  background-color: black;
+  color: white;
+  font-family: sans-serif;
+}
+
+body {
+  margin: 2em auto;
+  max-width: 60em;
+  padding: 0 1em;
+}
+
+a {
+  color: white;
+}
+
+table {
+  border-collapse: collapse;
+}
+
+th, td {
+  padding: 0.25em 1em 0.25em 0;
+  text-align: left;
+  vertical-align: top;
+}
+
+ul {
+  list-style: none;
+  margin: 0;
+  padding-left: 1em;
+}
+
+td > ul {
+  padding-left: 0;
+}
+";
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Generate a static website from a directory of .torrent files.")
+)]
+pub(crate) struct Site {
+  #[structopt(
+    name = "DIR",
+    value_name = "DIR",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Include each `.torrent` file in `DIR` in the site. Files that cannot be read, are not \
+            valid torrent metainfo, or have the same infohash as a file already included are \
+            skipped with a message on standard error."
+  )]
+  dir: PathBuf,
+  #[structopt(
+    long = "out",
+    short = "o",
+    value_name = "SITE",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Write site to directory `SITE`, creating it if it does not exist. `SITE` will contain \
+            `index.html`, which lists every torrent, and `style.css`. The `torrents` subdirectory \
+            of `SITE` will contain a page for each torrent, along with a copy of its `.torrent` \
+            file, both named after the torrent's infohash. All links are relative, so `SITE` can \
+            be hosted at any URL, or browsed locally."
+  )]
+  out: PathBuf,
+  #[structopt(
+    long = "title",
+    value_name = "TEXT",
+    help = "Set site title to `TEXT`. Defaults to the name of `DIR`."
+  )]
+  title: Option<String>,
+}
+
+/// A `.torrent` file in the site.
+struct Page {
+  data: Vec<u8>,
+  filename: String,
+  infohash: Infohash,
+  magnet: Url,
+  metainfo: Metainfo,
+  summary: TorrentSummary,
+}
+
+impl Site {
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
+    let root = env.resolve(&self.dir)?;
+    let out = env.resolve(&self.out)?;
+
+    let pages = Self::load(env, &root)?;
+
+    let title = match &self.title {
+      Some(title) => title.clone(),
+      None => root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+    };
+
+    let torrents = out.join("torrents");
+
+    fs::create_dir_all(&torrents).context(error::Filesystem { path: &torrents })?;
+
+    Self::write(&out.join("style.css"), STYLE.as_bytes())?;
+
+    Self::write(
+      &out.join("index.html"),
+      Self::index(&title, &pages).as_bytes(),
+    )?;
+
+    for page in &pages {
+      Self::write(
+        &torrents.join(format!("{}.html", page.infohash)),
+        Self::torrent(page).as_bytes(),
+      )?;
+
+      Self::write(
+        &torrents.join(format!("{}.torrent", page.infohash)),
+        &page.data,
+      )?;
+    }
+
+    Ok(())
+  }
+
+  fn write(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    fs::write(path, bytes).context(error::Filesystem { path })
+  }
+
+  fn header(title: &str, style: &str) -> Vec<String> {
+    vec![
+      "<!doctype html>".to_owned(),
+      "<html>".to_owned(),
+      "  <head>".to_owned(),
+      r#"    <meta charset="utf-8">"#.to_owned(),
+      r#"    <meta name="viewport" content="width=device-width, initial-scale=1">"#.to_owned(),
+      format!("    <title>{}</title>", xml_escape(title)),
+      format!(
+        r#"    <link href="{}" rel="stylesheet" type="text/css">"#,
+        style
+      ),
+      "  </head>".to_owned(),
+      "  <body>".to_owned(),
+      format!("    <h1>{}</h1>", xml_escape(title)),
+    ]
+  }
+
+  fn footer(lines: &mut Vec<String>) {
+    lines.push("  </body>".to_owned());
+    lines.push("</html>".to_owned());
+    lines.push(String::new());
+  }
+
+  fn links(page: &Page, prefix: &str) -> String {
+    format!(
+      r#"<a href="{}{}.torrent" download="{}">torrent</a> <a href="{}">magnet</a>"#,
+      prefix,
+      page.infohash,
+      xml_escape(&page.filename),
+      xml_escape(page.magnet.as_str()),
+    )
+  }
+
+  fn index(title: &str, pages: &[Page]) -> String {
+    let mut lines = Self::header(title, "style.css");
+
+    if pages.is_empty() {
+      lines.push("    <p>No torrents.</p>".to_owned());
+    } else {
+      lines.push("    <table>".to_owned());
+      lines.push(
+        "      <tr><th>Name</th><th>Size</th><th>Created</th><th>Download</th></tr>".to_owned(),
+      );
+
+      for page in pages {
+        let created = page
+          .metainfo
+          .creation_date
+          .and_then(|creation_date| i64::try_from(creation_date).ok())
+          .and_then(|creation_date| Utc.timestamp_opt(creation_date, 0).single())
+          .map(|date| date.format("%Y-%m-%d").to_string())
+          .unwrap_or_default();
+
+        lines.push(format!(
+          r#"      <tr><td><a href="torrents/{}.html">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>"#,
+          page.infohash,
+          xml_escape(&page.metainfo.info.name),
+          page.metainfo.content_size(),
+          created,
+          Self::links(page, "torrents/"),
+        ));
+      }
+
+      lines.push("    </table>".to_owned());
+    }
+
+    Self::footer(&mut lines);
+
+    lines.join("\n")
+  }
+
+  fn torrent(page: &Page) -> String {
+    let mut lines = Self::header(&page.metainfo.info.name, "../style.css");
+
+    lines.push(format!("    <p>{}</p>", Self::links(page, "")));
+
+    let mut table = Vec::new();
+    page
+      .summary
+      .write_html(&mut table)
+      .invariant_unwrap("writing to a vector cannot fail");
+    lines.push(String::from_utf8_lossy(&table).trim_end().to_owned());
+
+    lines.push(r#"    <p><a href="../index.html">All torrents</a></p>"#.to_owned());
+
+    Self::footer(&mut lines);
+
+    lines.join("\n")
+  }
+
+  /// Load the `.torrent` files in `root`, ordered by name and then by path.
+  fn load(env: &mut Env, root: &Path) -> Result<Vec<Page>, Error> {
+    let mut pages: Vec<Page> = Vec::new();
+    let mut seen: HashMap<Infohash, String> = HashMap::new();
+
+    for result in WalkBuilder::new(root)
+      .standard_filters(false)
+      .hidden(true)
+      .sort_by_file_name(|a, b| a.cmp(b))
+      .build()
+    {
+      let entry = result?;
+      let path = entry.path();
+
+      if !path.is_file() || path.extension() != Some(OsStr::new("torrent")) {
+        continue;
+      }
+
+      let display = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string();
+
+      let loaded = env
+        .read(InputTarget::Path(path.to_owned()))
+        .and_then(|input| {
+          let metainfo = Metainfo::from_input(&input)?;
+          let infohash = Infohash::from_input(&input)?;
+          let magnet = MagnetLink::from_metainfo_with_infohash(&metainfo, infohash)?.to_url();
+          let summary = TorrentSummary::from_input(&input)?;
+          Ok((metainfo, infohash, magnet, summary, input.data().to_vec()))
+        });
+
+      let (metainfo, infohash, magnet, summary, data) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+          errln!(env, "Skipping `{}`: {}", display, error)?;
+          continue;
+        }
+      };
+
+      if let Some(first) = seen.get(&infohash) {
+        errln!(env, "Skipping `{}`: Same infohash as `{}`", display, first)?;
+        continue;
+      }
+
+      seen.insert(infohash, display);
+
+      pages.push(Page {
+        filename: path
+          .file_name()
+          .map(|name| name.to_string_lossy().into_owned())
+          .unwrap_or_default(),
+        data,
+        infohash,
+        magnet,
+        metainfo,
+        summary,
+      });
+    }
+
+    pages.sort_by(|a, b| a.metainfo.info.name.cmp(&b.metainfo.info.name));
+
+    Ok(pages)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn site() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "site",
+        "dir",
+        "--out",
+        "site",
+        "--title",
+        "Foo & Bar",
+      ],
+      tree: {
+        dir: {
+          "invalid.torrent": "x",
+        },
+      },
+    };
+
+    let single = Metainfo::test_value_single();
+    single.dump(env.resolve("dir/a.torrent").unwrap()).unwrap();
+    single.dump(env.resolve("dir/b.torrent").unwrap()).unwrap();
+
+    let mut multiple = Metainfo::test_value_multiple();
+    multiple.info.name = "<MULTIPLE>".into();
+    multiple
+      .dump(env.resolve("dir/c.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    let err = env.err();
+    let mut lines = err.lines();
+    assert_eq!(
+      lines.next(),
+      Some("Skipping `b.torrent`: Same infohash as `a.torrent`")
+    );
+    assert!(lines
+      .next()
+      .unwrap()
+      .starts_with("Skipping `invalid.torrent`: "));
+    assert_eq!(lines.next(), None);
+
+    let single_infohash = single.infohash().unwrap();
+    let multiple_infohash = multiple.infohash().unwrap();
+
+    let index = env.read_to_string("site/index.html");
+    assert!(index.contains("<title>Foo &amp; Bar</title>"));
+    assert!(index.contains(r#"<link href="style.css" rel="stylesheet" type="text/css">"#));
+
+    let multiple_row = index
+      .find(&format!("torrents/{}.html", multiple_infohash))
+      .unwrap();
+    let single_row = index
+      .find(&format!("torrents/{}.html", single_infohash))
+      .unwrap();
+    assert!(multiple_row < single_row);
+
+    assert!(index.contains(&format!(
+      r#"<tr><td><a href="torrents/{}.html">NAME</a></td><td>32 KiB</td><td>1970-01-01</td><td><a href="torrents/{}.torrent" download="a.torrent">torrent</a> <a href="{}">magnet</a></td></tr>"#,
+      single_infohash,
+      single_infohash,
+      xml_escape(MagnetLink::from_metainfo(&single).unwrap().to_url().as_str()),
+    )));
+    assert!(index.contains("&lt;MULTIPLE&gt;"));
+
+    let page = env.read_to_string(format!("site/torrents/{}.html", multiple_infohash));
+    assert!(page.contains("<h1>&lt;MULTIPLE&gt;</h1>"));
+    assert!(page.contains(r#"<link href="../style.css" rel="stylesheet" type="text/css">"#));
+    assert!(page.contains(&format!(
+      r#"<a href="{}.torrent" download="c.torrent">torrent</a>"#,
+      multiple_infohash
+    )));
+    assert!(page.contains(&format!(
+      "<tr><th>Info Hash</th><td>{}</td></tr>",
+      multiple_infohash
+    )));
+    assert!(page.contains(r#"<ul class="tree"><li>&lt;MULTIPLE&gt;<ul>"#));
+    assert!(page.contains(r#"<a href="../index.html">All torrents</a>"#));
+
+    assert_eq!(
+      fs::read(
+        env
+          .resolve(format!("site/torrents/{}.torrent", single_infohash))
+          .unwrap()
+      )
+      .unwrap(),
+      fs::read(env.resolve("dir/a.torrent").unwrap()).unwrap(),
+    );
+
+    assert_eq!(env.read_to_string("site/style.css"), STYLE);
+  }
+
+  #[test]
+  fn empty() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "site",
+        "dir",
+        "--out",
+        "site",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    env.assert_ok();
+
+    let index = env.read_to_string("site/index.html");
+    assert!(index.contains("<title>dir</title>"));
+    assert!(index.contains("<p>No torrents.</p>"));
+  }
+
+  #[test]
+  fn infohash_correct_with_non_canonical_info_dict() -> Result<()> {
+    // The update URL is normalized when the info dictionary is re-serialized,
+    // which changes the infohash.
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "site",
+        "dir",
+        "--out",
+        "site",
+      ],
+      tree: {
+        dir: {
+          "foo.torrent": "d4:infod6:lengthi0e4:name3:foo12:piece lengthi16384e6:pieces0:\
+                          10:update-url18:HTTP://EXAMPLE.COMee",
+        },
+      },
+    };
+
+    env.assert_ok();
+
+    let infohash = Infohash::load(&env.resolve("dir/foo.torrent")?)?;
+    let reencoded = env.load_metainfo("dir/foo.torrent").infohash()?;
+
+    assert_ne!(infohash, reencoded);
+
+    let index = env.read_to_string("site/index.html");
+    assert!(index.contains(&format!("magnet:?xt=urn:btih:{}&amp;", infohash)));
+    assert!(!index.contains(&reencoded.to_string()));
+
+    let page = env.read_to_string(format!("site/torrents/{}.html", infohash));
+    assert!(page.contains(&format!("<tr><th>Info Hash</th><td>{}</td></tr>", infohash)));
+    assert!(!page.contains(&reencoded.to_string()));
+
+    Ok(())
+  }
+}
@@ -37,6 +37,14 @@ pub(crate) struct Stats {
     help = "Search `PATH` for torrents. May be a directory or a single torrent file."
   )]
   input: PathBuf,
+  #[structopt(
+    long = "json",
+    short = "j",
+    help = "Print report as JSON to standard output, instead of as text to standard error. \
+            Distributions of piece lengths, content sizes, and file counts include a histogram \
+            whose keys are the power-of-two lower bound of each bucket."
+  )]
+  json: bool,
   #[structopt(
     long = "print",
     short = "p",
@@ -46,9 +54,7 @@ pub(crate) struct Stats {
 }
 
 impl Stats {
-  pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
-    options.require_unstable("torrent stats subcommand")?;
-
+  pub(crate) fn run(self, env: &mut Env) -> Result<(), Error> {
     let path = env.resolve(self.input)?;
 
     let mut extractor = Extractor::new(self.print, &self.extract_patterns);
@@ -68,6 +74,12 @@ impl Stats {
       extractor.process(entry.path());
     }
 
+    if self.json {
+      let json = serde_json::to_string_pretty(&extractor.report()).context(error::JsonSerialize)?;
+      outln!(env, "{}", json)?;
+      return Ok(());
+    }
+
     errln!(env, "Torrents processed: {}", extractor.torrents)?;
     errln!(env, "Read failed:        {}", extractor.io_errors)?;
     errln!(
@@ -75,6 +87,25 @@ impl Stats {
       "Decode failed:      {}",
       extractor.bencode_decode_errors
     )?;
+    errln!(env, "Invalid metainfo:   {}", extractor.metainfo_errors)?;
+
+    match extractor.private_ratio() {
+      Some(ratio) => errln!(
+        env,
+        "Private:            {} ({:.1}%)",
+        extractor.private,
+        ratio * 100.0
+      )?,
+      None => errln!(env, "Private:            {}", extractor.private)?,
+    }
+
+    Self::distribution(env, "Piece lengths:      ", &extractor.piece_lengths, true)?;
+    Self::distribution(env, "Content sizes:      ", &extractor.content_sizes, true)?;
+    Self::distribution(env, "File counts:        ", &extractor.file_counts, false)?;
+
+    Self::counts(env, "Tracker domains:", &extractor.tracker_domains)?;
+    Self::counts(env, "Created by:", &extractor.created_by)?;
+    Self::counts(env, "Lint violations:", &extractor.lints)?;
 
     let mut paths = extractor.paths.into_iter().collect::<Vec<(String, u64)>>();
     paths.sort_by_key(|(_, count)| Reverse(*count));
@@ -97,13 +128,8 @@ impl Stats {
     }
 
     if !extractor.values.is_empty() {
-      let values = extractor
-        .values
-        .into_iter()
-        .collect::<Vec<(String, Vec<String>)>>();
-
       errln!(env, "Values:")?;
-      for (pattern, values) in values {
+      for (pattern, values) in extractor.values {
         err!(env, "{}: ", pattern)?;
         for (i, value) in values.iter().enumerate() {
           if i > 0 {
@@ -117,17 +143,110 @@ impl Stats {
 
     Ok(())
   }
+
+  fn distribution(
+    env: &mut Env,
+    label: &str,
+    distribution: &Distribution,
+    bytes: bool,
+  ) -> Result<(), Error> {
+    let (min, median, mean, max) = match (
+      distribution.min(),
+      distribution.median(),
+      distribution.mean(),
+      distribution.max(),
+    ) {
+      (Some(min), Some(median), Some(mean), Some(max)) => (min, median, mean, max),
+      _ => return errln!(env, "{}none", label),
+    };
+
+    if bytes {
+      #[allow(
+        clippy::as_conversions,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+      )]
+      let mean = Bytes(mean.round() as u64);
+      errln!(
+        env,
+        "{}min {}, median {}, mean {}, max {}",
+        label,
+        Bytes(min),
+        Bytes(median),
+        mean,
+        Bytes(max)
+      )
+    } else {
+      errln!(
+        env,
+        "{}min {}, median {}, mean {:.1}, max {}",
+        label,
+        min,
+        median,
+        mean,
+        max
+      )
+    }
+  }
+
+  fn counts<K: Display>(
+    env: &mut Env,
+    label: &str,
+    counts: &BTreeMap<K, u64>,
+  ) -> Result<(), Error> {
+    if counts.is_empty() {
+      return Ok(());
+    }
+
+    let mut counts = counts.iter().collect::<Vec<(&K, &u64)>>();
+    counts.sort_by_key(|(_, count)| Reverse(**count));
+    let width = counts[0].1.to_string().len();
+
+    errln!(env, "{}", label)?;
+    for (key, count) in counts {
+      errln!(env, "{:<width$} - {}", count, key, width = width)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+  torrents: u64,
+  read_failed: u64,
+  decode_failed: u64,
+  invalid_metainfo: u64,
+  private: u64,
+  private_ratio: Option<f64>,
+  piece_lengths: &'a Distribution,
+  content_sizes: &'a Distribution,
+  file_counts: &'a Distribution,
+  tracker_domains: &'a BTreeMap<String, u64>,
+  created_by: &'a BTreeMap<String, u64>,
+  lints: &'a BTreeMap<&'static str, u64>,
+  keys: &'a BTreeMap<String, u64>,
+  values: &'a BTreeMap<String, Vec<String>>,
 }
 
 struct Extractor {
   bencode_decode_errors: u64,
+  content_sizes: Distribution,
+  created_by: BTreeMap<String, u64>,
   current_path: String,
+  file_counts: Distribution,
   io_errors: u64,
-  paths: HashMap<String, u64>,
+  lints: BTreeMap<&'static str, u64>,
+  metainfo_errors: u64,
+  metainfos: u64,
+  paths: BTreeMap<String, u64>,
+  piece_lengths: Distribution,
   print: bool,
+  private: u64,
   regex_set: RegexSet,
   torrents: u64,
-  values: HashMap<String, Vec<String>>,
+  tracker_domains: BTreeMap<String, u64>,
+  values: BTreeMap<String, Vec<String>>,
 }
 
 impl Extractor {
@@ -138,15 +257,53 @@ impl Extractor {
     Self {
       bencode_decode_errors: 0,
       io_errors: 0,
-      paths: HashMap::new(),
+      metainfo_errors: 0,
+      metainfos: 0,
+      private: 0,
+      paths: BTreeMap::new(),
       torrents: 0,
-      values: HashMap::new(),
+      values: BTreeMap::new(),
       current_path: String::new(),
+      piece_lengths: Distribution::new(),
+      content_sizes: Distribution::new(),
+      file_counts: Distribution::new(),
+      tracker_domains: BTreeMap::new(),
+      created_by: BTreeMap::new(),
+      lints: BTreeMap::new(),
       print,
       regex_set,
     }
   }
 
+  fn report(&self) -> Report<'_> {
+    Report {
+      torrents: self.torrents,
+      read_failed: self.io_errors,
+      decode_failed: self.bencode_decode_errors,
+      invalid_metainfo: self.metainfo_errors,
+      private: self.private,
+      private_ratio: self.private_ratio(),
+      piece_lengths: &self.piece_lengths,
+      content_sizes: &self.content_sizes,
+      file_counts: &self.file_counts,
+      tracker_domains: &self.tracker_domains,
+      created_by: &self.created_by,
+      lints: &self.lints,
+      keys: &self.paths,
+      values: &self.values,
+    }
+  }
+
+  /// Fraction of torrents with valid metainfo that are private.
+  #[allow(clippy::as_conversions, clippy::cast_precision_loss)]
+  fn private_ratio(&self) -> Option<f64> {
+    if self.metainfos == 0 {
+      None
+    } else {
+      Some(self.private as f64 / self.metainfos as f64)
+    }
+  }
+
   fn process(&mut self, path: &Path) {
     if !path.is_file() || path.extension() != Some(OsStr::new("torrent")) {
       return;
@@ -172,9 +329,53 @@ impl Extractor {
       }
     } else {
       self.bencode_decode_errors += 1;
+      return;
+    }
+
+    let input = Input::new(InputTarget::Path(path.to_owned()), contents);
+
+    if let Ok(metainfo) = Metainfo::from_input(&input) {
+      self.aggregate(&input, &metainfo);
+    } else {
+      self.metainfo_errors += 1;
     }
   }
 
+  fn aggregate(&mut self, input: &Input, metainfo: &Metainfo) {
+    self.metainfos += 1;
+
+    if metainfo.info.private.unwrap_or(false) {
+      self.private += 1;
+    }
+
+    self
+      .piece_lengths
+      .record(metainfo.info.piece_length.count());
+    self.content_sizes.record(metainfo.content_size().count());
+    self.file_counts.record(match &metainfo.info.mode {
+      Mode::Single { .. } => 1,
+      Mode::Multiple { files } => files.len().into_u64(),
+    });
+
+    let domains = metainfo
+      .trackers()
+      .filter_map(Result::ok)
+      .filter_map(|url| url.host_str().map(str::to_owned))
+      .collect::<BTreeSet<String>>();
+
+    self.tracker_domains.increment_multiple(domains);
+
+    if let Some(created_by) = &metainfo.created_by {
+      self.created_by.increment_ref(created_by);
+    }
+
+    self.lints.increment_multiple(
+      Violation::find(input, metainfo)
+        .into_iter()
+        .map(|violation| violation.lint.name()),
+    );
+  }
+
   fn extract(&mut self, value: &Value) {
     let matches = self.regex_set.matches(&self.current_path);
 
@@ -268,3 +469,159 @@ impl Extractor {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn populate(env: &Env) {
+    let mut single = Metainfo::test_value_single();
+    single.info.private = Some(false);
+    single.dump(env.resolve("dir/a.torrent").unwrap()).unwrap();
+
+    let mut multiple = Metainfo::test_value_multiple();
+    multiple.announce_list = None;
+    multiple.info.piece_length = Bytes(32 * 1024);
+    multiple.info.pieces = PieceList::from_pieces(&["abc"]);
+    multiple
+      .dump(env.resolve("dir/b.torrent").unwrap())
+      .unwrap();
+  }
+
+  #[test]
+  fn text() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "stats",
+        "--input",
+        "dir",
+      ],
+      tree: {
+        dir: {
+          "c.torrent": "x",
+          "d.torrent": "de",
+        },
+      },
+    };
+
+    populate(&env);
+
+    env.assert_ok();
+
+    let err = env.err();
+    let lines = err.lines().take(13).collect::<Vec<&str>>();
+
+    assert_eq!(
+      lines,
+      &[
+        "Torrents processed: 4",
+        "Read failed:        0",
+        "Decode failed:      1",
+        "Invalid metainfo:   1",
+        "Private:            1 (50.0%)",
+        "Piece lengths:      min 16 KiB, median 16 KiB, mean 24 KiB, max 32 KiB",
+        "Content sizes:      min 32 KiB, median 32 KiB, mean 32 KiB, max 32 KiB",
+        "File counts:        min 1, median 1, mean 1.0, max 1",
+        "Tracker domains:",
+        "2 - announce.example",
+        "1 - a.example",
+        "1 - b.example",
+        "1 - c.example",
+      ]
+    );
+
+    assert!(err.contains("Created by:\n2 - CREATED BY\n"));
+  }
+
+  #[test]
+  fn json() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "stats",
+        "--json",
+        "--input",
+        "dir",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    populate(&env);
+
+    env.assert_ok();
+
+    let report: serde_json::Value = serde_json::from_str(&env.out()).unwrap();
+
+    assert_eq!(report["torrents"], 2);
+    assert_eq!(report["private"], 1);
+    assert_eq!(report["private_ratio"], 0.5);
+    assert_eq!(report["piece_lengths"]["histogram"]["16384"], 1);
+    assert_eq!(report["piece_lengths"]["histogram"]["32768"], 1);
+    assert_eq!(report["content_sizes"]["median"], 32 * 1024);
+    assert_eq!(report["file_counts"]["max"], 1);
+    assert_eq!(report["tracker_domains"]["announce.example"], 2);
+    assert_eq!(report["tracker_domains"]["a.example"], 1);
+    assert_eq!(report["created_by"]["CREATED BY"], 2);
+    assert_eq!(report["keys"]["info/name"], 2);
+  }
+
+  #[test]
+  fn lints() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "stats",
+        "--json",
+        "--input",
+        "dir",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    let mut metainfo = Metainfo::test_value_single();
+    metainfo.announce = None;
+    metainfo.announce_list = None;
+    metainfo
+      .dump(env.resolve("dir/a.torrent").unwrap())
+      .unwrap();
+    metainfo
+      .dump(env.resolve("dir/b.torrent").unwrap())
+      .unwrap();
+
+    env.assert_ok();
+
+    let report: serde_json::Value = serde_json::from_str(&env.out()).unwrap();
+
+    assert_eq!(report["lints"]["private-trackerless"], 2);
+  }
+
+  #[test]
+  fn limit() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "stats",
+        "--limit",
+        "1",
+        "--input",
+        "dir",
+      ],
+      tree: {
+        dir: {},
+      },
+    };
+
+    populate(&env);
+
+    env.assert_ok();
+
+    assert!(env.err().starts_with("Torrents processed: 1\n"));
+  }
+}
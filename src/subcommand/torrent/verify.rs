@@ -24,9 +24,42 @@ pub(crate) struct Verify {
     empty_values(false),
     parse(from_os_str),
     help = "Verify torrent content at `PATH` against torrent metainfo. Defaults to `name` field \
-            of torrent info dictionary."
+            of torrent info dictionary. If `PATH` is a `.tar` or `.zip` archive, torrent content \
+            is read from the archive, without extracting it. The files of a multi-file torrent \
+            are expected to be in a directory in the archive with the torrent's name, if there is \
+            one, and at the top level of the archive otherwise. `--io` and `--read-size` cannot \
+            be used with archives. Only the content of multi-file torrents is read from archives. \
+            The content of a single-file torrent is always verified as a regular file, even if it \
+            is an archive."
   )]
   content: Option<PathBuf>,
+  #[structopt(
+    long = "emit-resume",
+    value_names = &["FORMAT", "PATH"],
+    number_of_values = 2,
+    empty_values(false),
+    help = "Write resume data in `FORMAT` to `PATH`, recording which pieces were found to be \
+            intact, so that a client can start seeding the verified content without checking it \
+            again. `FORMAT` is `libtorrent`, for the `.fastresume` files used by libtorrent based \
+            clients such as qBittorrent and Deluge, or `transmission`, for Transmission \
+            `.resume` files. Clients look for the content in the directory containing it, under \
+            the torrent's name. Resume data is written even if verification fails, so that only \
+            the missing or corrupted pieces are downloaded. May be given more than once."
+  )]
+  emit_resume: Vec<OsString>,
+  #[structopt(
+    long = "io",
+    value_name = "BACKEND",
+    default_value = IoBackend::Buffered.into(),
+    possible_values = IoBackend::VARIANTS,
+    help = "Read files with `BACKEND`. `buffered` reads files through the page cache. `direct` \
+            opens files with `O_DIRECT`, bypassing the page cache, which avoids evicting other \
+            data from it when hashing content larger than memory. `fadvise` reads files through \
+            the page cache, but advises the kernel with `posix_fadvise` to drop the pages of \
+            each piece once it has been read. `direct` and `fadvise` fall back to `buffered` on \
+            platforms and filesystems that do not support them."
+  )]
+  io: IoBackend,
   #[structopt(
     name = INPUT_POSITIONAL,
     value_name = "INPUT",
@@ -47,6 +80,14 @@ pub(crate) struct Verify {
     help = METAINFO_HELP,
   )]
   input_flag: Option<InputTarget>,
+  #[structopt(
+    long = "read-size",
+    value_name = "BYTES",
+    help = "Read at most `BYTES` from files at a time. Accepts SI units, e.g. kib, mib, and gib. \
+            Must be a multiple of 4 KiB with `--io direct`. Defaults to 1 MiB with `--io direct`, \
+            and to as much of the current piece as possible otherwise."
+  )]
+  read_size: Option<Bytes>,
 }
 
 impl Verify {
@@ -58,6 +99,23 @@ impl Verify {
       &self.input_flag,
     )?;
 
+    let emit_resume = self
+      .emit_resume
+      .chunks(2)
+      .map(|pair| {
+        let format = pair[0]
+          .to_str()
+          .and_then(|text| text.parse::<ResumeFormat>().ok())
+          .ok_or_else(|| Error::ResumeFormatParse {
+            text: pair[0].to_string_lossy().into_owned(),
+          })?;
+
+        Ok((format, &pair[1]))
+      })
+      .collect::<Result<Vec<(ResumeFormat, &OsString)>>>()?;
+
+    let reader = FileReader::new(self.io, self.read_size)?;
+
     if !options.quiet {
       VerifyStep::Loading { metainfo: &target }.print(env)?;
     }
@@ -90,10 +148,36 @@ impl Verify {
       VerifyStep::Verifying { content: &content }.print(env)?;
     }
 
-    let status = metainfo.verify(&env.resolve(content)?, progress_bar)?;
+    let content = env.resolve(content)?;
+
+    let status = match Self::archive_format(&metainfo, &content) {
+      Some(format) => {
+        if self.io != IoBackend::Buffered || self.read_size.is_some() {
+          return Err(Error::ArchiveIo { path: content });
+        }
+
+        let archive = Archive::open(&content, format)?;
+        Verifier::verify_archive(&metainfo, &archive, progress_bar)?
+      }
+      None => metainfo.verify(&content, reader, progress_bar)?,
+    };
 
     status.print(env)?;
 
+    if !emit_resume.is_empty() {
+      let time = Utc::now().timestamp();
+
+      let infohash = Infohash::from_input(&input)?;
+
+      let resume = Resume::new(&metainfo, infohash, &status, &content, time)?;
+
+      for (format, path) in emit_resume {
+        let path = env.resolve(path)?;
+
+        fs::write(&path, resume.encode(format)).context(error::Filesystem { path })?;
+      }
+    }
+
     if status.good() {
       if !options.quiet {
         errln!(
@@ -106,6 +190,20 @@ impl Verify {
       Err(Error::Verify)
     }
   }
+
+  /// Format of the archive that `content` should be read from, if any. The
+  /// content of a single-file torrent is never read from an archive.
+  fn archive_format(metainfo: &Metainfo, content: &Path) -> Option<ArchiveFormat> {
+    if let Mode::Single { .. } = metainfo.info.mode {
+      return None;
+    }
+
+    if !content.is_file() {
+      return None;
+    }
+
+    ArchiveFormat::from_path(content)
+  }
 }
 
 #[cfg(test)]
@@ -722,4 +820,561 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn emit_resume() -> Result<()> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--piece-length",
+        "4",
+        "--allow",
+        "small-piece-length",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+          b: "def",
+          c: "ghi",
+        },
+      },
+    };
+
+    create_env.assert_ok();
+
+    create_env.remove_file("foo/a");
+
+    let torrent = create_env.resolve("foo.torrent")?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        &torrent,
+        "--emit-resume",
+        "libtorrent",
+        "foo.fastresume",
+        "--emit-resume",
+        "transmission",
+        "foo.resume",
+      ],
+      tree: {},
+    };
+
+    assert_matches!(verify_env.status(), Err(EXIT_FAILURE));
+
+    let save_path = Bencode::String(
+      create_env
+        .resolve("foo")?
+        .parent()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .as_bytes()
+        .to_vec(),
+    );
+
+    let get = |path: &str, key: &str| {
+      Bencode::from_input(&Input::from_path(&verify_env.resolve(path).unwrap()).unwrap())
+        .unwrap()
+        .get(&key.parse().unwrap())[0]
+        .clone()
+    };
+
+    let infohash: Sha1Digest = Infohash::load(&torrent)?.into();
+
+    assert_eq!(
+      get("foo.fastresume", "info-hash"),
+      Bencode::String(infohash.bytes().to_vec())
+    );
+    assert_eq!(
+      get("foo.fastresume", "pieces"),
+      Bencode::String(vec![0, 1, 1])
+    );
+    assert_eq!(get("foo.fastresume", "save_path"), save_path);
+
+    assert_eq!(get("foo.resume", "destination"), save_path);
+    assert_eq!(
+      get("foo.resume", "progress/pieces"),
+      Bencode::String(vec![0b0110_0000])
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn emit_resume_non_canonical() -> Result<()> {
+    // The update URL is normalized when the info dictionary is re-serialized,
+    // which changes the infohash.
+    let mut torrent = b"d4:infod6:lengthi3e4:name3:foo12:piece lengthi16384e6:pieces20:".to_vec();
+    torrent.extend(Sha1::from("abc").digest().bytes());
+    torrent.extend(b"10:update-url8:HTTP://Aee");
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        "foo.torrent",
+        "--emit-resume",
+        "libtorrent",
+        "foo.fastresume",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    verify_env.write("foo.torrent", &torrent);
+
+    verify_env.assert_ok();
+
+    let infohash: Sha1Digest = Infohash::load(&verify_env.resolve("foo.torrent")?)?.into();
+
+    assert_ne!(
+      infohash,
+      verify_env.load_metainfo("foo.torrent").infohash()?.into()
+    );
+
+    let resume = Bencode::from_input(&Input::from_path(&verify_env.resolve("foo.fastresume")?)?)?;
+
+    assert_eq!(
+      resume.get(&"info-hash".parse().unwrap()),
+      &[&Bencode::String(infohash.bytes().to_vec())]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn emit_resume_unknown_format() -> Result<()> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    create_env.assert_ok();
+
+    let torrent = create_env.resolve("foo.torrent")?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        &torrent,
+        "--emit-resume",
+        "utorrent",
+        "foo.resume",
+      ],
+      tree: {},
+    };
+
+    assert_matches!(
+      verify_env.run(),
+      Err(Error::ResumeFormatParse { text }) if text == "utorrent"
+    );
+
+    Ok(())
+  }
+
+  fn archive_env(archive: &str, contents: &[u8]) -> Result<(TestEnv, PathBuf)> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--piece-length",
+        "4",
+        "--allow",
+        "small-piece-length",
+        "--md5",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+          b: "def",
+          c: "ghi",
+        },
+      },
+    };
+
+    create_env.assert_ok();
+
+    fs::write(create_env.resolve(archive)?, contents).unwrap();
+
+    let torrent = create_env.resolve("foo.torrent")?;
+
+    Ok((create_env, torrent))
+  }
+
+  #[test]
+  fn archive_tar() -> Result<()> {
+    let (create_env, torrent) = archive_env(
+      "foo.tar",
+      &TarReader::test_archive(&[
+        ("foo/", b'5', b""),
+        ("foo/c", b'0', b"ghi"),
+        ("foo/a", b'0', b"abc"),
+        ("foo/b", b'0', b"def"),
+      ]),
+    )?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        &torrent,
+        "--content",
+        create_env.resolve("foo.tar")?,
+      ],
+      tree: {},
+    };
+
+    verify_env.assert_ok();
+
+    assert!(verify_env
+      .err()
+      .ends_with("Verification succeeded! \u{2728}\u{2728}\n"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn archive_zip() -> Result<()> {
+    let (create_env, torrent) = archive_env(
+      "foo.zip",
+      &Archive::test_zip(&[
+        ("a", b"abc", true),
+        ("b", b"def", false),
+        ("c", b"ghi", true),
+      ]),
+    )?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        &torrent,
+        "--content",
+        create_env.resolve("foo.zip")?,
+      ],
+      tree: {},
+    };
+
+    verify_env.assert_ok();
+
+    Ok(())
+  }
+
+  #[test]
+  fn archive_errors() -> Result<()> {
+    let (create_env, torrent) = archive_env(
+      "foo.tar",
+      &TarReader::test_archive(&[("./foo/a", b'0', b"abx"), ("./foo/c", b'0', b"ghij")]),
+    )?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        &torrent,
+        "--content",
+        create_env.resolve("foo.tar")?,
+      ],
+      tree: {},
+    };
+
+    assert_matches!(verify_env.status(), Err(EXIT_FAILURE));
+
+    let want = [
+      &format!(
+        "[1/2] \u{1F4BE} Loading metainfo from `{}`…",
+        torrent.display()
+      ),
+      &format!(
+        "[2/2] \u{1F9EE} Verifying pieces from `{}`…",
+        create_env.resolve("foo.tar")?.display()
+      ),
+      &format!(
+        "a: MD5 checksum mismatch: {} (expected {})",
+        Md5Digest::from_data("abx"),
+        Md5Digest::from_data("abc"),
+      ),
+      "b: File missing",
+      "c: 1 byte too long",
+      "Pieces corrupted.",
+      "error: Torrent verification failed.",
+      "",
+    ]
+    .join("\n");
+
+    assert_eq!(verify_env.err(), want);
+
+    Ok(())
+  }
+
+  #[test]
+  fn archive_io() -> Result<()> {
+    let (create_env, torrent) = archive_env(
+      "foo.tar",
+      &TarReader::test_archive(&[
+        ("foo/a", b'0', b"abc"),
+        ("foo/b", b'0', b"def"),
+        ("foo/c", b'0', b"ghi"),
+      ]),
+    )?;
+
+    let archive = create_env.resolve("foo.tar")?;
+
+    for args in &[&["--io", "direct"], &["--read-size", "4KiB"]] {
+      let mut verify_env = test_env! {
+        args: [
+          "torrent",
+          "verify",
+          "--input",
+          &torrent,
+          "--content",
+          &archive,
+          args[0],
+          args[1],
+        ],
+        tree: {},
+      };
+
+      assert_matches!(
+        verify_env.run(),
+        Err(Error::ArchiveIo { path }) if path == archive
+      );
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn archive_single_file_torrent() -> Result<()> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo.tar",
+      ],
+      tree: {
+        "foo.tar": "not a tar archive",
+      },
+    };
+
+    create_env.assert_ok();
+
+    let torrent = create_env.resolve("foo.tar.torrent")?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        &torrent,
+      ],
+      tree: {},
+    };
+
+    verify_env.assert_ok();
+
+    Ok(())
+  }
+
+  #[test]
+  fn archive_single_file_torrent_renamed() -> Result<()> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "release.zip",
+      ],
+      tree: {
+        "release.zip": "not a zip archive",
+      },
+    };
+
+    create_env.assert_ok();
+
+    fs::copy(
+      create_env.resolve("release.zip")?,
+      create_env.resolve("release-copy.zip")?,
+    )
+    .unwrap();
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        "--input",
+        create_env.resolve("release.zip.torrent")?,
+        "--content",
+        create_env.resolve("release-copy.zip")?,
+      ],
+      tree: {},
+    };
+
+    verify_env.assert_ok();
+
+    Ok(())
+  }
+
+  #[test]
+  fn sparse() -> Result<()> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "https://bar",
+        "--md5",
+      ],
+      tree: {},
+    };
+
+    let path = create_env.resolve("foo")?;
+
+    let mut file = File::create(&path).unwrap();
+    file.set_len(8 * 1024 * 1024).unwrap();
+    file.seek(SeekFrom::Start(2 * 1024 * 1024)).unwrap();
+    file.write_all(b"data").unwrap();
+    drop(file);
+
+    create_env.assert_ok();
+
+    let torrent = create_env.resolve("foo.torrent")?;
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        &torrent,
+      ],
+      tree: {},
+    };
+
+    verify_env.assert_ok();
+
+    let mut file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+    file.seek(SeekFrom::Start(5 * 1024 * 1024)).unwrap();
+    file.write_all(b"x").unwrap();
+    drop(file);
+
+    let mut verify_env = test_env! {
+      args: [
+        "torrent",
+        "verify",
+        &torrent,
+      ],
+      tree: {},
+    };
+
+    assert_matches!(verify_env.status(), Err(EXIT_FAILURE));
+
+    assert!(verify_env.err().contains("MD5 checksum mismatch"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn io_backends() -> Result<()> {
+    let mut create_env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "https://bar",
+        "--piece-length",
+        "16KiB",
+        "--md5",
+      ],
+      tree: {
+        foo: {
+          a: "abc",
+        },
+      },
+    };
+
+    let contents = (0..100_000)
+      .map(|i| u8::try_from(i % 251).unwrap())
+      .collect::<Vec<u8>>();
+
+    let path = create_env.resolve("foo/b")?;
+
+    fs::write(&path, &contents).unwrap();
+
+    create_env.assert_ok();
+
+    let torrent = create_env.resolve("foo.torrent")?;
+
+    for backend in IoBackend::VARIANTS {
+      let mut verify_env = test_env! {
+        args: [
+          "torrent",
+          "verify",
+          &torrent,
+          "--io",
+          backend,
+          "--read-size",
+          "8KiB",
+        ],
+        tree: {},
+      };
+
+      verify_env.assert_ok();
+    }
+
+    let mut file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+    file.seek(SeekFrom::Start(50_000)).unwrap();
+    file.write_all(b"x").unwrap();
+    drop(file);
+
+    for backend in IoBackend::VARIANTS {
+      let mut verify_env = test_env! {
+        args: [
+          "torrent",
+          "verify",
+          &torrent,
+          "--io",
+          backend,
+          "--read-size",
+          "8KiB",
+        ],
+        tree: {},
+      };
+
+      assert_matches!(verify_env.status(), Err(EXIT_FAILURE));
+
+      assert!(
+        verify_env.err().contains("MD5 checksum mismatch"),
+        "{} backend",
+        backend
+      );
+    }
+
+    Ok(())
+  }
 }
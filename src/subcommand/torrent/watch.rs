@@ -0,0 +1,445 @@
+use crate::common::*;
+
+use super::create::Create;
+
+const QUIET_PERIOD_DEFAULT: &str = "30";
+
+#[derive(StructOpt)]
+#[structopt(
+  help_message(consts::HELP_MESSAGE),
+  version_message(consts::VERSION_MESSAGE),
+  about("Watch a directory, and create a torrent for each new subdirectory.")
+)]
+pub(crate) struct Watch {
+  #[structopt(
+    name = "DIR",
+    value_name = "DIR",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Watch `DIR` for new subdirectories, and for changes to the contents of existing \
+            subdirectories. Subdirectories of `DIR` that were present when `imdl` was started are \
+            treated as new, unless a torrent has already been created for them. Hidden \
+            subdirectories are ignored."
+  )]
+  dir: PathBuf,
+  #[structopt(
+    long = "log",
+    value_name = "PATH",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Append a line to `PATH` for each torrent created, containing its infohash and the \
+            path of the `.torrent` file, separated by a tab. Defaults to `infohashes.log` in \
+            `OUT`."
+  )]
+  log: Option<PathBuf>,
+  #[structopt(
+    long = "once",
+    help = "Exit once a torrent has been created for each subdirectory present when `imdl` was \
+            started, instead of watching for new subdirectories indefinitely."
+  )]
+  once: bool,
+  #[structopt(
+    long = "out",
+    short = "o",
+    value_name = "OUT",
+    empty_values(false),
+    parse(from_os_str),
+    help = "Write the `.torrent` file for subdirectory `NAME` of `DIR` to `OUT/NAME.torrent`. \
+            Subdirectories for which the `.torrent` file already exists are skipped. `OUT` is \
+            created if it does not exist."
+  )]
+  out: PathBuf,
+  #[structopt(
+    long = "quiet-period",
+    value_name = "SECONDS",
+    default_value = QUIET_PERIOD_DEFAULT,
+    help = "Create a torrent for a subdirectory once nothing in it has changed for `SECONDS` \
+            seconds, so that torrents are not created from subdirectories that are still being \
+            written to. Torrents are created as `imdl torrent create` would create them, using \
+            settings from the profile selected with `--profile`, or the `default` profile, if \
+            present."
+  )]
+  quiet_period: u64,
+}
+
+impl Watch {
+  pub(crate) fn run(self, env: &mut Env, options: &Options) -> Result<(), Error> {
+    let root = env.resolve(&self.dir)?;
+    let out = env.resolve(&self.out)?;
+
+    fs::create_dir_all(&out).context(error::Filesystem { path: &out })?;
+
+    let log = match &self.log {
+      Some(log) => env.resolve(log)?,
+      None => out.join("infohashes.log"),
+    };
+
+    let quiet_period = Duration::from_secs(self.quiet_period);
+
+    let mut inotify = Inotify::new()?;
+    inotify.add(&root)?;
+    Self::watch_children(&mut inotify, &root);
+
+    // Subdirectories waiting to be quiet, and when they last changed.
+    let mut pending: BTreeMap<PathBuf, Instant> = BTreeMap::new();
+
+    Self::scan(&root, &out, Instant::now(), &mut pending)?;
+
+    loop {
+      let now = Instant::now();
+
+      let ready = pending
+        .iter()
+        .filter(|(_child, changed)| now.duration_since(**changed) >= quiet_period)
+        .map(|(child, _changed)| child.clone())
+        .collect::<Vec<PathBuf>>();
+
+      for child in ready {
+        pending.remove(&child);
+        Self::create(env, options, &child, &out, &log)?;
+      }
+
+      if self.once && pending.is_empty() {
+        return Ok(());
+      }
+
+      let timeout = pending
+        .values()
+        .map(|changed| (*changed + quiet_period).saturating_duration_since(now))
+        .min();
+
+      for change in inotify.read(timeout)? {
+        let changed = Instant::now();
+
+        match change {
+          Change::Overflow => {
+            Self::watch_children(&mut inotify, &root);
+            Self::scan(&root, &out, changed, &mut pending)?;
+          }
+          Change::Path(path) => {
+            if path.is_dir() {
+              inotify.add(&path).ok();
+              Self::watch_children(&mut inotify, &path);
+            }
+
+            if let Some(child) = Self::child(&root, &out, &path) {
+              pending.insert(child, changed);
+            }
+          }
+        }
+      }
+    }
+  }
+
+  /// Watch all directories beneath `dir`. Errors are ignored, since
+  /// directories may be removed while they are being added.
+  fn watch_children(inotify: &mut Inotify, dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+      Ok(entries) => entries,
+      Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+      let path = entry.path();
+
+      if entry
+        .file_type()
+        .map_or(false, |file_type| file_type.is_dir())
+      {
+        inotify.add(&path).ok();
+        Self::watch_children(inotify, &path);
+      }
+    }
+  }
+
+  /// Add each subdirectory of `root` that doesn't have a `.torrent` file to
+  /// `pending`.
+  fn scan(
+    root: &Path,
+    out: &Path,
+    now: Instant,
+    pending: &mut BTreeMap<PathBuf, Instant>,
+  ) -> Result<(), Error> {
+    for result in fs::read_dir(root).context(error::Filesystem { path: root })? {
+      let entry = result.context(error::Filesystem { path: root })?;
+
+      if let Some(child) = Self::child(root, out, &entry.path()) {
+        if !Self::torrent_path(out, &child).map_or(false, |torrent| torrent.exists()) {
+          pending.insert(child, now);
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// The subdirectory of `root` that contains `path`, if any, and if it
+  /// should be watched.
+  fn child(root: &Path, out: &Path, path: &Path) -> Option<PathBuf> {
+    let name = path.strip_prefix(root).ok()?.components().next()?;
+
+    let child = root.join(name);
+
+    if child == out || name.as_os_str().to_string_lossy().starts_with('.') || !child.is_dir() {
+      return None;
+    }
+
+    Some(child)
+  }
+
+  fn torrent_path(out: &Path, child: &Path) -> Option<PathBuf> {
+    let name = child.file_name()?.to_str()?;
+    Some(out.join(format!("{}.torrent", name)))
+  }
+
+  /// Create a torrent for `child` and record its infohash. Failures are
+  /// reported, but do not stop the watch.
+  fn create(
+    env: &mut Env,
+    options: &Options,
+    child: &Path,
+    out: &Path,
+    log: &Path,
+  ) -> Result<(), Error> {
+    if !child.is_dir() {
+      return Ok(());
+    }
+
+    let torrent = match Self::torrent_path(out, child) {
+      Some(torrent) => torrent,
+      None => {
+        errln!(
+          env,
+          "Skipping `{}`: {}",
+          child.display(),
+          Error::FilenameDecode {
+            filename: child.to_owned(),
+          }
+        )?;
+        return Ok(());
+      }
+    };
+
+    if torrent.exists() {
+      return Ok(());
+    }
+
+    let args: Vec<&OsStr> = vec![
+      OsStr::new("create"),
+      OsStr::new("--input"),
+      child.as_os_str(),
+      OsStr::new("--output"),
+      torrent.as_os_str(),
+    ];
+
+    let result = Create::from_iter_safe(args)
+      .map_err(|source| Error::Clap { source })
+      .and_then(|create| create.run(env, options))
+      .and_then(|()| env.read(InputTarget::Path(torrent.clone())))
+      .and_then(|input| Infohash::from_input(&input));
+
+    let infohash = match result {
+      Ok(infohash) => infohash,
+      Err(error) => {
+        errln!(
+          env,
+          "Failed to create torrent for `{}`: {}",
+          child.display(),
+          error
+        )?;
+        return Ok(());
+      }
+    };
+
+    let line = format!("{}\t{}", infohash, torrent.display());
+
+    let mut file = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(log)
+      .context(error::Filesystem { path: log })?;
+
+    writeln!(file, "{}", line).context(error::Filesystem { path: log })?;
+
+    outln!(env, "{}", line)?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn once() {
+    let mut env = test_env! {
+      args: [
+        "--profile",
+        "watch",
+        "torrent",
+        "watch",
+        "dir",
+        "--out",
+        "out",
+        "--quiet-period",
+        "0",
+        "--once",
+      ],
+      tree: {
+        dir: {
+          ".hidden": {
+            x: "x",
+          },
+          a: {
+            x: "x",
+          },
+          b: {
+            y: "y",
+          },
+          file: "z",
+        },
+        out: {
+          "b.torrent": "",
+        },
+        "imdl.toml": "
+          [profile.watch]
+          source = \"WATCH\"
+        ",
+      }
+    };
+
+    env.assert_ok();
+
+    let metainfo = env.load_metainfo("out/a.torrent");
+    assert_eq!(metainfo.info.source, Some("WATCH".into()));
+
+    let line = format!(
+      "{}\t{}\n",
+      metainfo.infohash().unwrap(),
+      env.resolve("out/a.torrent").unwrap().display()
+    );
+
+    assert_eq!(env.read_to_string("out/infohashes.log"), line);
+    assert_eq!(env.out(), line);
+    assert_eq!(env.read_to_string("out/b.torrent"), "");
+    assert!(!env.resolve("out/.hidden.torrent").unwrap().exists());
+    assert!(!env.resolve("out/file.torrent").unwrap().exists());
+  }
+
+  #[test]
+  fn log_appends() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "watch",
+        "dir",
+        "--out",
+        "out",
+        "--log",
+        "created.log",
+        "--quiet-period",
+        "0",
+        "--once",
+      ],
+      tree: {
+        dir: {
+          a: {
+            x: "x",
+          },
+        },
+        "created.log": "previous\n",
+      }
+    };
+
+    env.assert_ok();
+
+    let infohash = env.load_metainfo("out/a.torrent").infohash().unwrap();
+
+    assert_eq!(
+      env.read_to_string("created.log"),
+      format!(
+        "previous\n{}\t{}\n",
+        infohash,
+        env.resolve("out/a.torrent").unwrap().display()
+      )
+    );
+  }
+
+  #[test]
+  fn create_failure_is_reported() {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "watch",
+        "dir",
+        "--out",
+        "out",
+        "--quiet-period",
+        "0",
+        "--once",
+      ],
+      tree: {
+        dir: {
+          a: {
+            x: "x",
+          },
+          b: {
+            y: "y",
+          },
+        },
+        "imdl.toml": "
+          [profile.default]
+          private = true
+        ",
+      }
+    };
+
+    env.assert_ok();
+
+    let err = env.err();
+    for name in &["a", "b"] {
+      assert!(err.contains(&format!(
+        "Failed to create torrent for `{}`: {}",
+        env.resolve("dir").unwrap().join(name).display(),
+        Error::PrivateTrackerless,
+      )));
+    }
+    assert_eq!(env.out(), "");
+    assert!(!env.resolve("out/infohashes.log").unwrap().exists());
+  }
+
+  #[test]
+  fn pending() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let root = tempdir.path().join("root");
+    let out = root.join("out");
+
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::create_dir_all(root.join("done")).unwrap();
+    fs::create_dir_all(root.join(".hidden")).unwrap();
+    fs::create_dir_all(&out).unwrap();
+    fs::write(root.join("file"), "").unwrap();
+    fs::write(out.join("done.torrent"), "").unwrap();
+
+    let now = Instant::now();
+    let mut pending = BTreeMap::new();
+    Watch::scan(&root, &out, now, &mut pending).unwrap();
+
+    assert_eq!(
+      pending.keys().cloned().collect::<Vec<PathBuf>>(),
+      &[root.join("a")]
+    );
+
+    assert_eq!(
+      Watch::child(&root, &out, &root.join("a/b/c")),
+      Some(root.join("a"))
+    );
+    assert_eq!(Watch::child(&root, &out, &root.join("file")), None);
+    assert_eq!(Watch::child(&root, &out, &out.join("x.torrent")), None);
+    assert_eq!(Watch::child(&root, &out, &root.join(".hidden")), None);
+    assert_eq!(Watch::child(&root, &out, &root), None);
+  }
+}
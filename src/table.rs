@@ -161,6 +161,50 @@ impl Table {
     Ok(())
   }
 
+  pub(crate) fn write_html(&self, out: &mut dyn Write) -> io::Result<()> {
+    fn list(out: &mut dyn Write, values: &[String]) -> io::Result<()> {
+      write!(out, "<ul>")?;
+      for value in values {
+        write!(out, "<li>{}</li>", xml_escape(value))?;
+      }
+      write!(out, "</ul>")
+    }
+
+    writeln!(out, "<table>")?;
+
+    for (name, value) in self.rows() {
+      write!(out, "<tr><th>{}</th><td>", name)?;
+
+      match value {
+        Value::List(values) => list(out, values)?,
+        Value::Directory { root, files } => {
+          let mut tree = Tree::new(&root);
+          for file in files {
+            tree.insert(file.components());
+          }
+          write!(out, "<ul class=\"tree\">")?;
+          tree.write_html(out)?;
+          write!(out, "</ul>")?;
+        }
+        Value::Scalar(scalar) => write!(out, "{}", xml_escape(scalar))?,
+        Value::Size(bytes) => write!(out, "{}", bytes)?,
+        Value::Tiers(tiers) => {
+          write!(out, "<ul>")?;
+          for (name, values) in tiers {
+            write!(out, "<li>{}", xml_escape(name))?;
+            list(out, values)?;
+            write!(out, "</li>")?;
+          }
+          write!(out, "</ul>")?;
+        }
+      }
+
+      writeln!(out, "</td></tr>")?;
+    }
+
+    writeln!(out, "</table>")
+  }
+
   pub(crate) fn write_tab_delimited(&self, out: &mut dyn Write) -> io::Result<()> {
     for (name, value) in self.rows() {
       write!(out, "{}\t", name.to_lowercase())?;
@@ -242,6 +286,20 @@ impl<'name> Tree<'name> {
     self.children.push(child);
   }
 
+  fn write_html(&self, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, "<li>{}", xml_escape(self.name))?;
+
+    if !self.children.is_empty() {
+      write!(out, "<ul>")?;
+      for child in &self.children {
+        child.write_html(out)?;
+      }
+      write!(out, "</ul>")?;
+    }
+
+    write!(out, "</li>")
+  }
+
   fn lines(&self) -> Vec<(Vec<bool>, &'name str)> {
     let mut lines = Vec::new();
     let mut last = Vec::new();
@@ -417,4 +475,36 @@ Second  Row:        the
       "first\tthe\tthing\tabout\tthat\nsecond\tthe\tthing\tabout\tthat\n",
     );
   }
+
+  #[test]
+  fn html() {
+    let mut table = Table::new();
+    table.row("Name", "<Foo>");
+    table.size("Size", Bytes(1024));
+    table.list("List", vec!["a".into(), "b".into()]);
+    table.tiers("Tiers", vec![("Tier 1", &["x"])]);
+    table.directory(
+      "Files",
+      "Foo",
+      vec![
+        FilePath::from_components(&["a", "b"]),
+        FilePath::from_components(&["c"]),
+      ],
+    );
+    let mut cursor = Cursor::new(Vec::new());
+    table.write_html(&mut cursor).unwrap();
+    let have = String::from_utf8(cursor.into_inner()).unwrap();
+    assert_eq!(
+      have,
+      "<table>
+<tr><th>Name</th><td>&lt;Foo&gt;</td></tr>
+<tr><th>Size</th><td>1 KiB</td></tr>
+<tr><th>List</th><td><ul><li>a</li><li>b</li></ul></td></tr>
+<tr><th>Tiers</th><td><ul><li>Tier 1<ul><li>x</li></ul></li></ul></td></tr>
+<tr><th>Files</th><td><ul \
+       class=\"tree\"><li>Foo<ul><li>a<ul><li>b</li></ul></li><li>c</li></ul></li></ul></td></tr>
+</table>
+"
+    );
+  }
 }
@@ -0,0 +1,621 @@
+use crate::common::*;
+
+const BLOCK_SIZE: u64 = 512;
+
+/// Largest pax extended header or GNU long name that will be read.
+const EXTENSION_SIZE_MAX: u64 = 1024 * 1024;
+
+/// Reads the entries of a tar archive from a stream. Entries are returned by
+/// `next_entry`, after which the reader yields the contents of that entry.
+///
+/// POSIX ustar and pax archives, as well as GNU archives with long names, are
+/// supported.
+pub(crate) struct TarReader<'a> {
+  input: TarInput<'a>,
+  /// Offset of the next unread byte of the archive.
+  position: u64,
+  /// Bytes of the current entry's contents that have not been read.
+  remaining: u64,
+  /// Bytes of padding following the current entry's contents.
+  padding: u64,
+  finished: bool,
+}
+
+/// Where an archive is read from. Contents that are skipped are read and
+/// discarded from streams, but seeked over in files.
+enum TarInput<'a> {
+  Stream(&'a mut dyn BufRead),
+  File(&'a mut BufReader<File>),
+}
+
+/// An entry in a tar archive.
+#[derive(Debug, PartialEq)]
+pub(crate) struct TarEntry {
+  pub(crate) path: Vec<u8>,
+  pub(crate) size: u64,
+  /// Whether the entry is a regular file, as opposed to a directory, link, or
+  /// other special file.
+  pub(crate) file: bool,
+}
+
+impl TarEntry {
+  /// The entry's path in the torrent. Leading `/` and `.` components are
+  /// removed, as most tar implementations do when extracting.
+  pub(crate) fn file_path(&self) -> Result<FilePath, Error> {
+    let invalid = || Error::TarPath {
+      path: String::from_utf8_lossy(&self.path).into_owned(),
+    };
+
+    let path = str::from_utf8(&self.path).map_err(|_| invalid())?;
+
+    let components = path
+      .split('/')
+      .filter(|component| !component.is_empty() && *component != ".")
+      .collect::<Vec<&str>>();
+
+    if components.is_empty() || components.contains(&"..") {
+      return Err(invalid());
+    }
+
+    FilePath::from_relative_path(Path::new(&components.join("/")))
+  }
+}
+
+impl<'a> TarReader<'a> {
+  pub(crate) fn new(input: &'a mut dyn BufRead) -> Self {
+    Self::with_input(TarInput::Stream(input))
+  }
+
+  pub(crate) fn file(input: &'a mut BufReader<File>) -> Self {
+    Self::with_input(TarInput::File(input))
+  }
+
+  fn with_input(input: TarInput<'a>) -> Self {
+    Self {
+      position: 0,
+      remaining: 0,
+      padding: 0,
+      finished: false,
+      input,
+    }
+  }
+
+  /// Offset of the next unread byte of the archive, which, directly after
+  /// `next_entry` returns an entry, is the offset of its contents.
+  pub(crate) fn position(&self) -> u64 {
+    self.position
+  }
+
+  fn input(&mut self) -> &mut dyn BufRead {
+    match &mut self.input {
+      TarInput::Stream(stream) => *stream,
+      TarInput::File(file) => *file,
+    }
+  }
+
+  /// Skip any unread contents of the current entry, and read the header of
+  /// the next one. Returns `None` at the end of the archive.
+  pub(crate) fn next_entry(&mut self) -> io::Result<Option<TarEntry>> {
+    if self.finished {
+      return Ok(None);
+    }
+
+    self.skip(self.remaining + self.padding)?;
+    self.remaining = 0;
+    self.padding = 0;
+
+    let mut long_path = None;
+    let mut pax_path = None;
+    let mut pax_size = None;
+
+    loop {
+      let mut header = [0; 512];
+
+      if !self.read_block(&mut header)? || header.iter().all(|byte| *byte == 0) {
+        self.finished = true;
+        return Ok(None);
+      }
+
+      Self::check_checksum(&header)?;
+
+      let size = match pax_size.take() {
+        Some(size) => size,
+        None => Self::number(&header[124..136])?,
+      };
+
+      let padding = (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE;
+
+      match header[156] {
+        b'x' => {
+          let data = self.extension(size, padding)?;
+          for (key, value) in Self::pax_records(&data)? {
+            match key {
+              b"path" => pax_path = Some(value.to_vec()),
+              b"size" => pax_size = Some(Self::decimal(value)?),
+              _ => {}
+            }
+          }
+        }
+        b'L' => {
+          let mut data = self.extension(size, padding)?;
+          if let Some(nul) = data.iter().position(|byte| *byte == 0) {
+            data.truncate(nul);
+          }
+          long_path = Some(data);
+        }
+        b'g' | b'K' => self.skip(size + padding)?,
+        typeflag => {
+          let path = match pax_path.take().or_else(|| long_path.take()) {
+            Some(path) => path,
+            None => Self::header_path(&header),
+          };
+
+          self.remaining = size;
+          self.padding = padding;
+
+          return Ok(Some(TarEntry {
+            file: matches!(typeflag, b'0' | b'\0' | b'7'),
+            path,
+            size,
+          }));
+        }
+      }
+    }
+  }
+
+  /// Read a block, returning `false` if the stream ended before it began.
+  fn read_block(&mut self, block: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+
+    while read < block.len() {
+      match self.input().read(&mut block[read..]) {
+        Ok(0) if read == 0 => return Ok(false),
+        Ok(0) => return Err(Self::truncated()),
+        Ok(n) => {
+          read += n;
+          self.position += n.into_u64();
+        }
+        Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+        Err(error) => return Err(error),
+      }
+    }
+
+    Ok(true)
+  }
+
+  fn skip(&mut self, bytes: u64) -> io::Result<()> {
+    if bytes == 0 {
+      return Ok(());
+    }
+
+    if let TarInput::File(file) = &mut self.input {
+      let offset = i64::try_from(bytes).map_err(|_| Self::invalid("entry too large to skip"))?;
+      file.seek(SeekFrom::Current(offset))?;
+      self.position += bytes;
+      return Ok(());
+    }
+
+    let skipped = io::copy(&mut self.input().take(bytes), &mut io::sink())?;
+
+    self.position += skipped;
+
+    if skipped < bytes {
+      return Err(Self::truncated());
+    }
+
+    Ok(())
+  }
+
+  /// Read the contents of an extension entry, like a pax extended header.
+  fn extension(&mut self, size: u64, padding: u64) -> io::Result<Vec<u8>> {
+    if size > EXTENSION_SIZE_MAX {
+      return Err(Self::invalid("extended header too large"));
+    }
+
+    let mut data = Vec::new();
+    self.input().take(size).read_to_end(&mut data)?;
+
+    self.position += data.len().into_u64();
+
+    if data.len().into_u64() < size {
+      return Err(Self::truncated());
+    }
+
+    self.skip(padding)?;
+
+    Ok(data)
+  }
+
+  fn check_checksum(header: &[u8; 512]) -> io::Result<()> {
+    let expected = Self::number(&header[148..156])?;
+
+    let actual = header
+      .iter()
+      .enumerate()
+      .map(|(i, byte)| {
+        if (148..156).contains(&i) {
+          u64::from(b' ')
+        } else {
+          u64::from(*byte)
+        }
+      })
+      .sum::<u64>();
+
+    if actual != expected {
+      return Err(Self::invalid("header checksum mismatch"));
+    }
+
+    Ok(())
+  }
+
+  /// Path of an entry without a pax or GNU long name. POSIX ustar headers may
+  /// split the path between the name and prefix fields.
+  fn header_path(header: &[u8; 512]) -> Vec<u8> {
+    let name = Self::string(&header[0..100]);
+
+    if &header[257..263] == b"ustar\0" {
+      let prefix = Self::string(&header[345..500]);
+      if !prefix.is_empty() {
+        let mut path = prefix.to_vec();
+        path.push(b'/');
+        path.extend_from_slice(name);
+        return path;
+      }
+    }
+
+    name.to_vec()
+  }
+
+  /// Parse `LENGTH KEY=VALUE\n` records from a pax extended header.
+  fn pax_records(mut data: &[u8]) -> io::Result<Vec<(&[u8], &[u8])>> {
+    let mut records = Vec::new();
+
+    while !data.is_empty() {
+      let space = data
+        .iter()
+        .position(|byte| *byte == b' ')
+        .ok_or_else(|| Self::invalid("invalid pax record"))?;
+
+      let length = usize::try_from(Self::decimal(&data[..space])?)
+        .map_err(|_| Self::invalid("invalid pax record"))?;
+
+      if length <= space || length > data.len() || data[length - 1] != b'\n' {
+        return Err(Self::invalid("invalid pax record"));
+      }
+
+      let record = &data[space + 1..length - 1];
+
+      let equals = record
+        .iter()
+        .position(|byte| *byte == b'=')
+        .ok_or_else(|| Self::invalid("invalid pax record"))?;
+
+      records.push((&record[..equals], &record[equals + 1..]));
+
+      data = &data[length..];
+    }
+
+    Ok(records)
+  }
+
+  /// Parse a numeric header field, which is either octal text, or, if the
+  /// high bit of the first byte is set, a big-endian binary number.
+  fn number(field: &[u8]) -> io::Result<u64> {
+    if field.first().map_or(false, |byte| byte & 0x80 != 0) {
+      let mut number: u64 = 0;
+      for (i, byte) in field.iter().enumerate() {
+        let byte = if i == 0 { byte & 0x7f } else { *byte };
+        number = number
+          .checked_mul(256)
+          .and_then(|number| number.checked_add(byte.into()))
+          .ok_or_else(|| Self::invalid("numeric field overflow"))?;
+      }
+      return Ok(number);
+    }
+
+    let text = str::from_utf8(field)
+      .map_err(|_| Self::invalid("invalid numeric field"))?
+      .trim_matches(|c| c == '\0' || c == ' ');
+
+    if text.is_empty() {
+      return Ok(0);
+    }
+
+    u64::from_str_radix(text, 8).map_err(|_| Self::invalid("invalid numeric field"))
+  }
+
+  fn decimal(text: &[u8]) -> io::Result<u64> {
+    str::from_utf8(text)
+      .ok()
+      .and_then(|text| text.parse().ok())
+      .ok_or_else(|| Self::invalid("invalid pax record"))
+  }
+
+  fn string(field: &[u8]) -> &[u8] {
+    &field[..field
+      .iter()
+      .position(|byte| *byte == 0)
+      .unwrap_or(field.len())]
+  }
+
+  fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!("Invalid tar archive: {}", message),
+    )
+  }
+
+  fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated tar archive")
+  }
+
+  /// Build a ustar archive containing `entries`, each given as a path, a
+  /// typeflag, and contents.
+  #[cfg(test)]
+  pub(crate) fn test_archive(entries: &[(&str, u8, &[u8])]) -> Vec<u8> {
+    let mut archive = Vec::new();
+
+    for (path, typeflag, contents) in entries {
+      let mut header = [0u8; 512];
+      header[..path.len()].copy_from_slice(path.as_bytes());
+      header[100..107].copy_from_slice(b"0000644");
+      header[124..135].copy_from_slice(format!("{:011o}", contents.len()).as_bytes());
+      header[148..156].copy_from_slice(b"        ");
+      header[156] = *typeflag;
+      header[257..263].copy_from_slice(b"ustar\0");
+      header[263..265].copy_from_slice(b"00");
+
+      let checksum = header.iter().map(|byte| u64::from(*byte)).sum::<u64>();
+      header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+
+      archive.extend_from_slice(&header);
+      archive.extend_from_slice(contents);
+      archive.resize((archive.len() + 511) / 512 * 512, 0);
+    }
+
+    archive.resize(archive.len() + 1024, 0);
+
+    archive
+  }
+}
+
+impl Read for TarReader<'_> {
+  fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+    if self.remaining == 0 {
+      return Ok(0);
+    }
+
+    let max =
+      usize::try_from(self.remaining).map_or(buffer.len(), |remaining| remaining.min(buffer.len()));
+
+    let read = self.input().read(&mut buffer[..max])?;
+
+    if read == 0 && max > 0 {
+      return Err(Self::truncated());
+    }
+
+    self.remaining -= read.into_u64();
+    self.position += read.into_u64();
+
+    Ok(read)
+  }
+}
+
+impl BufRead for TarReader<'_> {
+  fn fill_buf(&mut self) -> io::Result<&[u8]> {
+    if self.remaining == 0 {
+      return Ok(&[]);
+    }
+
+    let remaining = self.remaining;
+
+    let buffer = self.input().fill_buf()?;
+
+    if buffer.is_empty() {
+      return Err(Self::truncated());
+    }
+
+    let len =
+      usize::try_from(remaining).map_or(buffer.len(), |remaining| remaining.min(buffer.len()));
+
+    Ok(&buffer[..len])
+  }
+
+  fn consume(&mut self, amount: usize) {
+    self.input().consume(amount);
+    self.remaining -= amount.into_u64();
+    self.position += amount.into_u64();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn entries(archive: &[u8]) -> io::Result<Vec<(TarEntry, Vec<u8>)>> {
+    let mut input = Cursor::new(archive);
+    let mut reader = TarReader::new(&mut input);
+    let mut entries = Vec::new();
+
+    while let Some(entry) = reader.next_entry()? {
+      let mut contents = Vec::new();
+      reader.read_to_end(&mut contents)?;
+      entries.push((entry, contents));
+    }
+
+    Ok(entries)
+  }
+
+  fn entry(path: &str, size: u64, file: bool) -> TarEntry {
+    TarEntry {
+      path: path.as_bytes().to_vec(),
+      size,
+      file,
+    }
+  }
+
+  #[test]
+  fn ustar() {
+    let archive = TarReader::test_archive(&[
+      ("dir/", b'5', b""),
+      ("dir/a", b'0', b"hello"),
+      ("dir/link", b'2', b""),
+      ("b", b'0', &[1; 600]),
+    ]);
+
+    assert_eq!(
+      entries(&archive).unwrap(),
+      &[
+        (entry("dir/", 0, false), Vec::new()),
+        (entry("dir/a", 5, true), b"hello".to_vec()),
+        (entry("dir/link", 0, false), Vec::new()),
+        (entry("b", 600, true), vec![1; 600]),
+      ]
+    );
+  }
+
+  #[test]
+  fn unread_contents_are_skipped() {
+    let archive = TarReader::test_archive(&[("a", b'0', &[1; 1000]), ("b", b'0', b"b")]);
+
+    let mut input = Cursor::new(archive);
+    let mut reader = TarReader::new(&mut input);
+
+    assert_eq!(reader.next_entry().unwrap(), Some(entry("a", 1000, true)));
+    assert_eq!(reader.next_entry().unwrap(), Some(entry("b", 1, true)));
+    assert_eq!(reader.next_entry().unwrap(), None);
+    assert_eq!(reader.next_entry().unwrap(), None);
+  }
+
+  #[test]
+  fn file() {
+    let archive = TarReader::test_archive(&[("a", b'0', &[1; 1000]), ("b", b'0', b"b")]);
+
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(&archive).unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut input = BufReader::new(file);
+    let mut reader = TarReader::file(&mut input);
+
+    assert_eq!(reader.next_entry().unwrap(), Some(entry("a", 1000, true)));
+    assert_eq!(reader.position(), 512);
+    assert_eq!(reader.next_entry().unwrap(), Some(entry("b", 1, true)));
+    assert_eq!(reader.position(), 2048);
+
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "b");
+    assert_eq!(reader.position(), 2049);
+
+    assert_eq!(reader.next_entry().unwrap(), None);
+  }
+
+  #[test]
+  fn long_names() {
+    let long = "x/".repeat(100) + "file";
+    let record = format!("path={}\n", long);
+    let pax = format!("{} {}", record.len() + 4, record);
+
+    let archive = TarReader::test_archive(&[
+      ("././@LongLink", b'L', format!("{}\0", long).as_bytes()),
+      ("truncated", b'0', b"a"),
+      ("pax", b'x', pax.as_bytes()),
+      ("truncated", b'0', b"b"),
+      ("global", b'g', b"8 a=bcd\n"),
+      ("c", b'0', b"c"),
+    ]);
+
+    assert_eq!(
+      entries(&archive).unwrap(),
+      &[
+        (entry(&long, 1, true), b"a".to_vec()),
+        (entry(&long, 1, true), b"b".to_vec()),
+        (entry("c", 1, true), b"c".to_vec()),
+      ]
+    );
+  }
+
+  #[test]
+  fn prefix() {
+    let mut archive = TarReader::test_archive(&[("name", b'0', b"")]);
+    archive[345..351].copy_from_slice(b"prefix");
+    archive[148..156].copy_from_slice(b"        ");
+    let checksum = archive[..512]
+      .iter()
+      .map(|byte| u64::from(*byte))
+      .sum::<u64>();
+    archive[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+
+    assert_eq!(
+      entries(&archive).unwrap(),
+      &[(entry("prefix/name", 0, true), Vec::new())]
+    );
+  }
+
+  #[test]
+  fn number() {
+    assert_eq!(TarReader::number(b"00000000012\0").unwrap(), 10);
+    assert_eq!(TarReader::number(b"     12 ").unwrap(), 10);
+    assert_eq!(TarReader::number(b"\0\0\0\0").unwrap(), 0);
+    assert_eq!(
+      TarReader::number(&[0x80, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0]).unwrap(),
+      1 << 32
+    );
+    assert!(TarReader::number(b"9").is_err());
+  }
+
+  #[test]
+  fn invalid() {
+    let error = entries(&[b'x'; 512]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  }
+
+  #[test]
+  fn truncated() {
+    let archive = TarReader::test_archive(&[("a", b'0', &[1; 1000])]);
+
+    let error = entries(&archive[..800]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+    let error = entries(&archive[..100]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+  }
+
+  #[test]
+  fn file_path() {
+    assert_eq!(
+      entry("./a//b/", 0, true).file_path().unwrap(),
+      FilePath::from_components(&["a", "b"])
+    );
+    assert_eq!(
+      entry("/a", 0, true).file_path().unwrap(),
+      FilePath::from_components(&["a"])
+    );
+
+    for path in &["", "./", "a/../b"] {
+      assert_matches!(
+        entry(path, 0, true).file_path(),
+        Err(Error::TarPath { path: invalid }) if invalid == *path
+      );
+    }
+
+    assert_matches!(
+      TarEntry {
+        path: vec![0xff],
+        size: 0,
+        file: true,
+      }
+      .file_path(),
+      Err(Error::TarPath { .. })
+    );
+  }
+
+  #[test]
+  fn empty() {
+    assert_eq!(entries(&[]).unwrap(), &[]);
+    assert_eq!(entries(&[0; 1024]).unwrap(), &[]);
+  }
+}
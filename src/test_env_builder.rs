@@ -2,24 +2,28 @@ use crate::common::*;
 
 pub(crate) struct TestEnvBuilder {
   args: Vec<OsString>,
+  config_root: Option<PathBuf>,
   current_dir: Option<PathBuf>,
   err_style: bool,
   input: Option<Box<dyn InputStream>>,
   out_is_term: bool,
   tempdir: Option<TempDir>,
   use_color: bool,
+  vars: BTreeMap<OsString, OsString>,
 }
 
 impl TestEnvBuilder {
   pub(crate) fn new() -> TestEnvBuilder {
     TestEnvBuilder {
       args: Vec::new(),
+      config_root: None,
       current_dir: None,
       err_style: false,
       input: None,
       out_is_term: false,
       tempdir: None,
       use_color: false,
+      vars: BTreeMap::new(),
     }
   }
 
@@ -48,6 +52,11 @@ impl TestEnvBuilder {
     self
   }
 
+  pub(crate) fn config_root(mut self, path: PathBuf) -> Self {
+    self.config_root = Some(path);
+    self
+  }
+
   pub(crate) fn arg_slice(mut self, args: &[&str]) -> Self {
     for arg in args.iter().cloned() {
       self.args.push(arg.into());
@@ -55,6 +64,11 @@ impl TestEnvBuilder {
     self
   }
 
+  pub(crate) fn var(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+    self.vars.insert(key.into(), value.into());
+    self
+  }
+
   pub(crate) fn tempdir(mut self, tempdir: TempDir) -> Self {
     self.tempdir = Some(tempdir);
     self
@@ -72,6 +86,12 @@ impl TestEnvBuilder {
       tempdir.path().to_owned()
     };
 
+    let config_root = if let Some(config_root) = self.config_root {
+      tempdir.path().join(config_root)
+    } else {
+      tempdir.path().to_owned()
+    };
+
     let out_stream = OutputStream::new(
       Box::new(out.clone()),
       self.use_color && self.out_is_term,
@@ -82,7 +102,9 @@ impl TestEnvBuilder {
 
     let env = Env::new(
       current_dir,
+      Some(config_root),
       self.args,
+      self.vars,
       self.input.unwrap_or_else(|| Box::new(io::empty())),
       out_stream,
       err_stream,
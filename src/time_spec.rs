@@ -0,0 +1,138 @@
+use crate::common::*;
+
+const UNITS: &[(&str, u64)] = &[
+  ("s", 1),
+  ("m", 60),
+  ("h", 60 * 60),
+  ("d", 24 * 60 * 60),
+  ("w", 7 * 24 * 60 * 60),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TimeSpec {
+  Ago(Duration),
+  At(SystemTime),
+}
+
+impl TimeSpec {
+  /// The time this spec refers to, relative to `now`. Durations that reach
+  /// back further than the Unix epoch resolve to the epoch.
+  pub(crate) fn resolve(&self, now: SystemTime) -> SystemTime {
+    match self {
+      Self::Ago(duration) => now
+        .checked_sub(*duration)
+        .map_or(SystemTime::UNIX_EPOCH, |time| {
+          time.max(SystemTime::UNIX_EPOCH)
+        }),
+      Self::At(time) => *time,
+    }
+  }
+
+  fn parse_duration(text: &str) -> Option<Duration> {
+    for (suffix, seconds) in UNITS {
+      if let Some(count) = text.strip_suffix(suffix) {
+        let count = count.parse::<u64>().ok()?;
+        return Some(Duration::from_secs(count.checked_mul(*seconds)?));
+      }
+    }
+
+    None
+  }
+}
+
+impl FromStr for TimeSpec {
+  type Err = Error;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    if let Some(duration) = Self::parse_duration(text) {
+      return Ok(Self::Ago(duration));
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+      return Ok(Self::At(time.into()));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+      return Ok(Self::At(
+        Utc.from_utc_datetime(&date.and_hms(0, 0, 0)).into(),
+      ));
+    }
+
+    Err(Error::TimeSpecParse {
+      text: text.to_owned(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn durations() {
+    assert_eq!(
+      "90s".parse::<TimeSpec>().unwrap(),
+      TimeSpec::Ago(Duration::from_secs(90))
+    );
+    assert_eq!(
+      "2h".parse::<TimeSpec>().unwrap(),
+      TimeSpec::Ago(Duration::from_secs(2 * 60 * 60))
+    );
+    assert_eq!(
+      "1w".parse::<TimeSpec>().unwrap(),
+      TimeSpec::Ago(Duration::from_secs(7 * 24 * 60 * 60))
+    );
+  }
+
+  #[test]
+  fn timestamps() {
+    let epoch = SystemTime::UNIX_EPOCH;
+    assert_eq!(
+      "1970-01-02".parse::<TimeSpec>().unwrap(),
+      TimeSpec::At(epoch + Duration::from_secs(24 * 60 * 60))
+    );
+    assert_eq!(
+      "1970-01-01T01:00:00+01:00".parse::<TimeSpec>().unwrap(),
+      TimeSpec::At(epoch)
+    );
+  }
+
+  #[test]
+  fn invalid() {
+    for text in &["", "d", "-1d", "10y", "yesterday", "2020-13-01"] {
+      assert_matches!(
+        text.parse::<TimeSpec>(),
+        Err(Error::TimeSpecParse { text: ref parsed }) if parsed == text
+      );
+    }
+  }
+
+  #[test]
+  fn resolve() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+    assert_eq!(
+      TimeSpec::Ago(Duration::from_secs(40)).resolve(now),
+      SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+    );
+    assert_eq!(
+      TimeSpec::At(SystemTime::UNIX_EPOCH).resolve(now),
+      SystemTime::UNIX_EPOCH
+    );
+  }
+
+  #[test]
+  fn resolve_before_epoch() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+    assert_eq!(
+      TimeSpec::Ago(Duration::from_secs(101)).resolve(now),
+      SystemTime::UNIX_EPOCH
+    );
+    assert_eq!(
+      "1000000000000d"
+        .parse::<TimeSpec>()
+        .unwrap()
+        .resolve(SystemTime::now()),
+      SystemTime::UNIX_EPOCH
+    );
+  }
+}
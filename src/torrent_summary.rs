@@ -24,8 +24,9 @@ impl TorrentSummary {
 
   pub(crate) fn from_input(input: &Input) -> Result<Self> {
     let metainfo = Metainfo::from_input(input)?;
-
-    Ok(Self::from_metainfo(metainfo)?)
+    let infohash = Infohash::from_input(input)?;
+    let size = Bytes(input.data().len().into_u64());
+    Ok(Self::new(metainfo, infohash, size))
   }
 
   pub(crate) fn write(&self, env: &mut Env) -> Result<()> {
@@ -45,6 +46,10 @@ impl TorrentSummary {
     Ok(())
   }
 
+  pub(crate) fn write_html(&self, out: &mut dyn Write) -> io::Result<()> {
+    self.table().write_html(out)
+  }
+
   fn table(&self) -> Table {
     let mut table = Table::new();
 
@@ -2,30 +2,46 @@ use crate::common::*;
 
 pub(crate) struct Verifier<'a> {
   metainfo: &'a Metainfo,
-  base: &'a Path,
+  content: Content<'a>,
+  bitfield: Vec<bool>,
   buffer: Vec<u8>,
   piece_length: usize,
-  pieces: PieceList,
   sha1: Sha1,
   piece_bytes_hashed: usize,
+  piece_missing_bytes: bool,
   progress_bar: Option<ProgressBar>,
+  reader: FileReader,
+}
+
+/// Where torrent content is read from.
+enum Content<'a> {
+  /// Files on disk, under this path.
+  Path(&'a Path),
+  /// Members of an archive, with paths starting with `prefix`.
+  Archive {
+    archive: &'a Archive,
+    prefix: String,
+  },
 }
 
 impl<'a> Verifier<'a> {
   fn new(
     metainfo: &'a Metainfo,
-    base: &'a Path,
+    content: Content<'a>,
+    reader: FileReader,
     progress_bar: Option<ProgressBar>,
   ) -> Result<Verifier<'a>> {
     let piece_length = metainfo.info.piece_length.as_piece_length()?.into_usize();
 
     Ok(Verifier {
+      bitfield: Vec::new(),
       buffer: vec![0; piece_length],
       piece_bytes_hashed: 0,
-      pieces: PieceList::new(),
+      piece_missing_bytes: false,
       sha1: Sha1::new(),
-      base,
+      content,
       metainfo,
+      reader: reader.piece_length(piece_length),
       piece_length,
       progress_bar,
     })
@@ -34,82 +50,180 @@ impl<'a> Verifier<'a> {
   pub(crate) fn verify(
     metainfo: &'a Metainfo,
     base: &'a Path,
+    reader: FileReader,
+    progress_bar: Option<ProgressBar>,
+  ) -> Result<Status> {
+    Self::new(metainfo, Content::Path(base), reader, progress_bar)?.verify_metainfo()
+  }
+
+  /// Verify content stored in `archive`. The files of a multi-file torrent
+  /// are expected to be inside a directory with the torrent's name, if the
+  /// archive contains one, and at the top level of the archive otherwise.
+  pub(crate) fn verify_archive(
+    metainfo: &'a Metainfo,
+    archive: &'a Archive,
     progress_bar: Option<ProgressBar>,
   ) -> Result<Status> {
-    Self::new(metainfo, base, progress_bar)?.verify_metainfo()
+    let name = &metainfo.info.name;
+
+    let prefix = if archive.contains_dir(name) {
+      format!("{}/", name)
+    } else {
+      String::new()
+    };
+
+    Self::new(
+      metainfo,
+      Content::Archive { archive, prefix },
+      FileReader::default(),
+      progress_bar,
+    )?
+    .verify_metainfo()
   }
 
   fn verify_metainfo(mut self) -> Result<Status> {
     match &self.metainfo.info.mode {
       Mode::Single { length, md5sum } => {
-        self.hash(&self.base).ok();
-        let error = FileError::verify(&self.base, *length, *md5sum).err();
+        let (file, error) = self.open(None, *length, *md5sum);
+        self.hash(file, *length);
 
-        let pieces = self.finish();
-        Ok(Status::single(pieces, error))
+        let bitfield = self.finish();
+        Ok(Status::single(bitfield, error))
       }
       Mode::Multiple { files } => {
         let mut status = Vec::new();
 
         for file in files {
-          let path = file.path.absolute(self.base);
-          self.hash(&path).ok();
-
-          status.push(FileStatus::status(
-            &path,
-            file.path.clone(),
-            file.length,
-            file.md5sum,
-          ));
+          let (reader, error) = self.open(Some(&file.path), file.length, file.md5sum);
+          self.hash(reader, file.length);
+
+          status.push(FileStatus::new(file.path.clone(), error));
         }
 
-        let pieces = self.finish();
+        let bitfield = self.finish();
+
+        Ok(Status::multiple(bitfield, status))
+      }
+    }
+  }
 
-        Ok(Status::multiple(pieces, status))
+  /// Open the file at `path` in the torrent, or the only file of a
+  /// single-file torrent if `path` is `None`, and check its length and MD5
+  /// checksum.
+  fn open(
+    &self,
+    path: Option<&FilePath>,
+    length: Bytes,
+    md5sum: Option<Md5Digest>,
+  ) -> (Option<Box<dyn Read>>, Option<FileError>) {
+    match &self.content {
+      Content::Path(base) => {
+        let path = path.map_or_else(|| base.to_path_buf(), |path| path.absolute(base));
+
+        let file = self
+          .reader
+          .open(&path)
+          .ok()
+          .map(|file| -> Box<dyn Read> { Box::new(file) });
+
+        (
+          file,
+          FileError::verify(&path, length, md5sum, &self.reader).err(),
+        )
+      }
+      Content::Archive { archive, prefix } => {
+        let member = match path {
+          Some(path) => format!("{}{}", prefix, path),
+          None => self.metainfo.info.name.clone(),
+        };
+
+        (
+          archive.open_member(&member).ok(),
+          FileError::verify_member(archive, &member, length, md5sum).err(),
+        )
       }
     }
   }
 
-  pub(crate) fn hash(&mut self, path: &Path) -> io::Result<()> {
-    let mut file = BufReader::new(File::open(path)?);
+  /// Hash `file`, which is expected to be `length` bytes long. Bytes that
+  /// cannot be read, because the file is missing, unreadable, or too short,
+  /// are counted towards the current piece without being hashed, and mark it
+  /// as bad, so that the pieces of later files stay aligned. If the file is
+  /// too long, all of it is hashed, so the pieces that follow are corrupted.
+  fn hash(&mut self, file: Option<Box<dyn Read>>, length: Bytes) {
+    let mut remaining = length.count();
+
+    if let Some(mut file) = file {
+      loop {
+        let limit = (self.piece_length - self.piece_bytes_hashed).min(self.reader.read_size());
 
-    loop {
-      let remaining = &mut self.buffer[..self.piece_length - self.piece_bytes_hashed];
+        let buffer = &mut self.buffer[..limit];
 
-      let bytes_read = file.read(remaining)?;
+        let bytes_read = match file.read(buffer) {
+          Ok(0) | Err(_) => break,
+          Ok(bytes_read) => bytes_read,
+        };
 
-      if bytes_read == 0 {
-        break;
+        self.sha1.update(&buffer[..bytes_read]);
+
+        remaining = remaining.saturating_sub(bytes_read.into_u64());
+
+        self.advance(bytes_read);
       }
+    }
 
-      let read = &remaining[..bytes_read];
+    while remaining > 0 {
+      let skipped = (self.piece_length - self.piece_bytes_hashed)
+        .into_u64()
+        .min(remaining);
 
-      self.sha1.update(read);
+      self.piece_missing_bytes = true;
 
-      self.piece_bytes_hashed += bytes_read;
+      remaining -= skipped;
 
-      if self.piece_bytes_hashed == self.piece_length {
-        self.pieces.push(self.sha1.digest().into());
-        self.sha1.reset();
-        self.piece_bytes_hashed = 0;
-      }
+      self.advance(usize::try_from(skipped).invariant_unwrap("skipped bytes fit in a piece"));
+    }
+  }
 
-      if let Some(progress_bar) = &self.progress_bar {
-        progress_bar.inc(bytes_read.into_u64());
-      }
+  fn advance(&mut self, bytes: usize) {
+    self.piece_bytes_hashed += bytes;
+
+    if self.piece_bytes_hashed == self.piece_length {
+      self.finish_piece();
     }
 
-    Ok(())
+    if let Some(progress_bar) = &self.progress_bar {
+      progress_bar.inc(bytes.into_u64());
+    }
+  }
+
+  fn finish_piece(&mut self) {
+    let digest = Sha1Digest::from(self.sha1.digest());
+
+    let good = !self.piece_missing_bytes
+      && self.metainfo.info.pieces.get(self.bitfield.len()) == Some(digest);
+
+    self.bitfield.push(good);
+    self.sha1.reset();
+    self.piece_bytes_hashed = 0;
+    self.piece_missing_bytes = false;
   }
 
-  fn finish(&mut self) -> bool {
+  /// Finish hashing, and return whether each piece is intact. If the content
+  /// is longer than the torrent, the pieces past the end of the torrent are
+  /// included, and are bad.
+  fn finish(mut self) -> Vec<bool> {
     if self.piece_bytes_hashed > 0 {
-      self.pieces.push(self.sha1.digest().into());
-      self.sha1.reset();
-      self.piece_bytes_hashed = 0;
+      self.finish_piece();
+    }
+
+    let count = self.metainfo.info.pieces.count();
+
+    if self.bitfield.len() < count {
+      self.bitfield.resize(count, false);
     }
 
-    self.pieces == self.metainfo.info.pieces
+    self.bitfield
   }
 }
 
@@ -141,7 +255,9 @@ mod tests {
 
     let metainfo = env.load_metainfo("foo.torrent");
 
-    assert!(metainfo.verify(&env.resolve("foo")?, None)?.good());
+    assert!(metainfo
+      .verify(&env.resolve("foo")?, FileReader::default(), None)?
+      .good());
 
     Ok(())
   }
@@ -172,7 +288,7 @@ mod tests {
 
     let metainfo = env.load_metainfo("foo.torrent");
 
-    let status = metainfo.verify(&env.resolve("foo")?, None)?;
+    let status = metainfo.verify(&env.resolve("foo")?, FileReader::default(), None)?;
 
     assert_eq!(status.count_bad(), 0);
 
@@ -180,4 +296,39 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn trailing_data() -> Result<()> {
+    let mut env = test_env! {
+      args: [
+        "torrent",
+        "create",
+        "--input",
+        "foo",
+        "--announce",
+        "https://bar",
+        "--piece-length",
+        "16KiB",
+      ],
+      tree: {
+        foo: "abc",
+      },
+    };
+
+    env.assert_ok();
+
+    env.write("foo", "abc".to_owned() + &"x".repeat(20 * 1024));
+
+    let metainfo = env.load_metainfo("foo.torrent");
+
+    let status = metainfo.verify(&env.resolve("foo")?, FileReader::default(), None)?;
+
+    assert_eq!(status.bitfield(), &[false, false]);
+
+    assert!(!status.pieces());
+
+    assert!(!status.good());
+
+    Ok(())
+  }
 }
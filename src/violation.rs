@@ -0,0 +1,331 @@
+use crate::common::*;
+
+/// Maximum number of pieces that common clients will load. This is
+/// libtorrent's default `max_pieces` limit.
+const MAX_PIECE_COUNT: u64 = 0x20_0000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Violation {
+  pub(crate) lint: Lint,
+  pub(crate) message: String,
+}
+
+impl Violation {
+  fn new(lint: Lint, message: impl Into<String>) -> Self {
+    Self {
+      lint,
+      message: message.into(),
+    }
+  }
+
+  /// Find all lint violations in `metainfo`, which was deserialized from
+  /// `input`.
+  pub(crate) fn find(input: &Input, metainfo: &Metainfo) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    Self::bencode(&mut violations, input);
+    Self::trackers(&mut violations, metainfo);
+    Self::pieces(&mut violations, &metainfo.info);
+    Self::files(&mut violations, &metainfo.info);
+
+    if let Some(encoding) = &metainfo.encoding {
+      if !encoding.eq_ignore_ascii_case(consts::ENCODING_UTF8) {
+        violations.push(Self::new(
+          Lint::NonUtf8Encoding,
+          format!("Encoding `{}` is not UTF-8", encoding),
+        ));
+      }
+    }
+
+    violations
+  }
+
+  fn bencode(violations: &mut Vec<Violation>, input: &Input) {
+    let mut parser = BencodeParser::new(input.data());
+
+    if let Err(err) = parser.parse() {
+      violations.push(Self::new(
+        Lint::NonCanonicalBencode,
+        format!("Invalid bencode: {}", err),
+      ));
+    }
+
+    for non_canonical in parser.non_canonical() {
+      violations.push(Self::new(
+        Lint::NonCanonicalBencode,
+        format!("Non-canonical bencode: {}", non_canonical),
+      ));
+    }
+  }
+
+  fn trackers(violations: &mut Vec<Violation>, metainfo: &Metainfo) {
+    let tiers = metainfo.announce_list.as_deref().unwrap_or_default();
+
+    let has_trackers = metainfo.announce.is_some() || tiers.iter().any(|tier| !tier.is_empty());
+
+    if metainfo.info.private == Some(true) && !has_trackers {
+      violations.push(Self::new(
+        Lint::PrivateTrackerless,
+        "Private torrent has no trackers",
+      ));
+    }
+
+    if metainfo.announce.is_none() {
+      violations.push(Self::new(
+        Lint::MissingAnnounce,
+        "Metainfo has no `announce` URL",
+      ));
+    }
+
+    let mut seen = BTreeSet::new();
+
+    for tracker in metainfo.announce.iter().chain(tiers.iter().flatten()) {
+      if let Err(err) = Url::parse(tracker) {
+        if seen.insert(tracker) {
+          violations.push(Self::new(
+            Lint::InvalidTracker,
+            format!("Tracker `{}` is not a valid URL: {}", tracker, err),
+          ));
+        }
+      }
+    }
+
+    // The `announce` URL is conventionally repeated in `announce-list`, so only
+    // check for duplicates within `announce-list`.
+    let mut seen = BTreeSet::new();
+
+    for tracker in tiers.iter().flatten() {
+      if !seen.insert(tracker) {
+        violations.push(Self::new(
+          Lint::DuplicateTracker,
+          format!(
+            "Tracker `{}` appears more than once in `announce-list`",
+            tracker
+          ),
+        ));
+      }
+    }
+  }
+
+  fn pieces(violations: &mut Vec<Violation>, info: &Info) {
+    let piece_length = info.piece_length;
+
+    if piece_length.count() < 16 * 1024 {
+      violations.push(Self::new(
+        Lint::SmallPieceLength,
+        format!("Piece length `{}` is less than 16 KiB", piece_length),
+      ));
+    }
+
+    if !piece_length.count().is_power_of_two() {
+      violations.push(Self::new(
+        Lint::UnevenPieceLength,
+        format!(
+          "Piece length `{}` is not an even power of two",
+          piece_length
+        ),
+      ));
+    }
+
+    let count = info.pieces.count().into_u64();
+
+    if count > MAX_PIECE_COUNT {
+      violations.push(Self::new(
+        Lint::AbsurdPieceCount,
+        format!(
+          "Piece count {} is greater than {}, which many clients will refuse to load",
+          count, MAX_PIECE_COUNT
+        ),
+      ));
+    }
+
+    if piece_length.count() > 0 {
+      let content_size = info.content_size().count();
+      let expected = content_size / piece_length.count()
+        + if content_size % piece_length.count() == 0 {
+          0
+        } else {
+          1
+        };
+
+      if count != expected {
+        violations.push(Self::new(
+          Lint::PieceCountMismatch,
+          format!(
+            "Piece count {} does not match the {} pieces needed for {} of content with piece \
+             length {}",
+            count,
+            expected,
+            info.content_size(),
+            piece_length
+          ),
+        ));
+      }
+    }
+  }
+
+  fn files(violations: &mut Vec<Violation>, info: &Info) {
+    match &info.mode {
+      Mode::Single { length, .. } => {
+        if length.count() == 0 {
+          violations.push(Self::new(
+            Lint::EmptyFile,
+            format!("File `{}` is empty", info.name),
+          ));
+        }
+
+        violations.extend(
+          PathViolation::find(&info.name, iter::empty())
+            .into_iter()
+            .map(Self::from),
+        );
+      }
+      Mode::Multiple { files } => {
+        let mut seen = BTreeSet::new();
+
+        for file in files {
+          if file.length.count() == 0 {
+            violations.push(Self::new(
+              Lint::EmptyFile,
+              format!("File `{}` is empty", file.path),
+            ));
+          }
+
+          if !seen.insert(&file.path) {
+            violations.push(Self::new(
+              Lint::DuplicatePath,
+              format!("Path `{}` appears more than once", file.path),
+            ));
+          }
+        }
+
+        violations.extend(
+          PathViolation::find(&info.name, files.iter().map(|file| &file.path))
+            .into_iter()
+            .map(Self::from),
+        );
+      }
+    }
+  }
+}
+
+impl From<PathViolation> for Violation {
+  fn from(violation: PathViolation) -> Self {
+    Self::new(violation.lint(), violation.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use pretty_assertions::assert_eq;
+
+  fn lints(metainfo: &Metainfo) -> Vec<Lint> {
+    let input = Input::new(
+      InputTarget::Path("foo.torrent".into()),
+      metainfo.serialize().unwrap(),
+    );
+    Violation::find(&input, metainfo)
+      .into_iter()
+      .map(|violation| violation.lint)
+      .collect()
+  }
+
+  #[test]
+  fn clean() {
+    assert_eq!(lints(&Metainfo::test_value_single()), &[]);
+  }
+
+  #[test]
+  fn non_canonical_bencode() {
+    let metainfo = Metainfo::test_value_single();
+    let mut data = metainfo.serialize().unwrap();
+    data.extend(b"garbage");
+    let input = Input::new(InputTarget::Path("foo.torrent".into()), data);
+    assert_eq!(
+      Violation::find(&input, &metainfo)
+        .into_iter()
+        .map(|violation| violation.lint)
+        .collect::<Vec<Lint>>(),
+      &[Lint::NonCanonicalBencode]
+    );
+  }
+
+  #[test]
+  fn trackers() {
+    let mut metainfo = Metainfo::test_value_single();
+    metainfo.announce = None;
+    metainfo.announce_list = Some(vec![
+      vec!["http://a.example".into(), "foo".into()],
+      vec!["http://a.example".into()],
+    ]);
+    assert_eq!(
+      lints(&metainfo),
+      &[
+        Lint::MissingAnnounce,
+        Lint::InvalidTracker,
+        Lint::DuplicateTracker
+      ]
+    );
+  }
+
+  #[test]
+  fn private_trackerless() {
+    let mut metainfo = Metainfo::test_value_single();
+    metainfo.announce = None;
+    metainfo.announce_list = None;
+    metainfo.info.private = Some(true);
+    assert_eq!(
+      lints(&metainfo),
+      &[Lint::PrivateTrackerless, Lint::MissingAnnounce]
+    );
+  }
+
+  #[test]
+  fn encoding() {
+    let mut metainfo = Metainfo::test_value_single();
+    metainfo.encoding = Some("latin-1".into());
+    assert_eq!(lints(&metainfo), &[Lint::NonUtf8Encoding]);
+  }
+
+  #[test]
+  fn pieces() {
+    let mut metainfo = Metainfo::test_value_single();
+    metainfo.info.piece_length = Bytes(1000);
+    assert_eq!(
+      lints(&metainfo),
+      &[
+        Lint::SmallPieceLength,
+        Lint::UnevenPieceLength,
+        Lint::PieceCountMismatch
+      ]
+    );
+  }
+
+  #[test]
+  fn files() {
+    let mut metainfo = Metainfo::test_value_multiple();
+    metainfo.info.pieces = PieceList::new();
+    metainfo.info.mode = Mode::Multiple {
+      files: vec![
+        FileInfo {
+          length: Bytes(0),
+          md5sum: None,
+          path: FilePath::from_components(&["a"]),
+          extra: BTreeMap::new(),
+        },
+        FileInfo {
+          length: Bytes(0),
+          md5sum: None,
+          path: FilePath::from_components(&["a"]),
+          extra: BTreeMap::new(),
+        },
+      ],
+    };
+    assert_eq!(
+      lints(&metainfo),
+      &[Lint::EmptyFile, Lint::EmptyFile, Lint::DuplicatePath]
+    );
+  }
+}
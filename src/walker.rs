@@ -13,6 +13,11 @@ pub(crate) struct Walker {
   include_hidden: bool,
   include_junk: bool,
   ignore: bool,
+  max_file_size: Option<Bytes>,
+  min_file_size: Option<Bytes>,
+  newer_than: Option<SystemTime>,
+  older_than: Option<SystemTime>,
+  reproducible: bool,
   sort_by: Vec<SortSpec>,
   patterns: Vec<Pattern>,
   root: PathBuf,
@@ -20,12 +25,23 @@ pub(crate) struct Walker {
 }
 
 impl Walker {
+  /// Whether or not `name` is the name of a junk file, which is skipped
+  /// unless `--include-junk` is given.
+  pub(crate) fn is_junk(name: &str) -> bool {
+    JUNK.contains(&name)
+  }
+
   pub(crate) fn new(root: &Path) -> Self {
     Self {
       follow_symlinks: false,
       include_hidden: false,
       include_junk: false,
       ignore: false,
+      max_file_size: None,
+      min_file_size: None,
+      newer_than: None,
+      older_than: None,
+      reproducible: false,
       sort_by: Vec::new(),
       patterns: Vec::new(),
       root: root.to_owned(),
@@ -51,6 +67,35 @@ impl Walker {
     Self { ignore, ..self }
   }
 
+  pub(crate) fn max_file_size(self, max_file_size: Option<Bytes>) -> Self {
+    Self {
+      max_file_size,
+      ..self
+    }
+  }
+
+  pub(crate) fn min_file_size(self, min_file_size: Option<Bytes>) -> Self {
+    Self {
+      min_file_size,
+      ..self
+    }
+  }
+
+  pub(crate) fn newer_than(self, newer_than: Option<SystemTime>) -> Self {
+    Self { newer_than, ..self }
+  }
+
+  pub(crate) fn older_than(self, older_than: Option<SystemTime>) -> Self {
+    Self { older_than, ..self }
+  }
+
+  pub(crate) fn reproducible(self, reproducible: bool) -> Self {
+    Self {
+      reproducible,
+      ..self
+    }
+  }
+
   pub(crate) fn sort_by(self, sort_by: Vec<SortSpec>) -> Self {
     Self { sort_by, ..self }
   }
@@ -79,7 +124,50 @@ impl Walker {
     Self { spinner, ..self }
   }
 
-  pub(crate) fn files(self) -> Result<Files, Error> {
+  /// Search for files, returning them along with the paths, relative to the
+  /// root, of files that were skipped by a filter and the reason they were
+  /// skipped.
+  pub(crate) fn walk(self) -> Result<(Files, Vec<(PathBuf, SkipReason)>), Error> {
+    if let Some(files) = self.single()? {
+      return Ok((files, Vec::new()));
+    }
+
+    self.collect()
+  }
+
+  /// Search for files, streaming them if `stream` is true and they are
+  /// sorted in path order, the default, so that they can be hashed while the
+  /// search is in progress, without holding all their paths in memory. The
+  /// total size of streamed files is not known in advance, and the paths of
+  /// skipped files are only returned if the files are not streamed.
+  pub(crate) fn files(self, stream: bool) -> Result<(Files, Vec<(PathBuf, SkipReason)>), Error> {
+    if !stream || !self.streamable() {
+      return self.walk();
+    }
+
+    if let Some(files) = self.single()? {
+      return Ok((files, Vec::new()));
+    }
+
+    let root = self.root.clone();
+
+    let stream = self.spinner(None).stream();
+
+    Ok((Files::stream(root, stream), Vec::new()))
+  }
+
+  /// Files can be streamed if they are sorted in path order, since that is
+  /// the order in which they are found.
+  fn streamable(&self) -> bool {
+    self
+      .sort_by
+      .first()
+      .map_or(true, |spec| *spec == SortSpec::default())
+  }
+
+  /// Check that the root may be searched, and return it as a single file, if
+  /// it is one.
+  fn single(&self) -> Result<Option<Files>, Error> {
     if !self.follow_symlinks
       && self
         .root
@@ -88,7 +176,9 @@ impl Walker {
         .file_type()
         .is_symlink()
     {
-      return Err(Error::SymlinkRoot { root: self.root });
+      return Err(Error::SymlinkRoot {
+        root: self.root.clone(),
+      });
     }
 
     let root_metadata = self
@@ -97,28 +187,222 @@ impl Walker {
       .context(error::Filesystem { path: &self.root })?;
 
     if root_metadata.is_file() {
-      return Ok(Files::file(self.root, Bytes::from(root_metadata.len())));
+      return Ok(Some(Files::file(
+        self.root.clone(),
+        Bytes::from(root_metadata.len()),
+      )));
     }
 
+    Ok(None)
+  }
+
+  /// Find all files in the root directory, and sort them.
+  fn collect(self) -> Result<(Files, Vec<(PathBuf, SkipReason)>), Error> {
     let mut file_infos = Vec::new();
-    let mut total_size = 0;
+    let mut skipped = Vec::new();
+    let mut sources = BTreeMap::new();
+    let mut total_size = Bytes(0);
+
+    let root = self.root.clone();
+    let sort_by = self.sort_by.clone();
+
+    for entry in self.stream() {
+      match entry? {
+        WalkEntry::File {
+          path,
+          source,
+          length,
+        } => {
+          if source != path.absolute(&root) {
+            if sources.contains_key(&path) {
+              return Err(Error::FilePathDuplicate { path });
+            }
+            sources.insert(path.clone(), source);
+          }
+
+          total_size += length;
+
+          file_infos.push(FileInfo {
+            md5sum: None,
+            path,
+            length,
+            extra: BTreeMap::new(),
+          });
+        }
+        WalkEntry::Skipped { path, reason } => skipped.push((path, reason)),
+      }
+    }
 
+    file_infos.sort_by(|a, b| SortSpec::compare(&sort_by, a, b));
+
+    skipped.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if !sources.is_empty() {
+      for pair in file_infos.windows(2) {
+        if pair[0].path == pair[1].path {
+          return Err(Error::FilePathDuplicate {
+            path: pair[0].path.clone(),
+          });
+        }
+      }
+    }
+
+    let files = Files::dir(
+      root,
+      total_size,
+      file_infos
+        .into_iter()
+        .map(|file_info| file_info.path)
+        .collect(),
+      sources,
+    );
+
+    Ok((files, skipped))
+  }
+
+  /// Search the root directory, which must not be a file. Entries of each
+  /// directory are visited in name order, so files are found in path order.
+  fn stream(self) -> FileStream {
     let mut walk_builder = WalkBuilder::new(&self.root);
     walk_builder
       .follow_links(self.follow_symlinks)
       .standard_filters(self.ignore)
       .require_git(false)
       .hidden(!self.include_hidden);
-    for result in walk_builder.build() {
+
+    if self.reproducible {
+      // Skip dotfiles ourselves, instead of relying on `ignore`, which also
+      // skips files hidden by attributes on some platforms, so that files only
+      // hidden on some platforms can be detected.
+      let include_hidden = self.include_hidden;
+      walk_builder
+        .git_global(false)
+        .hidden(false)
+        .filter_entry(move |entry| {
+          include_hidden
+            || entry.depth() == 0
+            || !entry.file_name().to_string_lossy().starts_with('.')
+        });
+
+      // Paths are normalized, so they must be sorted by their normalized
+      // names.
+      walk_builder
+        .sort_by_file_name(|a, b| a.to_string_lossy().nfc().cmp(b.to_string_lossy().nfc()));
+    } else {
+      walk_builder.sort_by_file_name(OsStr::cmp);
+    }
+
+    FileStream {
+      walk: walk_builder.build(),
+      walker: self,
+      renamed: false,
+    }
+  }
+
+  fn metadata_filter(&self, path: &Path, metadata: &Metadata) -> Result<Option<SkipReason>, Error> {
+    let len = Bytes::from(metadata.len());
+
+    if let Some(min) = self.min_file_size {
+      if len < min {
+        return Ok(Some(SkipReason::Small { min }));
+      }
+    }
+
+    if let Some(max) = self.max_file_size {
+      if len > max {
+        return Ok(Some(SkipReason::Large { max }));
+      }
+    }
+
+    if self.newer_than.is_none() && self.older_than.is_none() {
+      return Ok(None);
+    }
+
+    let modified = metadata.modified().context(error::Filesystem { path })?;
+
+    if let Some(newer_than) = self.newer_than {
+      if modified <= newer_than {
+        return Ok(Some(SkipReason::Old));
+      }
+    }
+
+    if let Some(older_than) = self.older_than {
+      if modified >= older_than {
+        return Ok(Some(SkipReason::New));
+      }
+    }
+
+    Ok(None)
+  }
+
+  fn pattern_filter(&self, relative: &Path) -> bool {
+    for Pattern { glob, include } in self.patterns.iter().rev() {
+      if glob.is_match(relative) {
+        return *include;
+      }
+    }
+
+    if let Some(Pattern { include, .. }) = self.patterns.first() {
+      return !include;
+    }
+
+    true
+  }
+}
+
+/// A file found by a `FileStream`, or a file that was skipped by a filter.
+pub(crate) enum WalkEntry {
+  File {
+    path: FilePath,
+    source: PathBuf,
+    length: Bytes,
+  },
+  Skipped {
+    path: PathBuf,
+    reason: SkipReason,
+  },
+}
+
+/// Files found by a `Walker`, produced while the search is in progress.
+pub(crate) struct FileStream {
+  walk: ignore::Walk,
+  walker: Walker,
+  renamed: bool,
+}
+
+impl FileStream {
+  /// Whether the paths of any files were changed by Unicode normalization.
+  pub(crate) fn renamed(&self) -> bool {
+    self.renamed
+  }
+
+  fn next_entry(&mut self) -> Result<Option<WalkEntry>, Error> {
+    let walker = &self.walker;
+
+    for result in &mut self.walk {
       let entry = result?;
       let path = entry.path();
 
-      if let Some(s) = &self.spinner {
-        let display_path = path.strip_prefix(&self.root).unwrap_or(&path);
+      if let Some(s) = &walker.spinner {
+        let display_path = path.strip_prefix(&walker.root).unwrap_or(&path);
         s.set_message(&display_path.display().to_string());
         s.tick();
       }
 
+      if walker.reproducible && entry.depth() > 0 {
+        if entry.path_is_symlink() {
+          return Err(Error::ReproducibleSymlink {
+            path: path.to_owned(),
+          });
+        }
+
+        if !walker.include_hidden && Platform::hidden(path)? {
+          return Err(Error::ReproducibleHidden {
+            path: path.to_owned(),
+          });
+        }
+      }
+
       let metadata = entry.metadata()?;
 
       if !metadata.is_file() {
@@ -126,63 +410,64 @@ impl Walker {
       }
 
       let relative = path
-        .strip_prefix(&self.root)
+        .strip_prefix(&walker.root)
         .context(error::PathStripPrefix {
           path,
-          prefix: &self.root,
+          prefix: &walker.root,
         })?;
 
       if relative.components().count() == 0 {
         return Err(Error::PathStripEmpty {
-          prefix: self.root.clone(),
+          prefix: walker.root.clone(),
           path: path.to_owned(),
         });
       }
 
-      if !self.pattern_filter(&relative) {
-        continue;
+      let skipped = |reason| {
+        Ok(Some(WalkEntry::Skipped {
+          path: relative.to_owned(),
+          reason,
+        }))
+      };
+
+      if !walker.pattern_filter(&relative) {
+        return skipped(SkipReason::Glob);
       }
 
-      let file_path = FilePath::from_relative_path(relative)?;
+      let mut file_path = FilePath::from_relative_path(relative)?;
 
-      if !self.include_junk && JUNK.contains(&file_path.name()) {
-        continue;
+      if !walker.include_junk && Walker::is_junk(file_path.name()) {
+        return skipped(SkipReason::Junk);
+      }
+
+      if let Some(reason) = walker.metadata_filter(path, &metadata)? {
+        return skipped(reason);
       }
 
-      let len = metadata.len();
-      total_size += len;
+      if walker.reproducible {
+        let normalized = file_path.nfc();
+        if normalized != file_path {
+          self.renamed = true;
+          file_path = normalized;
+        }
+      }
 
-      file_infos.push(FileInfo {
+      return Ok(Some(WalkEntry::File {
         path: file_path,
-        length: Bytes(len),
-        md5sum: None,
-      });
+        source: path.to_owned(),
+        length: Bytes(metadata.len()),
+      }));
     }
 
-    file_infos.sort_by(|a, b| SortSpec::compare(&self.sort_by, a, b));
-
-    Ok(Files::dir(
-      self.root,
-      Bytes::from(total_size),
-      file_infos
-        .into_iter()
-        .map(|file_info| file_info.path)
-        .collect(),
-    ))
+    Ok(None)
   }
+}
 
-  fn pattern_filter(&self, relative: &Path) -> bool {
-    for Pattern { glob, include } in self.patterns.iter().rev() {
-      if glob.is_match(relative) {
-        return *include;
-      }
-    }
-
-    if let Some(Pattern { include, .. }) = self.patterns.first() {
-      return !include;
-    }
+impl Iterator for FileStream {
+  type Item = Result<WalkEntry, Error>;
 
-    true
+  fn next(&mut self) -> Option<Self::Item> {
+    self.next_entry().transpose()
   }
 }
 
@@ -200,4 +485,206 @@ mod tests {
     assert!(walker.pattern_filter(Path::new("b")));
     assert!(walker.pattern_filter(Path::new("c")));
   }
+
+  #[test]
+  fn filters_report_skipped_files() {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("a"), "a").unwrap();
+    fs::write(tempdir.path().join("b"), "bb").unwrap();
+    fs::write(tempdir.path().join("c"), "ccc").unwrap();
+    fs::write(tempdir.path().join("Thumbs.db"), "").unwrap();
+
+    let (files, skipped) = Walker::new(tempdir.path())
+      .min_file_size(Some(Bytes(2)))
+      .globs(&["!c".into()])
+      .unwrap()
+      .walk()
+      .unwrap();
+
+    assert_eq!(
+      files.contents().unwrap(),
+      &[FilePath::from_components(&["b"])]
+    );
+    assert_eq!(
+      skipped,
+      &[
+        (PathBuf::from("Thumbs.db"), SkipReason::Junk),
+        (PathBuf::from("a"), SkipReason::Small { min: Bytes(2) }),
+        (PathBuf::from("c"), SkipReason::Glob),
+      ]
+    );
+  }
+
+  #[test]
+  fn modification_time_filters() {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("a"), "a").unwrap();
+    let modified = fs::metadata(tempdir.path().join("a"))
+      .unwrap()
+      .modified()
+      .unwrap();
+
+    let walk = |newer_than, older_than| {
+      Walker::new(tempdir.path())
+        .newer_than(newer_than)
+        .older_than(older_than)
+        .walk()
+        .unwrap()
+        .1
+    };
+
+    assert_eq!(walk(None, None), &[]);
+    assert_eq!(
+      walk(Some(modified), None),
+      &[(PathBuf::from("a"), SkipReason::Old)]
+    );
+    assert_eq!(
+      walk(None, Some(modified)),
+      &[(PathBuf::from("a"), SkipReason::New)]
+    );
+    assert_eq!(
+      walk(
+        Some(modified - Duration::from_secs(1)),
+        Some(modified + Duration::from_secs(1))
+      ),
+      &[]
+    );
+  }
+
+  #[test]
+  #[cfg(target_os = "linux")]
+  fn reproducible_normalizes_paths() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let source = tempdir.path().join("a\u{308}");
+    fs::write(&source, "abc").unwrap();
+
+    let files = Walker::new(tempdir.path())
+      .reproducible(true)
+      .walk()
+      .unwrap()
+      .0;
+
+    let path = FilePath::from_components(&["\u{e4}"]);
+    assert_eq!(files.contents().unwrap(), &[path.clone()]);
+    assert_eq!(files.source(&path), source);
+  }
+
+  fn stream_paths(files: Files) -> Vec<FilePath> {
+    match files.into_contents() {
+      Contents::Stream(stream) => stream
+        .filter_map(|entry| match entry.unwrap() {
+          WalkEntry::File { path, .. } => Some(path),
+          WalkEntry::Skipped { .. } => None,
+        })
+        .collect(),
+      Contents::File | Contents::List { .. } => panic!("Expected streamed files"),
+    }
+  }
+
+  #[test]
+  fn stream_order_matches_sorted_order() {
+    let tempdir = temptree! {
+      a: {
+        x: "",
+        "y z": "",
+      },
+      "a b": "",
+      "a-": {
+        c: "",
+      },
+      b: {
+        c: {
+          d: "",
+        },
+        "c.": "",
+      },
+      "B": "",
+      "Thumbs.db": "",
+    };
+
+    let (files, skipped) = Walker::new(tempdir.path()).files(true).unwrap();
+
+    assert_eq!(files.total_size(), None);
+    assert_eq!(skipped, &[]);
+
+    let streamed = stream_paths(files);
+
+    let (files, _skipped) = Walker::new(tempdir.path()).walk().unwrap();
+
+    assert_eq!(streamed, files.contents().unwrap());
+    assert_eq!(streamed.len(), 7);
+  }
+
+  #[test]
+  fn stream_filtered() {
+    let tempdir = temptree! {
+      a: "a",
+      b: "bb",
+      c: "ccc",
+    };
+
+    let (files, skipped) = Walker::new(tempdir.path())
+      .globs(&["!b".into()])
+      .unwrap()
+      .files(true)
+      .unwrap();
+
+    assert_eq!(files.total_size(), None);
+    assert_eq!(skipped, &[]);
+    assert_eq!(
+      stream_paths(files),
+      &[
+        FilePath::from_components(&["a"]),
+        FilePath::from_components(&["c"]),
+      ]
+    );
+  }
+
+  #[test]
+  fn files_sorted_by_size_are_not_streamed() {
+    let tempdir = temptree! {
+      a: "aaa",
+      b: "b",
+    };
+
+    let (files, _skipped) = Walker::new(tempdir.path())
+      .sort_by(vec!["size".parse().unwrap()])
+      .files(true)
+      .unwrap();
+
+    assert!(!files.is_stream());
+    assert_eq!(files.total_size(), Some(Bytes(4)));
+    assert_eq!(
+      files.contents().unwrap(),
+      &[
+        FilePath::from_components(&["b"]),
+        FilePath::from_components(&["a"]),
+      ]
+    );
+  }
+
+  #[test]
+  fn single_file_is_not_streamed() {
+    let tempdir = temptree! {
+      a: "aaa",
+    };
+
+    let (files, _skipped) = Walker::new(&tempdir.path().join("a")).files(true).unwrap();
+
+    assert!(matches!(files.into_contents(), Contents::File));
+  }
+
+  #[test]
+  fn files_are_only_streamed_if_requested() {
+    let tempdir = temptree! {
+      a: "aaa",
+      "Thumbs.db": "",
+    };
+
+    let (files, skipped) = Walker::new(tempdir.path()).files(false).unwrap();
+
+    assert!(!files.is_stream());
+    assert_eq!(files.total_size(), Some(Bytes(3)));
+    assert_eq!(skipped, &[(PathBuf::from("Thumbs.db"), SkipReason::Junk)]);
+  }
 }
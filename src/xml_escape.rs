@@ -0,0 +1,38 @@
+use crate::common::*;
+
+/// Escape `text` for inclusion in XML or HTML, as element content or as a
+/// quoted attribute value.
+pub(crate) fn xml_escape(text: &str) -> Cow<'_, str> {
+  if !text.contains(|c| matches!(c, '&' | '<' | '>' | '"' | '\'')) {
+    return Cow::Borrowed(text);
+  }
+
+  let mut escaped = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      _ => escaped.push(c),
+    }
+  }
+
+  Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn escape() {
+    assert_eq!(xml_escape("foo"), "foo");
+    assert_eq!(
+      xml_escape("<a href=\"x\">Tom & Jerry's</a>"),
+      "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+    );
+  }
+}